@@ -0,0 +1,233 @@
+//! 批量锚定子系统（core::anchor）：证据 hash、裁决摘要（及后续评价 hash）入队，按周期封批、构建 Merkle 树，把根写链并记录交易引用。
+//!
+//! 链接口 ChainAnchor：生产实现向锚定合约提交 (seq, root)；LocalChain 为内存链替身，供开发与测试（ANCHOR_CHAIN=local，默认）。
+//! 周期 ANCHOR_INTERVAL_SECS（默认 3600）；上链失败的批次保留根不变，下轮按序号重试。
+//! GET /api/v1/anchors 批次列表；GET /api/v1/anchors/proof?kind=&ref_id= 包含证明（可用 InclusionProof::verify 离线重算根）；
+//! GET /api/v1/anchors/tx/:tx_hash 查询链上记录的根，用于对照证明；POST /api/v1/ops/anchors/flush 立即封批上链。
+
+use crate::audit::Actor;
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io;
+use std::sync::Mutex;
+use traveltrust_core::anchor::AnchorError;
+use traveltrust_core::{AnchorBatch, AnchorKind, AnchorTx, InclusionProof};
+use uuid::Uuid;
+
+/// 默认封批周期
+const ANCHOR_INTERVAL_SECS: u64 = 3600;
+/// 本地链替身的 chain_id（与 anvil/hardhat 本地链一致）
+const LOCAL_CHAIN_ID: u64 = 31337;
+
+/// 链上记录的锚定根
+#[derive(Debug, Clone, Serialize)]
+pub struct OnChainRoot {
+    pub batch_seq: u64,
+    pub root: String,
+    pub tx: AnchorTx,
+}
+
+#[async_trait]
+pub trait ChainAnchor: Send + Sync {
+    /// 提交批次根，返回已确认的交易引用
+    async fn submit_root(&self, batch_seq: u64, root: &str) -> io::Result<AnchorTx>;
+
+    /// 按交易 hash 读回链上记录
+    async fn lookup(&self, tx_hash: &str) -> io::Result<Option<OnChainRoot>>;
+}
+
+/// 内存链替身：每次提交出一个块，区块时间取本机时间
+#[derive(Default)]
+pub struct LocalChain {
+    blocks: Mutex<Vec<OnChainRoot>>,
+}
+
+#[async_trait]
+impl ChainAnchor for LocalChain {
+    async fn submit_root(&self, batch_seq: u64, root: &str) -> io::Result<AnchorTx> {
+        let mut blocks = self.blocks.lock().unwrap_or_else(|e| e.into_inner());
+        let block_number = blocks.len() as u64 + 1;
+        let tx_hash = format!(
+            "0x{}",
+            hex::encode(Sha256::digest(format!("{}\n{}\n{}\n{}", LOCAL_CHAIN_ID, block_number, batch_seq, root).as_bytes()))
+        );
+        let tx = AnchorTx {
+            chain: "local".to_string(),
+            chain_id: LOCAL_CHAIN_ID,
+            tx_hash,
+            block_number,
+            anchored_at: Utc::now(),
+        };
+        blocks.push(OnChainRoot {
+            batch_seq,
+            root: root.to_string(),
+            tx: tx.clone(),
+        });
+        Ok(tx)
+    }
+
+    async fn lookup(&self, tx_hash: &str) -> io::Result<Option<OnChainRoot>> {
+        let blocks = self.blocks.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(blocks.iter().find(|b| b.tx.tx_hash.eq_ignore_ascii_case(tx_hash)).cloned())
+    }
+}
+
+/// 锚定链与封批互斥锁（周期任务与手动 flush 不并发提交同一批次）
+pub struct Anchoring {
+    pub chain: Box<dyn ChainAnchor>,
+    cycle: tokio::sync::Mutex<()>,
+}
+
+impl Anchoring {
+    /// ANCHOR_CHAIN：目前仅 local；其他值告警并回落本地替身
+    pub fn from_env() -> Self {
+        match env::var("ANCHOR_CHAIN").as_deref() {
+            Ok("local") | Err(_) => {}
+            Ok(other) => eprintln!("ANCHOR_CHAIN={} 尚未接入，使用本地链替身", other),
+        }
+        Self {
+            chain: Box::new(LocalChain::default()),
+            cycle: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+/// 后台周期封批上链
+pub async fn anchor_periodically(state: AppState) {
+    let secs = env::var("ANCHOR_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|s: &u64| *s > 0)
+        .unwrap_or(ANCHOR_INTERVAL_SECS);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(secs));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        run_anchor_cycle(&state, Utc::now()).await;
+    }
+}
+
+/// 封批（队列非空时）并提交全部待上链批次；返回本轮成功上链的批次数
+pub async fn run_anchor_cycle(state: &AppState, now: DateTime<Utc>) -> usize {
+    let _cycle = state.anchoring.cycle.lock().await;
+    let pending = {
+        let mut store = state.store.write().await;
+        if let Some(batch) = store.anchors.seal(now) {
+            eprintln!("[anchor] 封批 seq={} items={} root={}", batch.seq, batch.items.len(), batch.root);
+        }
+        store.anchors.unanchored()
+    };
+    let mut anchored = 0;
+    // 按序号提交；某批失败即停，保持链上序号连续
+    for (batch_id, seq, root) in pending {
+        match state.anchoring.chain.submit_root(seq, &root).await {
+            Ok(tx) => {
+                eprintln!("[anchor] 上链 seq={} root={} chain={} tx={} block={}", seq, root, tx.chain, tx.tx_hash, tx.block_number);
+                let _ = state.store.write().await.anchors.mark_anchored(batch_id, tx);
+                anchored += 1;
+            }
+            Err(e) => {
+                eprintln!("[anchor] 上链失败 seq={}，下轮重试: {}", seq, e);
+                break;
+            }
+        }
+    }
+    anchored
+}
+
+/// 批次摘要（不含条目）
+#[derive(Serialize)]
+pub struct AnchorBatchView {
+    pub id: Uuid,
+    pub seq: u64,
+    pub root: String,
+    pub leaf_count: usize,
+    pub sealed_at: DateTime<Utc>,
+    pub anchor: Option<AnchorTx>,
+}
+
+impl From<&AnchorBatch> for AnchorBatchView {
+    fn from(b: &AnchorBatch) -> Self {
+        Self {
+            id: b.id,
+            seq: b.seq,
+            root: b.root.clone(),
+            leaf_count: b.items.len(),
+            sealed_at: b.sealed_at,
+            anchor: b.anchor.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AnchorOverview {
+    pub queued: usize,
+    pub batches: Vec<AnchorBatchView>,
+}
+
+pub async fn list_anchors(State(state): State<AppState>, _actor: Actor) -> Json<AnchorOverview> {
+    let store = state.store.read().await;
+    Json(AnchorOverview {
+        queued: store.anchors.queued().len(),
+        batches: store.anchors.batches().iter().map(AnchorBatchView::from).collect(),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct ProofQuery {
+    pub kind: AnchorKind,
+    pub ref_id: Uuid,
+}
+
+pub async fn get_inclusion_proof(
+    State(state): State<AppState>,
+    Query(q): Query<ProofQuery>,
+    _actor: Actor,
+) -> ApiResult<Json<InclusionProof>> {
+    let store = state.store.read().await;
+    store.anchors.proof(q.kind, q.ref_id).map(Json).map_err(|e| match e {
+        AnchorError::Queued => ApiError::conflict("anchor_pending", "条目已入队，待下一批次封批上链"),
+        _ => ApiError::not_found("锚定条目"),
+    })
+}
+
+pub async fn get_chain_root(
+    State(state): State<AppState>,
+    Path(tx_hash): Path<String>,
+    _actor: Actor,
+) -> ApiResult<Json<OnChainRoot>> {
+    state
+        .anchoring
+        .chain
+        .lookup(&tx_hash)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, "chain_unavailable", e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("锚定交易"))
+}
+
+#[derive(Serialize)]
+pub struct FlushResult {
+    pub anchored: usize,
+    pub batches: Vec<AnchorBatchView>,
+}
+
+/// 立即封批上链（运营、测试）
+pub async fn flush_anchors(State(state): State<AppState>) -> Json<FlushResult> {
+    let anchored = run_anchor_cycle(&state, Utc::now()).await;
+    let store = state.store.read().await;
+    Json(FlushResult {
+        anchored,
+        batches: store.anchors.batches().iter().map(AnchorBatchView::from).collect(),
+    })
+}
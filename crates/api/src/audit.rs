@@ -0,0 +1,153 @@
+//! 证据访问审计（Runbook §5 访问留痕、04 §四「每次读审计」）：哈希链只追加日志见 traveltrust_core::audit。
+//!
+//! 访问者身份取自鉴权中间件挂载的 Principal（见 auth::auth_layer），未登录即 401；
+//! requestId 取 x-request-id（request_id_layer 保证存在）。导出仅限合规类角色（compliance / legal），由 core::rbac 路由表在鉴权中间件统一校验。
+//! 账号安全事件（注册、登录与会话、密码与邮箱变更见 auth、sessions、siwe，角色指派见 ops，TOTP 启用与使用、step-up 拒绝见 totp）记入另一条哈希链，经 /api/v1/ops/security-log/export 导出。
+
+use crate::auth::{self, Principal};
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{request::Parts, HeaderMap},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use traveltrust_core::{AccessAction, AccessLogEntry, SecurityEvent, SecurityLogEntry, UserRole};
+use uuid::Uuid;
+
+/// 发起请求的访问者
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub id: Uuid,
+    pub role: UserRole,
+    pub request_id: String,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> ApiResult<Self> {
+        let principal = parts.extensions.get::<Principal>().ok_or_else(auth::unauthenticated)?;
+        Ok(Self {
+            id: principal.user_id,
+            role: principal.role.clone(),
+            request_id: request_id(&parts.headers),
+        })
+    }
+}
+
+/// 请求的 x-request-id（未登录的入口如 /auth/login 亦需记审计）
+pub fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 逐条追加访问记录
+pub async fn record_access(state: &AppState, actor: &Actor, evidence_ids: &[Uuid], action: AccessAction) {
+    let now = Utc::now();
+    let mut log = state.audit.write().await;
+    for evidence_id in evidence_ids {
+        let entry = log.append(
+            *evidence_id,
+            actor.id,
+            actor.role.clone(),
+            action,
+            actor.request_id.clone(),
+            now,
+        );
+        eprintln!(
+            "[audit] seq={} evidence={} actor={} role={} action={} x-request-id={}",
+            entry.seq,
+            entry.evidence_id,
+            entry.actor_id,
+            entry.role.as_str(),
+            entry.action.as_str(),
+            entry.request_id
+        );
+    }
+}
+
+/// 追加一条账号安全事件
+pub async fn record_security(
+    state: &AppState,
+    actor_id: Uuid,
+    role: &UserRole,
+    event: SecurityEvent,
+    detail: String,
+    request_id: String,
+) {
+    let mut log = state.security_log.write().await;
+    let entry = log.append(actor_id, role.clone(), event, detail, request_id, Utc::now());
+    eprintln!(
+        "[audit] security seq={} actor={} role={} event={} {} x-request-id={}",
+        entry.seq,
+        entry.actor_id,
+        entry.role.as_str(),
+        entry.event.as_str(),
+        entry.detail,
+        entry.request_id
+    );
+}
+
+#[derive(Serialize)]
+pub struct AuditLogExport<E> {
+    pub exported_at: DateTime<Utc>,
+    /// 链头 hash：导出方留存，用于日后发现尾部截断
+    pub head_hash: String,
+    pub chain_valid: bool,
+    pub chain_error: Option<String>,
+    pub entries: Vec<E>,
+}
+
+/// 法务导出：全量日志 + 链校验结果（路由表限合规类角色）
+pub async fn export_access_log(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResult<Json<AuditLogExport<AccessLogEntry>>> {
+    let log = state.audit.read().await;
+    let verified = log.verify();
+    eprintln!(
+        "[audit] access log exported by {} ({}) entries={} x-request-id={}",
+        actor.id,
+        actor.role.as_str(),
+        log.entries().len(),
+        actor.request_id
+    );
+    Ok(Json(AuditLogExport {
+        exported_at: Utc::now(),
+        head_hash: log.head_hash().to_string(),
+        chain_valid: verified.is_ok(),
+        chain_error: verified.err().map(|e| e.to_string()),
+        entries: log.entries().to_vec(),
+    }))
+}
+
+/// 法务导出：账号安全事件日志 + 链校验结果（路由表限合规类角色）
+pub async fn export_security_log(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResult<Json<AuditLogExport<SecurityLogEntry>>> {
+    let log = state.security_log.read().await;
+    let verified = log.verify();
+    eprintln!(
+        "[audit] security log exported by {} ({}) entries={} x-request-id={}",
+        actor.id,
+        actor.role.as_str(),
+        log.entries().len(),
+        actor.request_id
+    );
+    Ok(Json(AuditLogExport {
+        exported_at: Utc::now(),
+        head_hash: log.head_hash().to_string(),
+        chain_valid: verified.is_ok(),
+        chain_error: verified.err().map(|e| e.to_string()),
+        entries: log.entries().to_vec(),
+    }))
+}
@@ -0,0 +1,1159 @@
+//! 邮箱注册与登录（04 §三 3.1、01 §2 用户与账号）：argon2id 存密码，短时 JWT 访问令牌 + 轮换刷新令牌（规则见 traveltrust_core::auth）。
+//!
+//! POST /auth/register {email, password, nickname?, role?}：role 仅可自选 tourist / guide，仲裁员与合规类角色由运营经
+//! /api/v1/ops/users/:id/role 指派。POST /auth/login 返回 access_token（15 分钟）与 refresh_token（30 天，仅此一次明文）。
+//! POST /auth/refresh 轮换刷新令牌；已轮换的旧令牌被重复使用时吊销整个会话。POST /auth/logout 吊销当前会话（all=true 吊销全部会话）。
+//! 会话记录登录设备与最近活跃，经 /api/v1/me/sessions 查看与逐个吊销（见 sessions）。
+//! 鉴权中间件（auth_layer）：校验 Authorization: Bearer，会话已吊销或用户不存在即 401；通过后把 Principal 挂到请求上，
+//! 角色以当前用户记录为准（运营改角色即时生效）。公开路由与各路由允许角色见 core::rbac 路由表（角色不符 403，见 rbac）。
+//! 运营账号：BOOTSTRAP_OPERATOR_EMAILS（逗号分隔）中的邮箱完成邮箱验证后自动获得 operator 角色，其余运营由已有运营指派。
+//! 邮箱验证与密码重置（一次性、限时、只存 sha256 的令牌，经 Mailer 发信）：注册后发送验证邮件，POST /auth/verify-email {token}
+//! 置 email_verified_at；POST /auth/forgot-password {email} 恒返回 202（不暴露邮箱是否注册）；POST /auth/reset-password {token, new_password}
+//! 与 PUT /api/v1/me/password {current_password, new_password} 改密后吊销该用户全部会话并发送通知邮件。
+//! PUT /api/v1/me/email {new_email, current_password} 更换邮箱：新邮箱需重新验证，吊销全部会话并通知原邮箱。
+//! 钱包登录（SIWE，见 siwe）与邮箱登录经 start_session 签发同一种会话；钱包账号可经 POST /api/v1/me/email {email, password} 关联邮箱
+//! （视同邮箱变更，同样吊销全部会话），邮箱账号经 /api/v1/me/wallet 绑定钱包（见 wallets）后即可用该钱包登录。
+//! 两步验证（见 totp）：已启用 TOTP 的账号登录须附 totp_code 或 recovery_code；中间件对仲裁员/运营强制启用并校验高风险路由的 step-up。
+//! 签名密钥来自 env JWT_SECRET（至少 32 字节）；未设时启动生成临时密钥，仅用于开发（重启后令牌全部失效）。
+
+use crate::audit;
+use crate::error::{ApiError, ApiResult};
+use crate::mailer::{self, OutgoingMail};
+use crate::sessions::{self, ClientContext};
+use crate::state::AppState;
+use crate::store::Store;
+use crate::totp::{self, SecondFactorInput};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::sync::OnceLock;
+use traveltrust_core::auth::{
+    self, OneTimeTokenError, RefreshError, RESET_PASSWORD_TTL_MINUTES, SESSION_TOUCH_INTERVAL_SECS, VERIFY_EMAIL_TTL_HOURS,
+};
+use traveltrust_core::{profile, rbac};
+use traveltrust_core::{KycStatus, OneTimePurpose, SecondFactor, SecurityEvent, User, UserRole};
+use uuid::Uuid;
+
+/// 访问令牌有效期
+const ACCESS_TOKEN_TTL_SECS: i64 = 15 * 60;
+/// 刷新令牌有效期
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const JWT_SECRET_MIN_LEN: usize = 32;
+const JWT_ISSUER: &str = "traveltrust";
+
+/// 账号：领域 User + 凭证
+#[derive(Debug, Clone)]
+pub struct UserAccount {
+    pub user: User,
+    /// argon2id PHC 字符串
+    pub password_hash: String,
+}
+
+/// 已认证的请求主体（auth_layer 挂载）
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: Uuid,
+    pub role: UserRole,
+    /// 会话 id（刷新令牌 family）
+    pub session_id: Uuid,
+    /// 账号已启用 TOTP
+    pub totp_enabled: bool,
+    /// 当前会话 step-up 有效期截止（无或已过期为 None）
+    pub step_up_until: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> ApiResult<Self> {
+        parts.extensions.get::<Principal>().cloned().ok_or_else(unauthenticated)
+    }
+}
+
+pub(crate) fn unauthenticated() -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED, "unauthenticated", "需要登录")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: Uuid,
+    role: UserRole,
+    sid: Uuid,
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+pub struct TokenIssuer {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+}
+
+impl TokenIssuer {
+    pub fn from_env() -> Self {
+        let secret = match env::var("JWT_SECRET") {
+            Ok(s) if s.len() >= JWT_SECRET_MIN_LEN => s.into_bytes(),
+            _ => {
+                eprintln!("JWT_SECRET 未设置或短于 32 字节，使用临时令牌签名密钥（仅建议用于开发）");
+                let mut secret = vec![0u8; JWT_SECRET_MIN_LEN];
+                rand::rngs::OsRng.fill_bytes(&mut secret);
+                secret
+            }
+        };
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[JWT_ISSUER]);
+        validation.leeway = 0;
+        Self {
+            encoding: EncodingKey::from_secret(&secret),
+            decoding: DecodingKey::from_secret(&secret),
+            validation,
+        }
+    }
+
+    fn issue(&self, user_id: Uuid, role: UserRole, session_id: Uuid, now: DateTime<Utc>) -> (String, DateTime<Utc>) {
+        let expires_at = now + Duration::seconds(ACCESS_TOKEN_TTL_SECS);
+        let claims = Claims {
+            sub: user_id,
+            role,
+            sid: session_id,
+            iat: now.timestamp(),
+            exp: expires_at.timestamp(),
+            iss: JWT_ISSUER.to_string(),
+        };
+        let token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+            .expect("HS256 编码不会失败");
+        (token, expires_at)
+    }
+
+    fn verify(&self, token: &str) -> Option<Claims> {
+        jsonwebtoken::decode::<Claims>(token, &self.decoding, &self.validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// 鉴权中间件：有 Bearer 即校验（无效一律 401）；再按路由权限表（core::rbac）校验登录与角色，不在表内的路由需登录
+pub async fn auth_layer(State(state): State<AppState>, mut req: Request<Body>, next: Next<Body>) -> Response {
+    let bearer = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.strip_prefix("Bearer ").map(str::trim).map(String::from));
+    let principal = match bearer {
+        Some(Some(token)) => match authenticate(&state, &token, &ClientContext::from_request_parts(req.headers(), req.extensions())).await {
+            Some(principal) => Some(principal),
+            None => return ApiError::new(StatusCode::UNAUTHORIZED, "invalid_token", "访问令牌无效、过期或会话已吊销").into_response(),
+        },
+        Some(None) => return ApiError::new(StatusCode::UNAUTHORIZED, "invalid_token", "Authorization 须为 Bearer 令牌").into_response(),
+        None => None,
+    };
+    let policy = rbac::route_policy(req.method().as_str(), req.uri().path());
+    let requires_login = policy.is_none_or(|p| p.audience.requires_login());
+    match (&principal, policy) {
+        (None, _) if requires_login => return unauthenticated().into_response(),
+        (Some(p), Some(policy)) if !policy.audience.permits(Some(&p.role)) => {
+            return crate::rbac::forbidden_role(policy, &p.role).into_response();
+        }
+        _ => {}
+    }
+    if let (Some(p), Some(policy)) = (&principal, policy) {
+        if let Err(e) = totp::enforce(&state, p, policy, audit::request_id(req.headers())).await {
+            return e.into_response();
+        }
+    }
+    if let Some(principal) = principal {
+        req.extensions_mut().insert(principal);
+    }
+    next.run(req).await
+}
+
+async fn authenticate(state: &AppState, token: &str, client: &ClientContext) -> Option<Principal> {
+    let claims = state.tokens.verify(token)?;
+    let now = Utc::now();
+    let (principal, stale) = {
+        let store = state.store.read().await;
+        let session = store.refresh_tokens.session(claims.sid).filter(|s| s.revoked_at.is_none())?;
+        let stale = now - session.last_seen_at >= Duration::seconds(SESSION_TOUCH_INTERVAL_SECS)
+            || (client.ip.is_some() && session.last_seen_ip != client.ip);
+        let account = store.users.get(&claims.sub)?;
+        let principal = Principal {
+            user_id: claims.sub,
+            role: account.user.role.clone(),
+            session_id: claims.sid,
+            totp_enabled: store.totp.is_enabled(claims.sub),
+            step_up_until: store.totp.step_up_valid_until(claims.sid, now),
+        };
+        (principal, stale)
+    };
+    // 最近活跃至多每分钟（或 IP 变化时）写一次
+    if stale {
+        let mut store = state.store.write().await;
+        store.refresh_tokens.touch(claims.sid, client.ip.clone(), now);
+        sessions::record_activity(&mut store, claims.sub, client, false, now);
+    }
+    Some(principal)
+}
+
+/// 刷新令牌与一次性令牌的存储形式
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 256 位随机不透明令牌（十六进制）
+fn new_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// argon2id（默认参数 m=19MiB, t=2, p=1），在阻塞线程池执行
+async fn hash_password(password: String) -> ApiResult<String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default().hash_password(password.as_bytes(), &salt).map(|h| h.to_string())
+    })
+    .await
+    .ok()
+    .and_then(Result::ok)
+    .ok_or_else(|| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", "密码处理失败"))
+}
+
+/// 校验密码；账号不存在时对哑哈希做同样计算，避免按耗时枚举邮箱
+async fn verify_password(password: String, hash: Option<String>) -> bool {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    tokio::task::spawn_blocking(move || {
+        let found = hash.is_some();
+        let hash = hash.unwrap_or_else(|| {
+            DUMMY_HASH
+                .get_or_init(|| {
+                    let salt = SaltString::generate(&mut OsRng);
+                    Argon2::default()
+                        .hash_password(b"dummy-password-for-timing", &salt)
+                        .map(|h| h.to_string())
+                        .unwrap_or_default()
+                })
+                .clone()
+        });
+        let ok = PasswordHash::new(&hash)
+            .is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok());
+        found && ok
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[derive(Deserialize)]
+pub struct RegisterRequest {
+    pub email: String,
+    pub password: String,
+    pub nickname: Option<String>,
+    pub role: Option<UserRole>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountView {
+    pub id: Uuid,
+    /// 仅以钱包（SIWE）登录、尚未关联邮箱的账号为 None
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub nickname: Option<String>,
+    pub avatar_url: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// 当前绑定钱包（见 wallets）
+    pub default_wallet_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&UserAccount> for AccountView {
+    fn from(a: &UserAccount) -> Self {
+        Self {
+            id: a.user.id,
+            email: Some(a.user.email.clone()).filter(|e| !e.is_empty()),
+            role: a.user.role.clone(),
+            nickname: a.user.nickname.clone(),
+            avatar_url: a.user.avatar_url.clone(),
+            email_verified_at: a.user.email_verified_at,
+            default_wallet_address: a.user.default_wallet_address.clone(),
+            created_at: a.user.created_at,
+            updated_at: a.user.updated_at,
+        }
+    }
+}
+
+pub async fn register(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RegisterRequest>,
+) -> ApiResult<(StatusCode, Json<AccountView>)> {
+    let email = auth::normalize_email(&req.email).map_err(|e| ApiError::bad_request("invalid_email", e.to_string()))?;
+    auth::validate_password(&req.password, &email).map_err(|e| ApiError::bad_request("weak_password", e.to_string()))?;
+    let nickname = match req.nickname.as_deref() {
+        Some(n) => profile::normalize_nickname(n).map_err(|e| ApiError::bad_request("invalid_nickname", e.to_string()))?,
+        None => None,
+    };
+    let role = req.role.unwrap_or(UserRole::Tourist);
+    if !matches!(role, UserRole::Tourist | UserRole::Guide) {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "role_not_self_assignable", "该角色须由运营指派"));
+    }
+    if state.store.read().await.user_emails.contains_key(&email) {
+        return Err(ApiError::conflict("email_taken", "该邮箱已注册"));
+    }
+    let password_hash = hash_password(req.password).await?;
+
+    let mut store = state.store.write().await;
+    // 哈希期间可能已被并发注册
+    if store.user_emails.contains_key(&email) {
+        return Err(ApiError::conflict("email_taken", "该邮箱已注册"));
+    }
+    let now = Utc::now();
+    let account = UserAccount {
+        user: User {
+            id: Uuid::new_v4(),
+            email: email.clone(),
+            role,
+            kyc_status: KycStatus::None,
+            created_at: now,
+            email_verified_at: None,
+            default_wallet_address: None,
+            nickname,
+            avatar_url: None,
+            updated_at: now,
+        },
+        password_hash,
+    };
+    store.user_emails.insert(email, account.user.id);
+    store.users.insert(account.user.id, account.clone());
+    let token = issue_one_time(&mut store, OneTimePurpose::VerifyEmail, &account.user);
+    drop(store);
+    audit::record_security(
+        &state,
+        account.user.id,
+        &account.user.role,
+        SecurityEvent::Registered,
+        String::new(),
+        audit::request_id(&headers),
+    )
+    .await;
+    send_mail(&state, verification_mail(&account.user.email, &token)).await;
+    Ok((StatusCode::CREATED, Json(AccountView::from(&account))))
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+    /// 已启用 TOTP 时必填其一
+    #[serde(flatten)]
+    pub second_factor: SecondFactorInput,
+}
+
+#[derive(Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+    pub session_id: Uuid,
+}
+
+fn invalid_credentials() -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED, "invalid_credentials", "邮箱或密码错误")
+}
+
+pub async fn login(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    client: ClientContext,
+    Json(req): Json<LoginRequest>,
+) -> ApiResult<Json<TokenPair>> {
+    let email = auth::normalize_email(&req.email).map_err(|_| invalid_credentials())?;
+    let account = {
+        let store = state.store.read().await;
+        store.user_emails.get(&email).and_then(|id| store.users.get(id)).cloned()
+    };
+    if !verify_password(req.password, account.as_ref().map(|a| a.password_hash.clone())).await {
+        return Err(invalid_credentials());
+    }
+    let account = account.ok_or_else(invalid_credentials)?;
+    let factor = totp::verify_login_factor(&state, &account.user, &req.second_factor, audit::request_id(&headers)).await?;
+
+    let tokens = start_session(&state, &mut *state.store.write().await, &account.user, factor, &client, Utc::now());
+    audit::record_security(
+        &state,
+        account.user.id,
+        &account.user.role,
+        SecurityEvent::LoggedIn,
+        format!("method=password session={}", tokens.session_id),
+        audit::request_id(&headers),
+    )
+    .await;
+    Ok(Json(tokens))
+}
+
+/// 新建会话（刷新令牌 family）并签发令牌对，记录登录设备并上报 Sybil 信号；邮箱登录与 SIWE 登录共用。
+/// 以 TOTP 通过第二因素的会话同时获得 step-up
+pub(crate) fn start_session(
+    state: &AppState,
+    store: &mut Store,
+    user: &User,
+    factor: Option<SecondFactor>,
+    client: &ClientContext,
+    now: DateTime<Utc>,
+) -> TokenPair {
+    let refresh_token = new_opaque_token();
+    let refresh_expires_at = now + Duration::days(REFRESH_TOKEN_TTL_DAYS);
+    store.refresh_tokens.prune(now);
+    let session_id = store.refresh_tokens.start_family(
+        user.id,
+        hash_token(&refresh_token),
+        client.device(),
+        now,
+        refresh_expires_at,
+    );
+    sessions::record_activity(store, user.id, client, true, now);
+    if factor == Some(SecondFactor::Totp) {
+        store.totp.record_step_up(session_id, now);
+    }
+    let (access_token, expires_at) = state.tokens.issue(user.id, user.role.clone(), session_id, now);
+    TokenPair {
+        access_token,
+        token_type: "Bearer",
+        expires_at,
+        refresh_token,
+        refresh_expires_at,
+        session_id,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+pub async fn refresh(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    client: ClientContext,
+    Json(req): Json<RefreshRequest>,
+) -> ApiResult<Json<TokenPair>> {
+    let now = Utc::now();
+    let refresh_token = new_opaque_token();
+    let refresh_expires_at = now + Duration::days(REFRESH_TOKEN_TTL_DAYS);
+    let (old, role) = {
+        let mut store = state.store.write().await;
+        let rotated = store.refresh_tokens.rotate(
+            &hash_token(req.refresh_token.trim()),
+            hash_token(&refresh_token),
+            now,
+            refresh_expires_at,
+        );
+        let old = match rotated {
+            Ok(old) => old,
+            Err(RefreshError::Reused { family_id, user_id }) => {
+                let role = store.users.get(&user_id).map(|a| a.user.role.clone());
+                drop(store);
+                if let Some(role) = role {
+                    audit::record_security(
+                        &state,
+                        user_id,
+                        &role,
+                        SecurityEvent::RefreshTokenReused,
+                        format!("session={}", family_id),
+                        audit::request_id(&headers),
+                    )
+                    .await;
+                }
+                return Err(ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "refresh_token_reused",
+                    "刷新令牌已被使用过，会话已吊销，请重新登录",
+                ));
+            }
+            Err(e) => return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid_refresh_token", e.to_string())),
+        };
+        let Some(account) = store.users.get(&old.user_id) else {
+            store.refresh_tokens.revoke_family(old.family_id, "user_missing", now);
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid_refresh_token", "账号不存在"));
+        };
+        let role = account.user.role.clone();
+        store.refresh_tokens.touch(old.family_id, client.ip.clone(), now);
+        sessions::record_activity(&mut store, old.user_id, &client, false, now);
+        (old, role)
+    };
+    let (access_token, expires_at) = state.tokens.issue(old.user_id, role, old.family_id, now);
+    Ok(Json(TokenPair {
+        access_token,
+        token_type: "Bearer",
+        expires_at,
+        refresh_token,
+        refresh_expires_at,
+        session_id: old.family_id,
+    }))
+}
+
+#[derive(Deserialize, Default)]
+pub struct LogoutRequest {
+    /// 吊销该用户全部会话
+    #[serde(default)]
+    pub all: bool,
+}
+
+pub async fn logout(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    body: Option<Json<LogoutRequest>>,
+) -> StatusCode {
+    let all = body.is_some_and(|Json(b)| b.all);
+    let now = Utc::now();
+    let detail = {
+        let mut store = state.store.write().await;
+        if all {
+            let n = store.refresh_tokens.revoke_user(principal.user_id, "logout_all", now);
+            format!("scope=all sessions={}", n)
+        } else {
+            store.refresh_tokens.revoke_family(principal.session_id, "logout", now);
+            format!("scope=current session={}", principal.session_id)
+        }
+    };
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::LoggedOut,
+        detail,
+        audit::request_id(&headers),
+    )
+    .await;
+    StatusCode::NO_CONTENT
+}
+
+/// 签发一次性令牌，返回明文（仅写入邮件）
+fn issue_one_time(store: &mut Store, purpose: OneTimePurpose, user: &User) -> String {
+    let now = Utc::now();
+    let ttl = match purpose {
+        OneTimePurpose::VerifyEmail => Duration::hours(VERIFY_EMAIL_TTL_HOURS),
+        OneTimePurpose::ResetPassword => Duration::minutes(RESET_PASSWORD_TTL_MINUTES),
+    };
+    let token = new_opaque_token();
+    store.one_time_tokens.prune(now);
+    store
+        .one_time_tokens
+        .issue(purpose, user.id, &user.email, hash_token(&token), now, now + ttl);
+    token
+}
+
+fn one_time_error(e: OneTimeTokenError) -> ApiError {
+    let code = match e {
+        OneTimeTokenError::Unknown => "token_invalid",
+        OneTimeTokenError::Expired => "token_expired",
+        OneTimeTokenError::Used => "token_used",
+    };
+    ApiError::bad_request(code, e.to_string())
+}
+
+/// 发信失败只记日志：账号操作已生效，用户可重新申请
+async fn send_mail(state: &AppState, mail: OutgoingMail) {
+    match state.mailer.send(&mail).await {
+        Ok(()) => eprintln!("[mail] 已发送 kind={} id={}", mail.kind, mail.id),
+        Err(e) => eprintln!("[mail] 发送失败 kind={} id={}: {}", mail.kind, mail.id, e),
+    }
+}
+
+fn verification_mail(to: &str, token: &str) -> OutgoingMail {
+    OutgoingMail::new(
+        to,
+        "verify_email",
+        "验证你的 TravelTrust 邮箱",
+        format!(
+            "请在 {} 小时内打开以下链接完成邮箱验证：\n{}\n\n验证码：{}\n如非本人操作请忽略本邮件。",
+            VERIFY_EMAIL_TTL_HOURS,
+            mailer::app_link("/verify-email", token),
+            token
+        ),
+    )
+}
+
+fn reset_mail(to: &str, token: &str) -> OutgoingMail {
+    OutgoingMail::new(
+        to,
+        "reset_password",
+        "重置你的 TravelTrust 密码",
+        format!(
+            "请在 {} 分钟内打开以下链接设置新密码：\n{}\n\n重置码：{}\n如非本人操作请忽略本邮件，原密码仍然有效。",
+            RESET_PASSWORD_TTL_MINUTES,
+            mailer::app_link("/reset-password", token),
+            token
+        ),
+    )
+}
+
+fn password_changed_mail(to: &str) -> OutgoingMail {
+    OutgoingMail::new(
+        to,
+        "password_changed",
+        "你的 TravelTrust 密码已修改",
+        "你的账号密码刚刚被修改，所有设备已退出登录。如非本人操作，请立即通过「忘记密码」重置并联系客服。",
+    )
+}
+
+fn email_changed_mail(to: &str, new_email: &str) -> OutgoingMail {
+    OutgoingMail::new(
+        to,
+        "email_changed",
+        "你的 TravelTrust 登录邮箱已更换",
+        format!(
+            "你的账号登录邮箱刚刚更换为 {}，所有设备已退出登录。如非本人操作，请立即联系客服。",
+            new_email
+        ),
+    )
+}
+
+/// BOOTSTRAP_OPERATOR_EMAILS：验证邮箱后自动获得 operator 角色的初始运营邮箱
+fn is_bootstrap_operator(email: &str) -> bool {
+    env::var("BOOTSTRAP_OPERATOR_EMAILS").is_ok_and(|list| {
+        list.split(',')
+            .filter_map(|e| auth::normalize_email(e).ok())
+            .any(|e| e == email)
+    })
+}
+
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    pub token: String,
+}
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<TokenRequest>,
+) -> ApiResult<Json<AccountView>> {
+    let now = Utc::now();
+    let mut store = state.store.write().await;
+    let token = store
+        .one_time_tokens
+        .consume(&hash_token(req.token.trim()), OneTimePurpose::VerifyEmail, now)
+        .map_err(one_time_error)?;
+    let account = store
+        .users
+        .get_mut(&token.user_id)
+        .filter(|a| a.user.email == token.email)
+        .ok_or_else(|| one_time_error(OneTimeTokenError::Unknown))?;
+    let mut events = Vec::new();
+    if account.user.email_verified_at.is_none() {
+        account.user.email_verified_at = Some(now);
+        account.user.updated_at = now;
+        events.push((SecurityEvent::EmailVerified, String::new()));
+        if is_bootstrap_operator(&account.user.email) && account.user.role != UserRole::Operator {
+            let previous = std::mem::replace(&mut account.user.role, UserRole::Operator);
+            events.push((
+                SecurityEvent::RoleChanged,
+                format!("from={} to=operator source=bootstrap", previous.as_str()),
+            ));
+        }
+    }
+    let view = AccountView::from(&*account);
+    drop(store);
+    for (event, detail) in events {
+        audit::record_security(&state, view.id, &view.role, event, detail, audit::request_id(&headers)).await;
+    }
+    Ok(Json(view))
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+/// 恒返回 202，不暴露邮箱是否注册
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ForgotPasswordRequest>,
+) -> StatusCode {
+    let Ok(email) = auth::normalize_email(&req.email) else {
+        return StatusCode::ACCEPTED;
+    };
+    let issued = {
+        let mut store = state.store.write().await;
+        let user = store.user_emails.get(&email).and_then(|id| store.users.get(id)).map(|a| a.user.clone());
+        user.map(|user| (issue_one_time(&mut store, OneTimePurpose::ResetPassword, &user), user))
+    };
+    if let Some((token, user)) = issued {
+        audit::record_security(
+            &state,
+            user.id,
+            &user.role,
+            SecurityEvent::PasswordResetRequested,
+            String::new(),
+            audit::request_id(&headers),
+        )
+        .await;
+        send_mail(&state, reset_mail(&user.email, &token)).await;
+    }
+    StatusCode::ACCEPTED
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ResetPasswordRequest>,
+) -> ApiResult<StatusCode> {
+    let token_hash = hash_token(req.token.trim());
+    // 先校验强度再核销，弱密码不消耗令牌
+    let email = {
+        let store = state.store.read().await;
+        let user_id = store.one_time_tokens.peek_user(&token_hash, OneTimePurpose::ResetPassword);
+        user_id.and_then(|id| store.users.get(&id)).map(|a| a.user.email.clone())
+    }
+    .ok_or_else(|| one_time_error(OneTimeTokenError::Unknown))?;
+    auth::validate_password(&req.new_password, &email).map_err(|e| ApiError::bad_request("weak_password", e.to_string()))?;
+    let password_hash = hash_password(req.new_password).await?;
+
+    let now = Utc::now();
+    let mut store = state.store.write().await;
+    let token = store
+        .one_time_tokens
+        .consume(&token_hash, OneTimePurpose::ResetPassword, now)
+        .map_err(one_time_error)?;
+    let account = store
+        .users
+        .get_mut(&token.user_id)
+        .filter(|a| a.user.email == token.email)
+        .ok_or_else(|| one_time_error(OneTimeTokenError::Unknown))?;
+    account.password_hash = password_hash;
+    account.user.updated_at = now;
+    // 能收到重置邮件即证明邮箱归属
+    account.user.email_verified_at.get_or_insert(now);
+    let (to, role) = (account.user.email.clone(), account.user.role.clone());
+    let sessions = store.refresh_tokens.revoke_user(token.user_id, "password_reset", now);
+    drop(store);
+    audit::record_security(
+        &state,
+        token.user_id,
+        &role,
+        SecurityEvent::PasswordReset,
+        format!("revoked_sessions={}", sessions),
+        audit::request_id(&headers),
+    )
+    .await;
+    send_mail(&state, password_changed_mail(&to)).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// 已登录改密：需旧密码；成功后全部会话（含当前）失效，需重新登录
+pub async fn change_password(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<ChangePasswordRequest>,
+) -> ApiResult<StatusCode> {
+    let account = state
+        .store
+        .read()
+        .await
+        .users
+        .get(&principal.user_id)
+        .cloned()
+        .ok_or_else(unauthenticated)?;
+    if !verify_password(req.current_password.clone(), Some(account.password_hash.clone())).await {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "invalid_credentials", "当前密码错误"));
+    }
+    if req.new_password == req.current_password {
+        return Err(ApiError::bad_request("weak_password", "新密码不得与当前密码相同"));
+    }
+    auth::validate_password(&req.new_password, &account.user.email)
+        .map_err(|e| ApiError::bad_request("weak_password", e.to_string()))?;
+    let password_hash = hash_password(req.new_password).await?;
+
+    let now = Utc::now();
+    let mut store = state.store.write().await;
+    let account = store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?;
+    account.password_hash = password_hash;
+    account.user.updated_at = now;
+    let to = account.user.email.clone();
+    store.one_time_tokens.invalidate(principal.user_id, OneTimePurpose::ResetPassword, now);
+    let sessions = store.refresh_tokens.revoke_user(principal.user_id, "password_changed", now);
+    drop(store);
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::PasswordChanged,
+        format!("revoked_sessions={}", sessions),
+        audit::request_id(&headers),
+    )
+    .await;
+    send_mail(&state, password_changed_mail(&to)).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct LinkEmailRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// 钱包（SIWE）账号关联邮箱与密码，之后两种方式均可登录；已有邮箱的账号不适用（409 email_already_set，改用 PUT 更换）。
+/// 视同邮箱变更：全部会话（含当前）失效，需重新登录
+pub async fn link_email(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<LinkEmailRequest>,
+) -> ApiResult<Json<AccountView>> {
+    let email = auth::normalize_email(&req.email).map_err(|e| ApiError::bad_request("invalid_email", e.to_string()))?;
+    auth::validate_password(&req.password, &email).map_err(|e| ApiError::bad_request("weak_password", e.to_string()))?;
+    let password_hash = hash_password(req.password).await?;
+
+    let mut store = state.store.write().await;
+    if store.user_emails.contains_key(&email) {
+        return Err(ApiError::conflict("email_taken", "该邮箱已注册"));
+    }
+    let account = store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?;
+    if !account.user.email.is_empty() {
+        return Err(ApiError::conflict("email_already_set", "账号已关联邮箱"));
+    }
+    account.user.email = email.clone();
+    account.password_hash = password_hash;
+    account.user.updated_at = Utc::now();
+    let account = account.clone();
+    store.user_emails.insert(email, principal.user_id);
+    let token = issue_one_time(&mut store, OneTimePurpose::VerifyEmail, &account.user);
+    let sessions = store.refresh_tokens.revoke_user(principal.user_id, "email_changed", Utc::now());
+    drop(store);
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::EmailLinked,
+        format!("revoked_sessions={}", sessions),
+        audit::request_id(&headers),
+    )
+    .await;
+    send_mail(&state, verification_mail(&account.user.email, &token)).await;
+    Ok(Json(AccountView::from(&account)))
+}
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    pub new_email: String,
+    pub current_password: String,
+}
+
+/// 已登录更换邮箱：需当前密码；新邮箱待验证，旧邮箱的验证/重置令牌作废，全部会话（含当前）失效，并通知原邮箱
+pub async fn change_email(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<ChangeEmailRequest>,
+) -> ApiResult<Json<AccountView>> {
+    let email = auth::normalize_email(&req.new_email).map_err(|e| ApiError::bad_request("invalid_email", e.to_string()))?;
+    let account = state
+        .store
+        .read()
+        .await
+        .users
+        .get(&principal.user_id)
+        .cloned()
+        .ok_or_else(unauthenticated)?;
+    if account.user.email.is_empty() {
+        return Err(ApiError::conflict("email_not_set", "账号尚未关联邮箱，请先关联"));
+    }
+    if !verify_password(req.current_password, Some(account.password_hash.clone())).await {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "invalid_credentials", "当前密码错误"));
+    }
+    if email == account.user.email {
+        return Err(ApiError::bad_request("email_unchanged", "新邮箱与当前邮箱相同"));
+    }
+
+    let now = Utc::now();
+    let mut store = state.store.write().await;
+    if store.user_emails.contains_key(&email) {
+        return Err(ApiError::conflict("email_taken", "该邮箱已注册"));
+    }
+    let account = store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?;
+    let old_email = std::mem::replace(&mut account.user.email, email.clone());
+    account.user.email_verified_at = None;
+    account.user.updated_at = now;
+    let account = account.clone();
+    store.user_emails.remove(&old_email);
+    store.user_emails.insert(email, principal.user_id);
+    store.one_time_tokens.invalidate(principal.user_id, OneTimePurpose::VerifyEmail, now);
+    store.one_time_tokens.invalidate(principal.user_id, OneTimePurpose::ResetPassword, now);
+    let token = issue_one_time(&mut store, OneTimePurpose::VerifyEmail, &account.user);
+    let sessions = store.refresh_tokens.revoke_user(principal.user_id, "email_changed", now);
+    drop(store);
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::EmailChanged,
+        format!("revoked_sessions={}", sessions),
+        audit::request_id(&headers),
+    )
+    .await;
+    send_mail(&state, email_changed_mail(&old_email, &account.user.email)).await;
+    send_mail(&state, verification_mail(&account.user.email, &token)).await;
+    Ok(Json(AccountView::from(&account)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailer::OutboxMailer;
+    use serde_json::Value;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    const PASSWORD: &str = "Blue-Horse-42";
+
+    /// 独立发件箱目录的 AppState
+    fn test_state() -> (AppState, PathBuf) {
+        let outbox = env::temp_dir().join(format!("traveltrust-outbox-{}", Uuid::new_v4()));
+        let mut state = AppState::from_env();
+        state.mailer = Arc::new(OutboxMailer::new(outbox.clone()));
+        (state, outbox)
+    }
+
+    /// 发件箱中的邮件，按发送顺序
+    fn outbox_mails(dir: &Path) -> Vec<Value> {
+        let mut names: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        names.retain(|p| p.extension().is_some_and(|e| e == "json") && !p.file_name().unwrap().to_string_lossy().starts_with('.'));
+        names.sort();
+        names
+            .iter()
+            .map(|p| serde_json::from_slice(&std::fs::read(p).unwrap()).unwrap())
+            .collect()
+    }
+
+    /// 最近一封 kind 邮件中链接携带的令牌
+    fn mailed_token(dir: &Path, kind: &str, to: &str) -> String {
+        let mail = outbox_mails(dir)
+            .into_iter()
+            .rev()
+            .find(|m| m["kind"] == kind && m["to"] == to)
+            .unwrap_or_else(|| panic!("发件箱中没有 {} 邮件", kind));
+        let body = mail["body"].as_str().unwrap();
+        let start = body.find("?token=").unwrap() + "?token=".len();
+        body[start..].lines().next().unwrap().trim().to_string()
+    }
+
+    fn mail_kinds(dir: &Path) -> Vec<(String, String)> {
+        outbox_mails(dir)
+            .iter()
+            .map(|m| (m["kind"].as_str().unwrap().to_string(), m["to"].as_str().unwrap().to_string()))
+            .collect()
+    }
+
+    async fn register_user(state: &AppState, email: &str) -> AccountView {
+        let (status, Json(view)) = register(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(RegisterRequest {
+                email: email.into(),
+                password: PASSWORD.into(),
+                nickname: None,
+                role: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        view
+    }
+
+    async fn principal_with_sessions(state: &AppState, user_id: Uuid, sessions: usize) -> Principal {
+        let mut store = state.store.write().await;
+        let user = store.users[&user_id].user.clone();
+        let mut current = None;
+        for _ in 0..sessions {
+            let tokens = start_session(state, &mut store, &user, None, &ClientContext::default(), Utc::now());
+            current.get_or_insert(tokens.session_id);
+        }
+        Principal {
+            user_id,
+            role: user.role,
+            session_id: current.unwrap(),
+            totp_enabled: false,
+            step_up_until: None,
+        }
+    }
+
+    async fn active_sessions(state: &AppState, user_id: Uuid) -> usize {
+        state.store.read().await.refresh_tokens.active_sessions(user_id, Utc::now()).len()
+    }
+
+    #[tokio::test]
+    async fn verify_email_token_from_outbox_is_single_use() {
+        let (state, outbox) = test_state();
+        let account = register_user(&state, "walker@example.com").await;
+        assert!(account.email_verified_at.is_none());
+        let token = mailed_token(&outbox, "verify_email", "walker@example.com");
+
+        let Json(verified) = verify_email(State(state.clone()), HeaderMap::new(), Json(TokenRequest { token: token.clone() }))
+            .await
+            .unwrap();
+        assert_eq!(verified.id, account.id);
+        assert!(verified.email_verified_at.is_some());
+
+        let reused = verify_email(State(state.clone()), HeaderMap::new(), Json(TokenRequest { token }))
+            .await
+            .unwrap_err();
+        assert_eq!(reused.code, "token_used");
+        let bogus = verify_email(State(state.clone()), HeaderMap::new(), Json(TokenRequest { token: "0".repeat(64) }))
+            .await
+            .unwrap_err();
+        assert_eq!(bogus.code, "token_invalid");
+        let _ = std::fs::remove_dir_all(outbox);
+    }
+
+    #[tokio::test]
+    async fn reset_password_token_is_single_use_and_revokes_sessions() {
+        let (state, outbox) = test_state();
+        let account = register_user(&state, "reset@example.com").await;
+        principal_with_sessions(&state, account.id, 2).await;
+
+        // 未注册邮箱同样 202 且不发信
+        assert_eq!(
+            forgot_password(State(state.clone()), HeaderMap::new(), Json(ForgotPasswordRequest { email: "nobody@example.com".into() })).await,
+            StatusCode::ACCEPTED
+        );
+        assert!(!mail_kinds(&outbox).iter().any(|(_, to)| to == "nobody@example.com"));
+        assert_eq!(
+            forgot_password(State(state.clone()), HeaderMap::new(), Json(ForgotPasswordRequest { email: "Reset@Example.com".into() })).await,
+            StatusCode::ACCEPTED
+        );
+        let token = mailed_token(&outbox, "reset_password", "reset@example.com");
+
+        // 弱密码不消耗令牌
+        let weak = reset_password(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(ResetPasswordRequest { token: token.clone(), new_password: "short".into() }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(weak.code, "weak_password");
+
+        let status = reset_password(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(ResetPasswordRequest { token: token.clone(), new_password: "Green-Falcon-77".into() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(active_sessions(&state, account.id).await, 0);
+        assert!(mail_kinds(&outbox).contains(&("password_changed".into(), "reset@example.com".into())));
+
+        let reused = reset_password(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(ResetPasswordRequest { token, new_password: "Other-Falcon-88".into() }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(reused.code, "token_invalid");
+        let _ = std::fs::remove_dir_all(outbox);
+    }
+
+    #[tokio::test]
+    async fn change_password_revokes_all_sessions() {
+        let (state, outbox) = test_state();
+        let account = register_user(&state, "change@example.com").await;
+        let principal = principal_with_sessions(&state, account.id, 3).await;
+        assert_eq!(active_sessions(&state, account.id).await, 3);
+
+        let wrong = change_password(
+            State(state.clone()),
+            principal.clone(),
+            HeaderMap::new(),
+            Json(ChangePasswordRequest { current_password: "Wrong-Horse-42".into(), new_password: "Green-Falcon-77".into() }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(wrong.code, "invalid_credentials");
+        assert_eq!(active_sessions(&state, account.id).await, 3);
+
+        let status = change_password(
+            State(state.clone()),
+            principal,
+            HeaderMap::new(),
+            Json(ChangePasswordRequest { current_password: PASSWORD.into(), new_password: "Green-Falcon-77".into() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(active_sessions(&state, account.id).await, 0);
+        assert!(mail_kinds(&outbox).contains(&("password_changed".into(), "change@example.com".into())));
+        let _ = std::fs::remove_dir_all(outbox);
+    }
+
+    #[tokio::test]
+    async fn change_email_revokes_sessions_and_old_verify_token() {
+        let (state, outbox) = test_state();
+        let account = register_user(&state, "old@example.com").await;
+        let old_token = mailed_token(&outbox, "verify_email", "old@example.com");
+        let principal = principal_with_sessions(&state, account.id, 2).await;
+
+        let Json(changed) = change_email(
+            State(state.clone()),
+            principal,
+            HeaderMap::new(),
+            Json(ChangeEmailRequest { new_email: "New@Example.com".into(), current_password: PASSWORD.into() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(changed.email.as_deref(), Some("new@example.com"));
+        assert!(changed.email_verified_at.is_none());
+        assert_eq!(active_sessions(&state, account.id).await, 0);
+        let kinds = mail_kinds(&outbox);
+        assert!(kinds.contains(&("email_changed".into(), "old@example.com".into())));
+        assert!(kinds.contains(&("verify_email".into(), "new@example.com".into())));
+
+        let stale = verify_email(State(state.clone()), HeaderMap::new(), Json(TokenRequest { token: old_token }))
+            .await
+            .unwrap_err();
+        // 旧令牌作废后在签发新令牌时被清理
+        assert_eq!(stale.code, "token_invalid");
+        let token = mailed_token(&outbox, "verify_email", "new@example.com");
+        let Json(verified) = verify_email(State(state.clone()), HeaderMap::new(), Json(TokenRequest { token }))
+            .await
+            .unwrap();
+        assert!(verified.email_verified_at.is_some());
+        let _ = std::fs::remove_dir_all(outbox);
+    }
+}
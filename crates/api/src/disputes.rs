@@ -0,0 +1,302 @@
+//! 发起争议（04 §三 3.3 POST /api/v1/orders/:id/dispute）：仅订单双方、仅 Escrowed 订单可发起（escrow::can_dispute）。
+//! 每次发起后重算滚动争议率，越过阈值的类别自动登记冻结（08-3、Runbook §11）。
+//! 争议容量（W-D3-CAPACITY）：Paused 模式（含证据主存储中断达 7 天）拒绝新争议；Extended 模式延长 SLA 并上调 arbFee（不超过 arbFeeCap）。
+//! 每个争议创建时启动 SLA 时钟（core::sla）；稳定币冻结期间新建的争议时钟即刻暂停。
+//! 争议开启即对订单证据加法律保全（core::retention），保全期间原文件不随保留期删除。
+//! 发起时自动指派仲裁员（Store::pick_arbitrator：已启用 TOTP、未冻结、在办争议最少者），状态为 assigned；
+//! 无可用仲裁员时保持 open，由运营经 POST /api/v1/ops/disputes/:id/assign 指派或改派（冻结的仲裁员不可被指派）。
+//!
+//! 查询（GET /api/v1/disputes、/api/v1/disputes/:id）：仲裁员仅见指派给本人的争议，运营、执行器不限。
+//!
+//! 裁决（04 §三 POST /api/v1/disputes/:id/resolve）：仅被指派的仲裁员，未指派的争议不可裁决；请求体 {refund_ratio, slash_guide}。
+//! 裁决记录摘要（Dispute::resolution_digest）由 TSA 盖章后才落库（Runbook §11：裁决采信时间须可验证），检测到时间回滚时拒绝裁决；摘要另入队批量上链锚定（见 anchoring）。
+//! 裁决停止 SLA 计时并释放争议保全；资金按裁决执行另行处理，订单状态保持 disputed。
+//! 每次裁决后对全部已裁决争议重算偏差（core::bias）：连续同向登记强制复核，类别同向占比越线冻结该类别新单，
+//! 仲裁员越线冻结该仲裁员；被冻结的仲裁员不得再裁决，待运营解除。解除后该仲裁员/类别从解除时刻重新计数（core::bias::BiasBaselines）。
+
+use crate::audit::Actor;
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::rbac;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use traveltrust_core::escrow::DefaultEscrow;
+use traveltrust_core::{
+    AnchorKind, BiasAction, BiasBaselines, Dispute, DisputeMode, DisputeResolution, DisputeStatus, EscrowState, FreezeReason, HoldReason,
+    OrderState, Ownership, RulingRecord, SlaClock, SlaPolicy, UserRole,
+};
+use uuid::Uuid;
+
+#[derive(Deserialize, Default)]
+pub struct CreateDisputeRequest {
+    #[serde(default)]
+    pub evidence_hashes: Vec<String>,
+}
+
+/// 发起争议响应：争议本体 + 受理时的模式、应付仲裁费与裁决 SLA 截止
+#[derive(Serialize)]
+pub struct DisputeCreated {
+    #[serde(flatten)]
+    pub dispute: Dispute,
+    pub dispute_mode: DisputeMode,
+    pub arb_fee_usdc: u64,
+    pub sla_due_at: DateTime<Utc>,
+}
+
+pub async fn create_dispute(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    principal: Principal,
+    Json(req): Json<CreateDisputeRequest>,
+) -> ApiResult<(StatusCode, Json<DisputeCreated>)> {
+    let mut store = state.store.write().await;
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    rbac::ensure_owner(store.is_order_party(order, principal.user_id), Ownership::OrderParty, &principal.role)?;
+    if !DefaultEscrow::can_dispute(order.state) {
+        return Err(ApiError::conflict("invalid_order_state", "仅资金已锁定（escrowed）的订单可发起争议"));
+    }
+    let scopes = store.order_scopes(order);
+
+    let now = Utc::now();
+    let (snapshot, assessment) = state.risk.write().await.assess_capacity(&mut store, now);
+    if snapshot.evidence_storage_outage {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "evidence_storage_outage",
+            "证据主存储中断超过 7 天，暂停新争议受理（08-3）",
+        )
+        .with_detail(serde_json::json!(assessment)));
+    }
+    if assessment.mode == DisputeMode::Paused {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "dispute_intake_paused",
+            "争议容量超限，暂停新争议受理（Runbook §8/§11）",
+        )
+        .with_detail(serde_json::json!(assessment)));
+    }
+
+    let mut dispute = Dispute {
+        id: Uuid::new_v4(),
+        order_id,
+        status: DisputeStatus::Open,
+        evidence_hashes: req.evidence_hashes,
+        arbitrator_id: None,
+        resolution: None,
+        resolved_at: None,
+        created_at: now,
+        resolution_timestamp: None,
+    };
+    if let Some(order) = store.orders.get_mut(&order_id) {
+        order.state = OrderState::Disputed;
+    }
+    store.invalidate_order_stats(order_id);
+    let mut risk = state.risk.write().await;
+    let mut clock = SlaClock::start(&dispute, SlaPolicy::with_target(risk.capacity.sla(assessment.mode)));
+    if let Some(arbitrator_id) = store.pick_arbitrator(|id| risk.freezes.arbitrator_frozen(id).is_some()) {
+        dispute.arbitrator_id = Some(arbitrator_id);
+        dispute.status = DisputeStatus::Assigned;
+        clock.enter_stage(DisputeStatus::Assigned, now);
+    }
+    if risk.stablecoin_frozen_since.is_some() {
+        let _ = clock.pause(now);
+    }
+    let sla_due_at = clock.due_at();
+    store.sla_clocks.insert(dispute.id, clock);
+    store
+        .legal_holds
+        .place(order_id, HoldReason::DisputeOpen { dispute_id: dispute.id }, now);
+    store.disputes.insert(dispute.id, dispute.clone());
+    drop(store);
+
+    risk.dispute_rates.record_dispute(now, scopes);
+    let anomalies = risk.dispute_rates.evaluate(now, &risk.rate_thresholds);
+    for anomaly in anomalies {
+        if !risk.freezes.is_frozen(&anomaly.scope) {
+            eprintln!("[risk] 争议率异常，冻结类别 {:?} rate={:.4} peer_mean={:.4} score={:.2}", anomaly.scope, anomaly.rate, anomaly.peer_mean, anomaly.score);
+            risk.freezes.freeze(
+                anomaly.scope,
+                FreezeReason::DisputeRate {
+                    rate: anomaly.rate,
+                    peer_mean: anomaly.peer_mean,
+                    score: anomaly.score,
+                },
+                now,
+            );
+        }
+    }
+    Ok((
+        StatusCode::CREATED,
+        Json(DisputeCreated {
+            dispute,
+            dispute_mode: assessment.mode,
+            arb_fee_usdc: assessment.arb_fee_usdc,
+            sla_due_at,
+        }),
+    ))
+}
+
+/// 仲裁员仅见指派给本人的争议
+fn visible_to(dispute: &Dispute, principal: &Principal) -> bool {
+    principal.role != UserRole::Arbitrator || dispute.arbitrator_id == Some(principal.user_id)
+}
+
+pub async fn list_disputes(State(state): State<AppState>, principal: Principal) -> Json<Vec<Dispute>> {
+    let store = state.store.read().await;
+    let mut disputes: Vec<Dispute> = store.disputes.values().filter(|d| visible_to(d, &principal)).cloned().collect();
+    disputes.sort_by_key(|d| d.created_at);
+    Json(disputes)
+}
+
+pub async fn get_dispute(
+    State(state): State<AppState>,
+    Path(dispute_id): Path<Uuid>,
+    principal: Principal,
+) -> ApiResult<Json<Dispute>> {
+    let store = state.store.read().await;
+    let dispute = store.disputes.get(&dispute_id).ok_or_else(|| ApiError::not_found("争议"))?;
+    rbac::ensure_owner(visible_to(dispute, &principal), Ownership::AssignedArbitrator, &principal.role)?;
+    Ok(Json(dispute.clone()))
+}
+
+#[derive(Deserialize)]
+pub struct AssignDisputeRequest {
+    pub arbitrator_id: Uuid,
+}
+
+/// 运营指派或改派仲裁员：目标须为已启用 TOTP 的仲裁员且未被冻结，已裁决的争议不可改派
+pub async fn assign_dispute(
+    State(state): State<AppState>,
+    Path(dispute_id): Path<Uuid>,
+    principal: Principal,
+    Json(req): Json<AssignDisputeRequest>,
+) -> ApiResult<Json<Dispute>> {
+    let mut store = state.store.write().await;
+    let dispute = store.disputes.get(&dispute_id).ok_or_else(|| ApiError::not_found("争议"))?;
+    if dispute.status == DisputeStatus::Resolved {
+        return Err(ApiError::conflict("dispute_already_resolved", "争议已裁决"));
+    }
+    let arbitrator = store.users.get(&req.arbitrator_id).ok_or_else(|| ApiError::not_found("仲裁员"))?;
+    if arbitrator.user.role != UserRole::Arbitrator {
+        return Err(ApiError::bad_request("not_arbitrator", "指派对象须为仲裁员账号"));
+    }
+    if !store.totp.is_enabled(req.arbitrator_id) {
+        return Err(ApiError::conflict("totp_enrollment_required", "该仲裁员未启用 TOTP，无法裁决"));
+    }
+    if let Some(freeze) = state.risk.read().await.freezes.arbitrator_frozen(req.arbitrator_id) {
+        return Err(ApiError::new(StatusCode::LOCKED, "arbitrator_frozen", "该仲裁员已冻结，不可指派新案件")
+            .with_detail(serde_json::json!(freeze)));
+    }
+
+    let now = Utc::now();
+    let mut assigned = dispute.clone();
+    let previous = assigned.arbitrator_id.replace(req.arbitrator_id);
+    assigned.status = DisputeStatus::Assigned;
+    if let Some(clock) = store.sla_clocks.get_mut(&dispute_id) {
+        clock.enter_stage(DisputeStatus::Assigned, now);
+    }
+    store.disputes.insert(dispute_id, assigned.clone());
+    eprintln!(
+        "[dispute] 指派 dispute={} arbitrator={} previous={:?} by={}",
+        dispute_id, req.arbitrator_id, previous, principal.user_id
+    );
+    Ok(Json(assigned))
+}
+
+#[derive(Deserialize)]
+pub struct ResolveDisputeRequest {
+    /// 0.0 ~ 1.0，退给游客的比例
+    pub refund_ratio: f64,
+    pub slash_guide: bool,
+}
+
+pub async fn resolve_dispute(
+    State(state): State<AppState>,
+    Path(dispute_id): Path<Uuid>,
+    actor: Actor,
+    Json(req): Json<ResolveDisputeRequest>,
+) -> ApiResult<Json<Dispute>> {
+    if !(0.0..=1.0).contains(&req.refund_ratio) {
+        return Err(ApiError::bad_request("invalid_refund_ratio", "refund_ratio 须在 0 ~ 1 之间"));
+    }
+    let mut store = state.store.write().await;
+    let dispute = store.disputes.get(&dispute_id).ok_or_else(|| ApiError::not_found("争议"))?;
+    if dispute.status == DisputeStatus::Resolved {
+        return Err(ApiError::conflict("dispute_already_resolved", "争议已裁决"));
+    }
+    rbac::ensure_owner(dispute.arbitrator_id == Some(actor.id), Ownership::AssignedArbitrator, &actor.role)?;
+    if let Some(freeze) = state.risk.read().await.freezes.arbitrator_frozen(actor.id) {
+        return Err(ApiError::new(StatusCode::LOCKED, "arbitrator_frozen", "裁决偏向越线，该仲裁员已冻结，待运营解除")
+            .with_detail(serde_json::json!(freeze)));
+    }
+
+    let now = Utc::now();
+    let mut resolved = dispute.clone();
+    resolved.status = DisputeStatus::Resolved;
+    resolved.arbitrator_id = Some(actor.id);
+    resolved.resolution = Some(DisputeResolution {
+        refund_ratio: req.refund_ratio,
+        slash_guide: req.slash_guide,
+    });
+    resolved.resolved_at = Some(now);
+    let digest = resolved.resolution_digest().expect("裁决内容与时间已填写");
+    resolved.resolution_timestamp = Some(state.tsa.stamp_sha256(&digest)?);
+
+    if let Some(clock) = store.sla_clocks.get_mut(&dispute_id) {
+        clock.enter_stage(DisputeStatus::Resolved, now);
+    }
+    store.legal_holds.release_dispute(dispute_id, now);
+    store.anchors.enqueue(AnchorKind::Ruling, dispute_id, digest.clone(), now);
+    store.disputes.insert(dispute_id, resolved.clone());
+    store.stats.invalidate(actor.id);
+    store.invalidate_order_stats(resolved.order_id);
+    eprintln!(
+        "[dispute] 裁决 dispute={} order={} arbitrator={} refund_ratio={} slash_guide={} digest={}",
+        dispute_id, resolved.order_id, actor.id, req.refund_ratio, req.slash_guide, digest
+    );
+
+    let records: Vec<RulingRecord> = store
+        .disputes
+        .values()
+        .filter_map(|d| {
+            let scopes = store.orders.get(&d.order_id).map(|o| store.order_scopes(o)).unwrap_or_default();
+            RulingRecord::from_dispute(d, scopes)
+        })
+        .collect();
+    drop(store);
+    let mut risk = state.risk.write().await;
+    let report = risk.bias.evaluate(&records, &BiasBaselines::from_freezes(&risk.freezes));
+    for action in report.actions {
+        match action {
+            BiasAction::ForceSecondReview { dispute_ids } => {
+                let added = risk.second_reviews.flag(&dispute_ids, now);
+                if added > 0 {
+                    eprintln!("[risk] 连续同向裁决 {} 单，新增强制复核 {} 单", dispute_ids.len(), added);
+                }
+            }
+            BiasAction::PauseCategory { scope, lean } => {
+                if !risk.freezes.is_frozen(&scope) {
+                    eprintln!("[risk] 裁决集体偏向，冻结类别 {:?} lean={:.4}", scope, lean);
+                    risk.freezes.freeze(scope, FreezeReason::RulingBias { lean }, now);
+                }
+            }
+            BiasAction::FreezeArbitrator {
+                arbitrator_id,
+                lean,
+                streak,
+            } => {
+                if risk.freezes.arbitrator_frozen(arbitrator_id).is_none() {
+                    eprintln!("[risk] 裁决偏向，冻结仲裁员 {} lean={:.4} streak={}", arbitrator_id, lean, streak);
+                    risk.freezes.freeze_arbitrator(arbitrator_id, lean, streak, now);
+                }
+            }
+        }
+    }
+    Ok(Json(resolved))
+}
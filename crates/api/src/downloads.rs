@@ -0,0 +1,288 @@
+//! 证据下载签名链接（04 §三「证据上传与 pre-signed URL 获取」、01 §6 访问 P0）：HMAC-SHA256，短时有效。
+//!
+//! GET /api/v1/orders/:id/evidence/:evidence_id/url 为有读取权限的访问者签发链接，签名覆盖证据、订单、访问者、角色与过期时间；每次签发记 share 审计。
+//! GET /api/v1/evidence/:evidence_id/download 取用时校验签名与过期，要求请求者即签发对象，并按当前数据重查订单读取权限
+//! （链接不可转给他人使用，权限撤销后链接即失效）；每次下载记 download 审计。
+//! 取用版本（variant）：照片原文件（含 EXIF/GPS）仅限该订单争议的被指派仲裁员与合规类角色；订单双方默认获取去元数据副本，仲裁查看器可取缩略图（见 redaction）。
+//! 密钥来自 env EVIDENCE_URL_SECRET（至少 32 字节）；未设时启动生成临时密钥，仅用于开发（重启后已签发链接失效）。
+
+use crate::audit::{self, Actor};
+use crate::error::{ApiError, ApiResult};
+use crate::evidence::{ensure_can_read, storage_error};
+use crate::state::AppState;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::env;
+use tokio_util::io::ReaderStream;
+use traveltrust_core::privacy::requires_redaction;
+use traveltrust_core::{AccessAction, DerivedKind, Evidence, UserRole};
+use uuid::Uuid;
+
+/// 签名链接有效期
+const SIGNED_URL_TTL_SECS: i64 = 300;
+const URL_SECRET_MIN_LEN: usize = 32;
+
+pub struct UrlSigner {
+    secret: Vec<u8>,
+}
+
+impl UrlSigner {
+    pub fn from_env() -> Self {
+        let secret = match env::var("EVIDENCE_URL_SECRET") {
+            Ok(s) if s.len() >= URL_SECRET_MIN_LEN => s.into_bytes(),
+            _ => {
+                eprintln!("EVIDENCE_URL_SECRET 未设置或短于 32 字节，使用临时下载链接签名密钥（仅建议用于开发）");
+                let mut secret = vec![0u8; URL_SECRET_MIN_LEN];
+                rand::rngs::OsRng.fill_bytes(&mut secret);
+                secret
+            }
+        };
+        Self { secret }
+    }
+
+    fn mac(&self, grant: &DownloadGrant) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC 接受任意长度密钥");
+        mac.update(
+            format!(
+                "evidence_id={}\nvariant={}\norder_id={}\nactor_id={}\nrole={}\nexpires={}",
+                grant.evidence_id,
+                grant.variant.as_str(),
+                grant.order_id,
+                grant.actor_id,
+                grant.role.as_str(),
+                grant.expires
+            )
+            .as_bytes(),
+        );
+        mac
+    }
+
+    pub fn sign(&self, grant: &DownloadGrant) -> String {
+        hex::encode(self.mac(grant).finalize().into_bytes())
+    }
+
+    /// 常数时间比较
+    pub fn verify(&self, grant: &DownloadGrant, signature: &str) -> bool {
+        hex::decode(signature).is_ok_and(|sig| self.mac(grant).verify_slice(&sig).is_ok())
+    }
+}
+
+/// 下载版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    Original,
+    Redacted,
+    Thumbnail,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::Original => "original",
+            Variant::Redacted => "redacted",
+            Variant::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+/// 链接授权内容（签名覆盖全部字段）
+#[derive(Debug, Clone)]
+pub struct DownloadGrant {
+    pub evidence_id: Uuid,
+    pub variant: Variant,
+    pub order_id: Uuid,
+    pub actor_id: Uuid,
+    pub role: UserRole,
+    /// 过期时间（Unix 秒）
+    pub expires: i64,
+}
+
+#[derive(Serialize)]
+pub struct SignedUrl {
+    pub url: String,
+    pub variant: Variant,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct IssueQuery {
+    /// 缺省：仲裁员/合规类角色取原文件，其他角色取照片的去元数据副本（非照片为原文件）
+    pub variant: Option<Variant>,
+}
+
+pub async fn issue_download_url(
+    State(state): State<AppState>,
+    Path((order_id, evidence_id)): Path<(Uuid, Uuid)>,
+    Query(q): Query<IssueQuery>,
+    actor: Actor,
+) -> ApiResult<Json<SignedUrl>> {
+    let variant = {
+        let store = state.store.read().await;
+        ensure_can_read(&store, order_id, &actor)?;
+        let evidence = store
+            .evidence
+            .get(&evidence_id)
+            .filter(|e| e.order_id == order_id)
+            .ok_or_else(|| ApiError::not_found("该订单下的证据"))?;
+        if evidence.deleted_at.is_some() {
+            return Err(evidence_deleted());
+        }
+        let variant = q.variant.unwrap_or_else(|| default_variant(evidence, &actor.role));
+        resolve_object(evidence, variant, &actor.role)?;
+        variant
+    };
+    audit::record_access(&state, &actor, &[evidence_id], AccessAction::Share).await;
+    let expires_at = Utc::now() + Duration::seconds(SIGNED_URL_TTL_SECS);
+    let grant = DownloadGrant {
+        evidence_id,
+        variant,
+        order_id,
+        actor_id: actor.id,
+        role: actor.role.clone(),
+        expires: expires_at.timestamp(),
+    };
+    let url = format!(
+        "/api/v1/evidence/{}/download?variant={}&order_id={}&actor_id={}&role={}&expires={}&signature={}",
+        evidence_id,
+        variant.as_str(),
+        order_id,
+        actor.id,
+        actor.role.as_str(),
+        grant.expires,
+        state.url_signer.sign(&grant)
+    );
+    Ok(Json(SignedUrl {
+        url,
+        variant,
+        expires_at,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct DownloadQuery {
+    pub variant: Variant,
+    pub order_id: Uuid,
+    pub actor_id: Uuid,
+    pub role: UserRole,
+    pub expires: i64,
+    pub signature: String,
+}
+
+pub async fn download_evidence(
+    State(state): State<AppState>,
+    Path(evidence_id): Path<Uuid>,
+    Query(q): Query<DownloadQuery>,
+    actor: Actor,
+) -> ApiResult<Response> {
+    let grant = DownloadGrant {
+        evidence_id,
+        variant: q.variant,
+        order_id: q.order_id,
+        actor_id: q.actor_id,
+        role: q.role,
+        expires: q.expires,
+    };
+    if !state.url_signer.verify(&grant, &q.signature) {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "invalid_signature", "下载链接签名无效"));
+    }
+    if Utc::now().timestamp() > grant.expires {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "url_expired", "下载链接已过期，请重新获取"));
+    }
+    if actor.id != grant.actor_id || actor.role != grant.role {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "forbidden", "下载链接仅限签发对象使用"));
+    }
+    let evidence = {
+        let store = state.store.read().await;
+        ensure_can_read(&store, grant.order_id, &actor)?;
+        store
+            .evidence
+            .get(&evidence_id)
+            .filter(|e| e.order_id == grant.order_id)
+            .cloned()
+            .ok_or_else(|| ApiError::not_found("证据"))?
+    };
+    if evidence.deleted_at.is_some() {
+        return Err(evidence_deleted());
+    }
+    let object = resolve_object(&evidence, grant.variant, &actor.role)?;
+    let reader = state.evidence_store.open(&object.key).await.map_err(storage_error)?;
+    audit::record_access(&state, &actor, &[evidence_id], AccessAction::Download).await;
+    Ok((
+        [
+            (header::CONTENT_TYPE, object.mime),
+            (header::CONTENT_LENGTH, object.size_bytes.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", object.key)),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CACHE_CONTROL, "private, no-store".to_string()),
+        ],
+        Body::from_stream(ReaderStream::new(reader)),
+    )
+        .into_response())
+}
+
+/// 仲裁员与合规类角色可取含元数据的照片原文件
+fn is_privileged(role: &UserRole) -> bool {
+    matches!(role, UserRole::Arbitrator) || role.is_compliance()
+}
+
+fn default_variant(evidence: &Evidence, role: &UserRole) -> Variant {
+    if is_privileged(role) || !requires_redaction(&evidence.sniffed_mime) {
+        Variant::Original
+    } else {
+        Variant::Redacted
+    }
+}
+
+/// 下载对象（对象键、MIME、大小）
+struct ObjectRef {
+    key: String,
+    mime: String,
+    size_bytes: u64,
+}
+
+/// 按版本与角色解析下载对象
+fn resolve_object(evidence: &Evidence, variant: Variant, role: &UserRole) -> ApiResult<ObjectRef> {
+    let derived = |kind: DerivedKind| {
+        evidence.derived(kind).map(|d| ObjectRef {
+            key: d.location.key.clone(),
+            mime: d.mime.clone(),
+            size_bytes: d.size_bytes,
+        })
+    };
+    match variant {
+        Variant::Original => {
+            if requires_redaction(&evidence.sniffed_mime) && !is_privileged(role) {
+                return Err(ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    "original_restricted",
+                    "照片原文件含位置等元数据，仅限仲裁员与合规角色获取，请使用去元数据副本",
+                ));
+            }
+            Ok(ObjectRef {
+                key: evidence.location.key.clone(),
+                mime: evidence.sniffed_mime.clone(),
+                size_bytes: evidence.size_bytes,
+            })
+        }
+        Variant::Redacted => derived(DerivedKind::Redacted).ok_or_else(|| {
+            ApiError::conflict("redacted_copy_unavailable", "该证据无去元数据副本（类型不支持或处理失败）")
+        }),
+        Variant::Thumbnail => derived(DerivedKind::Thumbnail).ok_or_else(|| ApiError::not_found("缩略图")),
+    }
+}
+
+/// 原文件已按保留期删除：元数据与回执仍可查（Runbook §9）
+fn evidence_deleted() -> ApiError {
+    ApiError::new(StatusCode::GONE, "evidence_deleted", "证据原文件已按保留期删除，hash 与回执仍保留")
+}
@@ -0,0 +1,73 @@
+//! API 错误响应：与 not_impl_json 同形的 JSON（status 为机读错误码），便于前端/DApp 统一处理（04 §三）。
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use traveltrust_core::EvidenceError;
+
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub detail: Option<Value>,
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn not_found(what: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", format!("{} 不存在", what))
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, code, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "status": self.code,
+            "message": self.message,
+        });
+        if let Some(detail) = self.detail {
+            body["detail"] = detail;
+        }
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<EvidenceError> for ApiError {
+    fn from(e: EvidenceError) -> Self {
+        let (status, code) = match e {
+            EvidenceError::TooLarge { .. } => (StatusCode::PAYLOAD_TOO_LARGE, "evidence_too_large"),
+            EvidenceError::TypeNotAllowed(_) | EvidenceError::UnknownType | EvidenceError::TypeMismatch { .. } => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "evidence_type_rejected")
+            }
+            EvidenceError::Empty | EvidenceError::InvalidHash => (StatusCode::BAD_REQUEST, "invalid_evidence"),
+        };
+        Self::new(status, code, e.to_string())
+    }
+}
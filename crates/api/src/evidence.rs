@@ -0,0 +1,324 @@
+//! 证据上传（04 §三 POST /api/v1/orders/:id/evidence，01 §6）：流式 multipart，单独的大小上限（08-3 evidenceMaxSize=50MB），
+//! 不经全局 1MB 请求体限制。边收边算 sha256、按文件头魔数校验类型（evidenceTypeAllowlist），落盘后返回签名回执（Runbook §5）。
+//!
+//! 表单字段：file（必填，带 Content-Type）、dispute_id（可选，须属于该订单）；上传者为当前登录账号，须为订单双方。
+//! 存储：EVIDENCE_DIR（默认 data/evidence）暂存，校验后以 sha256 为对象键存入 EvidenceStore（见 evidence_store）；同 hash 已存在时不覆盖（争议期间证据不可覆盖，Runbook §5）。
+//! 回执验签公钥：GET /api/v1/evidence/receipt-keys（含已轮换旧钥，见 receipts）。原文件 hash 与回执各附 TSA 时间戳（见 tsa），原文件 hash 另入队批量上链锚定（见 anchoring）。
+//! 查看：GET /api/v1/orders/:id/evidence 仅订单双方、该订单争议的被指派仲裁员与合规类角色可读，每条证据记一次 view 审计（见 audit）；原文件与派生件经签名下载链接获取（见 downloads）。
+//! 照片归档前生成去元数据副本与缩略图（见 redaction）；列表附带派生件签名。
+
+use crate::audit::{self, Actor};
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::rbac;
+use crate::redaction::{self, StagedDerivative};
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{
+    extract::{multipart::Field, Multipart, Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path as FsPath, PathBuf};
+use tokio::io::AsyncWriteExt;
+use traveltrust_core::evidence::{self, EVIDENCE_MAX_SIZE_BYTES};
+use traveltrust_core::{
+    AccessAction, AnchorKind, DerivedEvidence, Evidence, EvidenceReceipt, Ownership, ReceiptPublicKey, SignedEvidenceReceipt,
+    StorageLocation, TimeStampToken, UserRole,
+};
+use uuid::Uuid;
+
+/// 上传路由的请求体上限：证据上限 + multipart 边界与文本字段余量
+pub const EVIDENCE_REQUEST_LIMIT: usize = EVIDENCE_MAX_SIZE_BYTES as usize + 64 * 1024;
+/// 魔数嗅探所需的文件头长度
+pub(crate) const SNIFF_LEN: usize = 16;
+/// 对象键正被保留期清理删除时的重试间隔
+const PIN_RETRY_MILLIS: u64 = 100;
+
+/// 已落临时文件、待校验与归档的上传（单次上传与分片上传共用，见 uploads）
+pub(crate) struct Staged {
+    pub(crate) tmp_path: PathBuf,
+    pub(crate) sha256: String,
+    pub(crate) size: u64,
+    pub(crate) declared_mime: String,
+    pub(crate) sniffed_mime: &'static str,
+}
+
+pub async fn upload_evidence(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    principal: Principal,
+    mut multipart: Multipart,
+) -> ApiResult<(StatusCode, Json<SignedEvidenceReceipt>)> {
+    ensure_order_party(&*state.store.read().await, order_id, &principal)?;
+    tokio::fs::create_dir_all(&state.evidence_dir).await.map_err(storage_error)?;
+
+    let mut staged: Option<Staged> = None;
+    let mut dispute_id: Option<Uuid> = None;
+    let result: ApiResult<()> = async {
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            match field.name().unwrap_or_default() {
+                "file" if staged.is_none() => staged = Some(stage_file(&mut field, &state.evidence_dir).await?),
+                "dispute_id" => dispute_id = Some(parse_uuid_field(field, "dispute_id").await?),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    .await;
+    let staged = match (result, staged) {
+        (Ok(()), Some(staged)) => staged,
+        (Ok(()), None) => return Err(ApiError::bad_request("missing_file", "缺少 file 字段")),
+        (Err(e), staged) => {
+            if let Some(s) = staged {
+                let _ = tokio::fs::remove_file(&s.tmp_path).await;
+            }
+            return Err(e);
+        }
+    };
+    let outcome = finalize(&state, order_id, principal.user_id, dispute_id, &staged).await;
+    let _ = tokio::fs::remove_file(&staged.tmp_path).await;
+    let evidence = outcome?;
+    Ok((StatusCode::CREATED, Json(issue_receipt(&state, &evidence))))
+}
+
+/// 列表项：回执内容 + 已签名派生件（去元数据副本、缩略图，可按 source_sha256 离线验签）+ 原文件 hash 时间戳
+#[derive(Serialize)]
+pub struct EvidenceView {
+    #[serde(flatten)]
+    pub receipt: EvidenceReceipt,
+    pub derived: Vec<DerivedEvidence>,
+    pub timestamp: Option<TimeStampToken>,
+}
+
+/// 订单证据列表
+pub async fn list_evidence(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    actor: Actor,
+) -> ApiResult<Json<Vec<EvidenceView>>> {
+    let views: Vec<EvidenceView> = {
+        let store = state.store.read().await;
+        ensure_can_read(&store, order_id, &actor)?;
+        let mut items: Vec<_> = store.evidence.values().filter(|e| e.order_id == order_id).collect();
+        items.sort_by_key(|e| e.uploaded_at);
+        items
+            .into_iter()
+            .map(|e| EvidenceView {
+                receipt: e.receipt(),
+                derived: e.derived.clone(),
+                timestamp: e.timestamp.clone(),
+            })
+            .collect()
+    };
+    let ids: Vec<Uuid> = views.iter().map(|v| v.receipt.evidence_id).collect();
+    audit::record_access(&state, &actor, &ids, AccessAction::View).await;
+    Ok(Json(views))
+}
+
+/// 证据读取权限（工单内可读，04 §四）：订单双方、该订单争议的被指派仲裁员与合规类角色
+pub(crate) fn ensure_can_read(store: &Store, order_id: Uuid, actor: &Actor) -> ApiResult<()> {
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    let allowed = store.is_order_party(order, actor.id)
+        || (actor.role == UserRole::Arbitrator && store.is_order_arbitrator(order_id, actor.id))
+        || actor.role.is_compliance();
+    rbac::ensure_owner(allowed, Ownership::EvidenceReader, &actor.role)
+}
+
+/// 证据提交：仅订单双方
+pub(crate) fn ensure_order_party(store: &Store, order_id: Uuid, principal: &Principal) -> ApiResult<()> {
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    rbac::ensure_owner(store.is_order_party(order, principal.user_id), Ownership::OrderParty, &principal.role)
+}
+
+/// 签发回执并对回执摘要加盖时间戳（见 tsa）
+pub(crate) fn issue_receipt(state: &AppState, evidence: &Evidence) -> SignedEvidenceReceipt {
+    let mut signed = state.receipts.sign(evidence.receipt());
+    signed.timestamp = stamp(state, &signed.digest());
+    signed
+}
+
+/// 盖章失败（时间回滚）不阻断上传：证据照常归档，令牌留空并已告警
+fn stamp(state: &AppState, sha256: &str) -> Option<TimeStampToken> {
+    state
+        .tsa
+        .stamp_sha256(sha256)
+        .map_err(|e| eprintln!("[evidence] 时间戳签发失败 imprint={}: {:?}", sha256, e))
+        .ok()
+}
+
+/// 回执验签公钥列表：按回执中的 key_id 取钥离线验签（traveltrust_core::evidence::verify_receipt_with_keys）
+pub async fn receipt_keys(State(state): State<AppState>) -> Json<Vec<ReceiptPublicKey>> {
+    Json(state.receipts.public_keys())
+}
+
+/// 流式写临时文件：累计大小超限即中止（413），读满文件头即校验类型（415），不等整文件传完
+async fn stage_file(field: &mut Field<'_>, dir: &FsPath) -> ApiResult<Staged> {
+    let declared_mime = field.content_type().unwrap_or_default().to_string();
+    let tmp_path = dir.join(format!(".upload-{}", Uuid::new_v4()));
+    let result = write_stream(field, &tmp_path, &declared_mime).await;
+    match result {
+        Ok((sha256, size, sniffed_mime)) => Ok(Staged {
+            tmp_path,
+            sha256,
+            size,
+            declared_mime,
+            sniffed_mime,
+        }),
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            Err(e)
+        }
+    }
+}
+
+async fn write_stream(field: &mut Field<'_>, tmp_path: &FsPath, declared_mime: &str) -> ApiResult<(String, u64, &'static str)> {
+    let mut file = tokio::fs::File::create(tmp_path).await.map_err(storage_error)?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+    let mut head: Vec<u8> = Vec::with_capacity(SNIFF_LEN);
+    let mut sniffed: Option<&'static str> = None;
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        size += chunk.len() as u64;
+        evidence::validate_size(size).map_err(ApiError::from)?;
+        if sniffed.is_none() {
+            head.extend(chunk.iter().take(SNIFF_LEN - head.len()));
+            if head.len() == SNIFF_LEN {
+                sniffed = Some(check_type(declared_mime, &head)?);
+            }
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(storage_error)?;
+    }
+    evidence::validate_size(size).map_err(ApiError::from)?;
+    let sniffed = match sniffed {
+        Some(s) => s,
+        None => check_type(declared_mime, &head)?,
+    };
+    file.sync_all().await.map_err(storage_error)?;
+    Ok((hex::encode(hasher.finalize()), size, sniffed))
+}
+
+pub(crate) fn check_type(declared_mime: &str, head: &[u8]) -> ApiResult<&'static str> {
+    let sniffed = evidence::sniff_mime(head);
+    evidence::validate_type(declared_mime, sniffed)?;
+    sniffed.ok_or_else(|| ApiError::from(evidence::EvidenceError::UnknownType))
+}
+
+/// 归档（按 sha256 命名，不覆盖已有文件）并登记证据；照片另生成去元数据副本与缩略图（见 redaction）；dispute_id 给出时把 hash 追加到争议
+pub(crate) async fn finalize(
+    state: &AppState,
+    order_id: Uuid,
+    uploader_id: Uuid,
+    dispute_id: Option<Uuid>,
+    staged: &Staged,
+) -> ApiResult<Evidence> {
+    {
+        let store = state.store.read().await;
+        if let Some(id) = dispute_id {
+            if store.disputes.get(&id).is_none_or(|d| d.order_id != order_id) {
+                return Err(ApiError::not_found("该订单下的争议"));
+            }
+        }
+    }
+    let evidence = Evidence {
+        id: Uuid::new_v4(),
+        sha256: staged.sha256.clone(),
+        uploader_id,
+        order_id,
+        dispute_id,
+        declared_mime: staged.declared_mime.clone(),
+        sniffed_mime: staged.sniffed_mime.to_string(),
+        size_bytes: staged.size,
+        uploaded_at: Utc::now(),
+        location: StorageLocation {
+            backend: state.evidence_store.backend().to_string(),
+            key: staged.sha256.clone(),
+        },
+        deleted_at: None,
+        derived: Vec::new(),
+        timestamp: stamp(state, &staged.sha256),
+    };
+    evidence.validate()?;
+
+    let derivatives = redaction::derive(state, staged).await;
+    let archived = archive(state, evidence, staged, &derivatives).await;
+    redaction::cleanup(&derivatives).await;
+    archived
+}
+
+/// 归档原文件与派生件：对象写入（最大 50MB，多副本逐个写入）在 store 锁外进行，写入前登记对象键使保留期清理（retention）跳过这些键，
+/// 避免复用同 hash 对象的同时该对象被删除（键正被清理删除时等删除完成再写）；写完后持 store 写锁只做元数据登记
+async fn archive(
+    state: &AppState,
+    mut evidence: Evidence,
+    staged: &Staged,
+    derivatives: &[StagedDerivative],
+) -> ApiResult<Evidence> {
+    let keys: Vec<String> = std::iter::once(staged.sha256.clone())
+        .chain(derivatives.iter().map(|d| d.derived.sha256.clone()))
+        .collect();
+    while !state.store.write().await.pin_object_keys(&keys) {
+        tokio::time::sleep(std::time::Duration::from_millis(PIN_RETRY_MILLIS)).await;
+    }
+    let stored = put_objects(state, &mut evidence, staged, derivatives).await;
+
+    let mut store = state.store.write().await;
+    store.unpin_object_keys(&keys);
+    stored?;
+    if let Some(dispute) = evidence.dispute_id.and_then(|id| store.disputes.get_mut(&id)) {
+        if !dispute.evidence_hashes.contains(&evidence.sha256) {
+            dispute.evidence_hashes.push(evidence.sha256.clone());
+        }
+    }
+    store.anchors.enqueue(AnchorKind::Evidence, evidence.id, evidence.sha256.clone(), evidence.uploaded_at);
+    store.evidence.insert(evidence.id, evidence.clone());
+    drop(store);
+    eprintln!(
+        "[evidence] order={} evidence={} sha256={} size={} derived={}",
+        evidence.order_id,
+        evidence.id,
+        evidence.sha256,
+        evidence.size_bytes,
+        evidence.derived.len()
+    );
+    Ok(evidence)
+}
+
+/// 按 sha256 写入对象存储，已存在时不覆盖；派生件写入失败只记日志，不登记该派生件
+async fn put_objects(
+    state: &AppState,
+    evidence: &mut Evidence,
+    staged: &Staged,
+    derivatives: &[StagedDerivative],
+) -> ApiResult<()> {
+    state
+        .evidence_store
+        .put_if_absent(&staged.sha256, &staged.tmp_path)
+        .await
+        .map_err(storage_error)?;
+    for d in derivatives {
+        match state.evidence_store.put_if_absent(&d.derived.sha256, &d.tmp_path).await {
+            Ok(_) => evidence.derived.push(d.derived.clone()),
+            Err(e) => eprintln!("[evidence] 派生件归档失败 kind={} source={}: {}", d.derived.kind.as_str(), evidence.sha256, e),
+        }
+    }
+    Ok(())
+}
+
+async fn parse_uuid_field(field: Field<'_>, name: &str) -> ApiResult<Uuid> {
+    let text = field.text().await.map_err(multipart_error)?;
+    Uuid::parse_str(text.trim()).map_err(|_| ApiError::bad_request("invalid_field", format!("{} 须为 UUID", name)))
+}
+
+fn multipart_error(e: axum::extract::multipart::MultipartError) -> ApiError {
+    ApiError::bad_request("invalid_multipart", e.body_text())
+}
+
+pub(crate) fn storage_error(e: std::io::Error) -> ApiError {
+    eprintln!("[evidence] 存储错误: {}", e);
+    ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "evidence_storage_unavailable", "证据存储暂不可用，请重试")
+}
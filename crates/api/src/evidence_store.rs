@@ -0,0 +1,350 @@
+//! 证据原文件对象存储抽象（04 §一 证据存储、01 §6）：按 sha256 作对象键，只增不改。
+//!
+//! 上传先在 EVIDENCE_DIR 本地暂存（流式计算 hash、分片拼装），校验通过后 put_if_absent 进对象存储。
+//! 开发/测试用本地文件系统实现；S3 兼容后端实现同一 trait 后在 AppState 中替换即可。
+//! 冗余（08-3 多区域/多云）：ReplicatedStore 写入全部副本、从任一健康副本读取，后台巡检补齐缺失副本并记录各后端中断时长
+//! （见 monitor_evidence_storage）；主存储中断达 7 天时争议模式进入 Paused。
+//! 多副本写入、删除与修复耗时随副本数增长，一律在 store 锁外执行；锁内只登记/解除对象键（Store.archiving_keys、deleting_keys）。
+
+use crate::state::AppState;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncRead;
+use traveltrust_core::availability::primary_outage_exceeded;
+use traveltrust_core::BackendHealth;
+use uuid::Uuid;
+
+pub type ObjectReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// 健康巡检与副本修复周期
+const STORAGE_MONITOR_INTERVAL_SECS: u64 = 300;
+
+#[async_trait]
+pub trait EvidenceStore: Send + Sync {
+    /// 写入 StorageLocation.backend 的后端名
+    fn backend(&self) -> &str;
+
+    /// 把本地暂存文件存为 key；key 已存在时不覆盖（返回 false）。不消费暂存文件，由调用方清理
+    async fn put_if_absent(&self, key: &str, staged: &Path) -> io::Result<bool>;
+
+    async fn exists(&self, key: &str) -> io::Result<bool>;
+
+    /// 读取对象；不存在时返回 NotFound
+    async fn open(&self, key: &str) -> io::Result<ObjectReader>;
+
+    /// 删除对象；不存在时返回 NotFound
+    async fn delete(&self, key: &str) -> io::Result<()>;
+
+    /// 可用性探测（健康巡检）
+    async fn probe(&self) -> io::Result<()>;
+}
+
+/// 本地文件系统：root 下以 key 为文件名
+pub struct LocalFsStore {
+    name: String,
+    root: PathBuf,
+}
+
+impl LocalFsStore {
+    pub fn new(name: impl Into<String>, root: PathBuf) -> Self {
+        Self { name: name.into(), root }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        // 仅接受 sha256 形式的 key，防止路径穿越
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "非法对象键"));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl EvidenceStore for LocalFsStore {
+    fn backend(&self) -> &str {
+        &self.name
+    }
+
+    async fn put_if_absent(&self, key: &str, staged: &Path) -> io::Result<bool> {
+        let path = self.path(key)?;
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(false);
+        }
+        tokio::fs::create_dir_all(&self.root).await?;
+        // 先复制到同目录临时文件再改名，读者不会看到半个对象
+        let tmp = self.root.join(format!(".put-{}", Uuid::new_v4()));
+        if let Err(e) = tokio::fs::copy(staged, &tmp).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(true)
+    }
+
+    async fn exists(&self, key: &str) -> io::Result<bool> {
+        tokio::fs::try_exists(self.path(key)?).await
+    }
+
+    async fn open(&self, key: &str) -> io::Result<ObjectReader> {
+        let file = tokio::fs::File::open(self.path(key)?).await?;
+        Ok(Box::pin(file))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        tokio::fs::remove_file(self.path(key)?).await
+    }
+
+    async fn probe(&self) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        let meta = tokio::fs::metadata(&self.root).await?;
+        if meta.permissions().readonly() {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "存储目录只读"));
+        }
+        Ok(())
+    }
+}
+
+/// 多副本存储：第一个副本为主存储。写入全部副本（至少一个成功即成功，缺失副本由巡检补齐），读取依次尝试健康副本
+pub struct ReplicatedStore {
+    replicas: Vec<Arc<dyn EvidenceStore>>,
+    health: Mutex<Vec<BackendHealth>>,
+}
+
+impl ReplicatedStore {
+    pub fn new(replicas: Vec<Arc<dyn EvidenceStore>>) -> Self {
+        let health = replicas
+            .iter()
+            .enumerate()
+            .map(|(i, r)| BackendHealth::new(r.backend(), i == 0))
+            .collect();
+        Self {
+            replicas,
+            health: Mutex::new(health),
+        }
+    }
+
+    pub fn health(&self) -> Vec<BackendHealth> {
+        self.health.lock().map(|h| h.clone()).unwrap_or_default()
+    }
+
+    fn record<T>(&self, idx: usize, result: &io::Result<T>) {
+        let now = Utc::now();
+        let Ok(mut health) = self.health.lock() else {
+            return;
+        };
+        let Some(h) = health.get_mut(idx) else {
+            return;
+        };
+        match result {
+            // 对象不存在不算后端故障
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                if h.is_healthy() {
+                    eprintln!("[evidence] 存储后端 {} 不可用: {}", h.backend, e);
+                }
+                h.record_failure(e.to_string(), now);
+            }
+            _ => {
+                if !h.is_healthy() {
+                    eprintln!("[evidence] 存储后端 {} 恢复，中断 {} 秒", h.backend, h.outage(now).num_seconds());
+                }
+                h.record_ok(now);
+            }
+        }
+    }
+
+    /// 健康副本在前、故障副本在后（故障副本仍尝试，成功即恢复）
+    fn read_order(&self) -> Vec<usize> {
+        let health = self.health();
+        let (mut healthy, down): (Vec<usize>, Vec<usize>) =
+            (0..self.replicas.len()).partition(|&i| health.get(i).is_none_or(|h| h.is_healthy()));
+        healthy.extend(down);
+        healthy
+    }
+
+    /// 探测全部副本并更新健康状态
+    pub async fn probe_all(&self) {
+        for (idx, replica) in self.replicas.iter().enumerate() {
+            let result = replica.probe().await;
+            self.record(idx, &result);
+        }
+    }
+
+    /// 为缺失 key 的健康副本从其他副本补齐；staging 为本地暂存目录。返回补齐的副本数
+    pub async fn repair(&self, key: &str, staging: &Path) -> usize {
+        let mut missing = Vec::new();
+        let mut source = None;
+        for (idx, replica) in self.replicas.iter().enumerate() {
+            let result = replica.exists(key).await;
+            self.record(idx, &result);
+            match result {
+                Ok(true) => source = source.or(Some(idx)),
+                Ok(false) => missing.push(idx),
+                Err(_) => {}
+            }
+        }
+        let Some(source) = source else {
+            if !missing.is_empty() {
+                eprintln!("[evidence] 副本修复失败：所有可用副本均缺失 key={}", key);
+            }
+            return 0;
+        };
+        if missing.is_empty() {
+            return 0;
+        }
+        let tmp = staging.join(format!(".repair-{}", Uuid::new_v4()));
+        let copied = async {
+            let mut reader = self.replicas[source].open(key).await?;
+            let mut file = tokio::fs::File::create(&tmp).await?;
+            tokio::io::copy(&mut reader, &mut file).await?;
+            file.sync_all().await
+        }
+        .await;
+        let mut repaired = 0;
+        if let Err(e) = copied {
+            self.record(source, &Err::<(), _>(e));
+        } else {
+            for idx in missing {
+                let result = self.replicas[idx].put_if_absent(key, &tmp).await;
+                self.record(idx, &result);
+                if result.is_ok() {
+                    eprintln!("[evidence] 副本修复 key={} {} -> {}", key, self.replicas[source].backend(), self.replicas[idx].backend());
+                    repaired += 1;
+                }
+            }
+        }
+        let _ = tokio::fs::remove_file(&tmp).await;
+        repaired
+    }
+}
+
+#[async_trait]
+impl EvidenceStore for ReplicatedStore {
+    fn backend(&self) -> &str {
+        "replicated"
+    }
+
+    async fn put_if_absent(&self, key: &str, staged: &Path) -> io::Result<bool> {
+        let mut created = false;
+        let mut stored = false;
+        let mut last_err = None;
+        for (idx, replica) in self.replicas.iter().enumerate() {
+            let result = replica.put_if_absent(key, staged).await;
+            self.record(idx, &result);
+            match result {
+                Ok(c) => {
+                    stored = true;
+                    created |= c;
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        match (stored, last_err) {
+            (true, _) => Ok(created),
+            (false, Some(e)) => Err(e),
+            (false, None) => Err(io::Error::other("未配置存储副本")),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> io::Result<bool> {
+        let mut last_err = None;
+        for idx in self.read_order() {
+            let result = self.replicas[idx].exists(key).await;
+            self.record(idx, &result);
+            match result {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(e) => last_err = Some(e),
+            }
+        }
+        last_err.map_or(Ok(false), Err)
+    }
+
+    async fn open(&self, key: &str) -> io::Result<ObjectReader> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "对象不存在");
+        for idx in self.read_order() {
+            let result = self.replicas[idx].open(key).await;
+            self.record(idx, &result);
+            match result {
+                Ok(reader) => return Ok(reader),
+                // 有副本故障时优先报告故障而非 NotFound
+                Err(e) if e.kind() != io::ErrorKind::NotFound || last_err.kind() == io::ErrorKind::NotFound => {
+                    last_err = e
+                }
+                Err(_) => {}
+            }
+        }
+        Err(last_err)
+    }
+
+    /// 删除全部副本；任一副本故障则返回错误（调用方下轮重试），不存在视为已删除
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        let mut deleted = false;
+        for (idx, replica) in self.replicas.iter().enumerate() {
+            let result = replica.delete(key).await;
+            self.record(idx, &result);
+            match result {
+                Ok(()) => deleted = true,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if deleted {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::NotFound, "对象不存在"))
+        }
+    }
+
+    async fn probe(&self) -> io::Result<()> {
+        self.probe_all().await;
+        if self.health().iter().any(|h| h.is_healthy()) {
+            Ok(())
+        } else {
+            Err(io::Error::other("全部存储副本不可用"))
+        }
+    }
+}
+
+/// 后台巡检：探测各副本、补齐在册证据（未删除，含派生件）的缺失副本，并按主存储中断时长更新争议受理状态；探测与修复均不持 store 锁
+pub async fn monitor_evidence_storage(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(STORAGE_MONITOR_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let replicas = &state.evidence_replicas;
+        replicas.probe_all().await;
+        let keys: HashSet<String> = {
+            let store = state.store.read().await;
+            store
+                .evidence
+                .values()
+                .filter(|e| e.deleted_at.is_none())
+                .flat_map(|e| std::iter::once(&e.location.key).chain(e.derived.iter().map(|d| &d.location.key)))
+                .cloned()
+                .collect()
+        };
+        for key in keys {
+            // 修复期间登记对象键，防止保留期清理在副本补齐的同时删除该键（否则已删对象会被补回）
+            let pin = [key];
+            let pinned = {
+                let mut store = state.store.write().await;
+                store.object_key_in_use(&pin[0]) && store.pin_object_keys(&pin)
+            };
+            if !pinned {
+                continue;
+            }
+            replicas.repair(&pin[0], &state.evidence_dir).await;
+            state.store.write().await.unpin_object_keys(&pin);
+        }
+        let outage = primary_outage_exceeded(&replicas.health(), Utc::now());
+        let mut risk = state.risk.write().await;
+        if risk.evidence_storage_outage != outage {
+            eprintln!("[risk] 证据主存储中断阈值状态 {} -> {}", risk.evidence_storage_outage, outage);
+            risk.evidence_storage_outage = outage;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! 导游注册（04 §三 3.3 POST /api/v1/guides）：新注册导游为 Pending，质押达标后转 Active（01 §4）。
+//! 资料归属当前登录账号，每账号一份；游客注册导游资料后账号角色转为 guide（01 §7）。
+
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+use serde::Deserialize;
+use traveltrust_core::{Guide, GuideStatus, ServiceType, UserRole};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct CreateGuideRequest {
+    pub city: String,
+    pub country_code: String,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    pub service_types: Vec<ServiceType>,
+    pub bio: Option<String>,
+}
+
+pub async fn create_guide(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<CreateGuideRequest>,
+) -> ApiResult<(StatusCode, Json<Guide>)> {
+    if req.city.trim().is_empty() || req.country_code.trim().is_empty() {
+        return Err(ApiError::bad_request("invalid_location", "city 与 country_code 不可为空"));
+    }
+    if req.service_types.is_empty() {
+        return Err(ApiError::bad_request("invalid_service_types", "service_types 至少一项"));
+    }
+    let mut store = state.store.write().await;
+    if store.guides.values().any(|g| g.user_id == principal.user_id) {
+        return Err(ApiError::conflict("guide_profile_exists", "该账号已注册导游资料"));
+    }
+    let guide = Guide {
+        id: Uuid::new_v4(),
+        user_id: principal.user_id,
+        city: req.city.trim().to_string(),
+        country_code: req.country_code.trim().to_uppercase(),
+        languages: req.languages,
+        service_types: req.service_types,
+        bio: req.bio,
+        stake_amount: "0".to_string(),
+        status: GuideStatus::Pending,
+        created_at: Utc::now(),
+    };
+    if let Some(account) = store.users.get_mut(&principal.user_id) {
+        if account.user.role == UserRole::Tourist {
+            account.user.role = UserRole::Guide;
+            account.user.updated_at = guide.created_at;
+            eprintln!("[auth] 注册导游资料 user={} role: tourist -> guide", principal.user_id);
+        }
+    }
+    store.guides.insert(guide.id, guide.clone());
+    store.stats.invalidate(principal.user_id);
+    Ok((StatusCode::CREATED, Json(guide)))
+}
@@ -0,0 +1,83 @@
+//! 外发邮件抽象（04 §三 3.1 邮箱验证、找回密码）：认证流程只经 Mailer 发信，不直连邮件服务。
+//!
+//! 默认实现 OutboxMailer 把每封邮件写成 MAIL_OUTBOX_DIR（默认 data/outbox）下的一个 JSON 文件，供开发与测试读取验证链接；
+//! 接入 SMTP / 邮件服务商时实现同一 trait 后在 AppState 中替换即可。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutgoingMail {
+    pub id: Uuid,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    /// 邮件类别：verify_email / reset_password / password_changed
+    pub kind: &'static str,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OutgoingMail {
+    pub fn new(to: &str, kind: &'static str, subject: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            to: to.to_string(),
+            subject: subject.into(),
+            body: body.into(),
+            kind,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &OutgoingMail) -> io::Result<()>;
+}
+
+/// 本地发件箱：每封邮件一个 JSON 文件，文件名按创建时间排序
+pub struct OutboxMailer {
+    dir: PathBuf,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            env::var("MAIL_OUTBOX_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("data/outbox")),
+        )
+    }
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, mail: &OutgoingMail) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let name = format!("{}-{}.json", mail.created_at.format("%Y%m%dT%H%M%S%.6fZ"), mail.id);
+        let json = serde_json::to_vec_pretty(mail).map_err(io::Error::other)?;
+        // 先写临时文件再改名，读取方不会看到半封邮件
+        let tmp = self.dir.join(format!(".{}", name));
+        tokio::fs::write(&tmp, json).await?;
+        tokio::fs::rename(&tmp, self.dir.join(name)).await
+    }
+}
+
+/// 前端地址：APP_BASE_URL（默认 http://localhost:5173）
+pub fn app_base_url() -> String {
+    env::var("APP_BASE_URL").unwrap_or_else(|_| "http://localhost:5173".to_string())
+}
+
+/// 邮件中的验证/重置链接，指向前端页面
+pub fn app_link(path: &str, token: &str) -> String {
+    format!("{}{}?token={}", app_base_url().trim_end_matches('/'), path, token)
+}
@@ -2,22 +2,77 @@
 //!
 //! SSOT：Backend 启动时从 env SSOT_VERSION 读取；STRICT_SSOT=1 时未设置则拒绝启动。见 08-5 §4、Runbook §10、04 §四。
 //! traceId：响应头 x-request-id 由请求头带入或自动生成，与 01 §9 贯通 requestId→txHash→logIndex 一致。
-//! 路由：/health、/api/v1/guides 为占位实现；其余为 501 占位，实现时按 04 §三 与 01 §10 17 条（幂等、traceId）补齐。
-//! 幂等：请求头 Idempotency-Key / X-Idempotency-Key 在中间件透传并回写；对 POST/PUT 做 key 去重与结果复用（01 §10 #14），缓存键=method+path+key，最多 1000 条。
-//! 环境变量：PORT（默认 3000）、CORS_ORIGINS（逗号分隔的允许 origin，未设则开发态允许任意；生产应设置）。
+//! 鉴权：/auth/* 与 PUT /api/v1/me/password 为邮箱密码账号、JWT 访问令牌 + 轮换刷新令牌、邮箱验证与密码重置（见 auth，邮件经 mailer）；
+//! /auth/siwe/* 为钱包签名登录（EIP-4361，见 siwe），与邮箱登录签发同一种会话，POST /api/v1/me/email 为钱包账号关联邮箱；
+//! 除 /health、导游浏览、公钥与签名域查询及 /auth/* 公开入口外，路由均需 Authorization: Bearer，未登录返回 401；运营经 /api/v1/ops/users/:id/role 指派角色。
+//! 钱包：/api/v1/me/wallet/challenge 与 PUT/DELETE /api/v1/me/wallet 为 EIP-191 签名证明的绑定、换绑与解绑，GET /api/v1/me/wallets 查询历史；
+//! 存在未终结订单、争议或证据上传时不可换绑/解绑（01 §10 12 缝 #11，见 wallets）。
+//! 两步验证：/api/v1/me/totp/* 启用 TOTP（恢复码）与 step-up；仲裁员、运营须启用后才能访问角色专属路由，裁决、风控参数与角色变更须 5 分钟内 step-up（见 totp）；
+//! 启用与每次校验、登录登出、会话吊销、密码/邮箱/角色变更记入账号安全事件哈希链，合规/法务经 /api/v1/ops/security-log/export 导出。
+//! 资料：GET/PUT /api/v1/me 为当前用户资料（昵称、头像、角色列表、导游资料；见 profile），GET /api/v1/me/stats 为按角色统计摘要（见 stats）；
+//! 会话：/api/v1/me/sessions 查看登录设备（标签、IP、User-Agent、最近活跃）、逐个吊销与退出所有设备（见 sessions）；
+//! 登录与活跃的 IP、设备 id 汇入 Sybil 信号，运营经 /api/v1/ops/sybil-signals 复核同 IP/设备多账号。
+//! 订单签名：下单与接单须附绑定钱包的 EIP-712 签名（OrderIntent / OrderAcceptance），签名域与类型定义经 GET /api/v1/signing/eip712 公开（见 orders）。
+//! 授权：每条路由的允许角色与资源归属规则见 core::rbac 路由表（新增路由须同步登记），拒绝返回结构化 403（见 rbac）；
+//! `traveltrust-api --rbac-matrix` 输出路由权限矩阵（docs/01-附-路由权限矩阵.md 由此生成）。
+//! 路由：/health、/api/v1/guides 为占位实现；POST /api/v1/guides、POST /api/v1/orders、POST /api/v1/orders/:id/accept、/api/v1/orders/:id/reviews（见 reviews）、POST /api/v1/orders/:id/dispute、POST /api/v1/orders/:id/evidence 与分片续传 /api/v1/orders/:id/evidence/uploads/*（见 uploads）、GET /api/v1/orders/:id/evidence 及签名下载链接（见 downloads）、GET /api/v1/evidence/receipt-keys、GET /api/v1/disputes[/:id]、POST /api/v1/disputes/:id/resolve、POST /api/v1/timestamps 与 GET /api/v1/timestamps/keys（见 tsa）为内存存储实现（见 store）；其余为 501 占位，实现时按 04 §三 与 01 §10 17 条（幂等、traceId）补齐。
+//! 资金状态：执行器经 /api/v1/ops/orders/:id/escrow 与 /api/v1/ops/orders/:id/settlement 登记链上托管锁定与放款/退款结果（见 settlements）。
+//! 风控：异常争议率自动冻结类别新单（08-3、Runbook §11），运营经 /api/v1/ops/freezes 查询与解除；
+//! 争议发起时自动指派仲裁员，运营经 /api/v1/ops/disputes/:id/assign 指派或改派（见 disputes）；
+//! 裁决偏差（见 disputes）越线冻结类别或仲裁员、登记强制复核，经 /api/v1/ops/arbitrator-freezes、/api/v1/ops/second-reviews 查询与解除；
+//! 争议容量模式（Normal/Extended/Paused，W-D3-CAPACITY）经 /api/v1/ops/dispute-capacity 查询与维护在岗仲裁员数；
+//! 争议 SLA 时钟每分钟推进一次并打印违约告警，经 /api/v1/ops/sla 查询，稳定币冻结事件经 /api/v1/ops/stablecoin-freeze 登记（冻结期间 SLA 暂停）。
+//! 证据访问：GET /api/v1/orders/:id/evidence 与签名下载链接（见 downloads）逐条记哈希链审计，合规/法务经 /api/v1/ops/evidence-access-log/export 导出（见 audit）。
+//! 证据保留：争议开启自动法律保全，保留期（1095 天）满且无保全的原文件定时删除（见 retention），经 /api/v1/ops/legal-holds、/api/v1/ops/evidence-deletions 管理与查询；
+//! 证据多副本存储健康与中断时长经 /api/v1/ops/evidence-storage 查询，主存储中断达 7 天暂停新争议（见 evidence_store）。
+//! 证据 hash 与裁决摘要按周期 Merkle 封批上链锚定，包含证明经 /api/v1/anchors/proof 查询（见 anchoring）。
+//! 请求体：全局 1MB；证据上传路由单独挂载，上限为 08-3 evidenceMaxSize（50MB）+ 表单余量，超时 10 分钟（见 evidence）；分片 PUT 上限为单片 4MB + 余量。
+//! 幂等：请求头 Idempotency-Key / X-Idempotency-Key 在中间件透传并回写；对 POST/PUT 做 key 去重与结果复用（01 §10 #14），缓存键=method+path+key+Authorization 摘要（不同账号互不复用），/auth/* 不缓存（令牌只下发一次），最多 1000 条。
+//! 环境变量：PORT（默认 3000）、JWT_SECRET（访问令牌 HS256 密钥，至少 32 字节；未设则每次启动随机生成）、MAIL_OUTBOX_DIR（本地发件箱，默认 data/outbox）、APP_BASE_URL（邮件链接前缀，默认 http://localhost:5173）、SIWE_DOMAIN（SIWE 消息 domain，默认取 APP_BASE_URL 的 host）、CHAIN_ID（订单签名域 chainId，默认 31337）、ESCROW_FACTORY_ADDRESS（签名域 verifyingContract，未设为零地址）、TRUST_X_FORWARDED_FOR（=1 时客户端 IP 取 X-Forwarded-For 末项，仅在可信反代之后开启）、CORS_ORIGINS（逗号分隔的允许 origin，未设则开发态允许任意；生产应设置）、DISPUTE_RATE_RULE（zscore:N 或 ratio:N，默认 zscore:3）、ARBITRATORS_ON_DUTY（默认 3）、EVIDENCE_DIR（默认 data/evidence，主证据存储）、EVIDENCE_REPLICA_DIRS（证据副本目录，逗号分隔）、EVIDENCE_URL_SECRET（证据下载链接 HMAC 密钥，至少 32 字节）、RECEIPT_SIGNING_KEYS（回执签名密钥，逗号分隔；末项为当前签发密钥 key_id:ed25519 种子十六进制，已轮换旧钥写 key_id:pub:公钥十六进制）、TSA_SIGNING_KEYS（时间戳服务签名密钥，格式同上）、ANCHOR_CHAIN（锚定链，目前仅 local 替身）、ANCHOR_INTERVAL_SECS（封批上链周期，默认 3600）。
+
+mod anchoring;
+mod audit;
+mod auth;
+mod disputes;
+mod downloads;
+mod error;
+mod evidence;
+mod evidence_store;
+mod guides;
+mod mailer;
+mod ops;
+mod orders;
+mod profile;
+mod rbac;
+mod receipts;
+mod redaction;
+mod retention;
+mod reviews;
+mod sessions;
+mod settlements;
+mod siwe;
+mod state;
+mod stats;
+mod store;
+mod totp;
+mod tsa;
+mod uploads;
+mod wallets;
 
 use axum::{
     body::Body,
-    extract::Path,
+    extract::{DefaultBodyLimit, Path},
     http::{header::HeaderName, header::HeaderValue, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use bytes::Bytes;
 use http_body_util::BodyExt;
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use state::AppState;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -39,6 +94,10 @@ fn main() {
 
 #[tokio::main]
 async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if env::args().any(|a| a == "--rbac-matrix") {
+        print!("{}", traveltrust_core::rbac::render_matrix());
+        return Ok(());
+    }
     let ssot_version = env::var("SSOT_VERSION").unwrap_or_else(|_| "unset".to_string());
     if env::var("STRICT_SSOT").as_deref() == Ok("1") && ssot_version == "unset" {
         eprintln!("STRICT_SSOT=1: SSOT_VERSION 未设置，拒绝启动");
@@ -68,39 +127,108 @@ async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     let idem_cache: Arc<RwLock<IdempotencyCache>> = Arc::new(RwLock::new(IdempotencyCache::default()));
     let idem_cache_clone = Arc::clone(&idem_cache);
+    let state = AppState::from_env();
+
+    // 证据上传不受全局 1MB 限制：单独路由、单独上限与超时，merge 在全局 body limit 之后
+    let evidence_upload = Router::new()
+        .route("/api/v1/orders/:id/evidence", post(evidence::upload_evidence))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(evidence::EVIDENCE_REQUEST_LIMIT))
+        .layer(TimeoutLayer::new(Duration::from_secs(600)))
+        .with_state(state.clone());
+    let evidence_chunks = Router::new()
+        .route("/api/v1/orders/:id/evidence/uploads/:upload_id/chunks/:index", put(uploads::put_chunk))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(uploads::UPLOAD_CHUNK_MAX_BYTES as usize + 64 * 1024))
+        .layer(TimeoutLayer::new(Duration::from_secs(120))) // 单片 4MB，弱网下留足余量
+        .with_state(state.clone());
+    tokio::spawn(uploads::sweep_expired_uploads(state.clone()));
+    tokio::spawn(retention::sweep_retention(state.clone()));
+    tokio::spawn(evidence_store::monitor_evidence_storage(state.clone()));
+    tokio::spawn(anchoring::anchor_periodically(state.clone()));
+    tokio::spawn(ops::advance_sla_periodically(state.clone()));
 
     let app = Router::new()
         .route("/health", get(health))
-        .route("/api/v1/guides", get(guides_list_placeholder).post(not_impl_v1))
+        .route("/api/v1/guides", get(guides_list_placeholder).post(guides::create_guide))
         .route("/api/v1/guides/:id", get(not_impl_guides_id))
         .route("/api/v1/guides/:id/stake", post(not_impl_v1))
-        .route("/api/v1/me", get(not_impl_me).put(not_impl_v1))
-        .route("/api/v1/me/stats", get(not_impl_me)) // 04 §三 可选：统计摘要，与 /api/v1/me 二选一或并存
-        .route("/api/v1/me/password", put(not_impl_v1))
-        .route("/api/v1/orders", get(not_impl_orders).post(not_impl_v1))
+        .route("/api/v1/me", get(profile::get_profile).put(profile::update_profile))
+        .route("/api/v1/me/stats", get(stats::get_stats))
+        .route("/api/v1/me/password", put(auth::change_password))
+        .route("/api/v1/me/email", post(auth::link_email).put(auth::change_email))
+        .route("/api/v1/me/wallet/challenge", post(wallets::create_challenge))
+        .route("/api/v1/me/wallet", put(wallets::bind_wallet).delete(wallets::unbind_wallet))
+        .route("/api/v1/me/wallets", get(wallets::list_wallets))
+        .route("/api/v1/me/totp", get(totp::get_status).delete(totp::disable))
+        .route("/api/v1/me/totp/enroll", post(totp::enroll))
+        .route("/api/v1/me/totp/confirm", post(totp::confirm))
+        .route("/api/v1/me/totp/step-up", post(totp::step_up))
+        .route("/api/v1/me/totp/recovery-codes", post(totp::regenerate_recovery_codes))
+        .route("/api/v1/me/sessions", get(sessions::list_sessions).delete(sessions::revoke_all_sessions))
+        .route("/api/v1/me/sessions/:id", delete(sessions::revoke_session))
+        .route("/api/v1/orders", get(not_impl_orders).post(orders::create_order))
         .route("/api/v1/orders/:id", get(not_impl_orders_id))
-        .route("/api/v1/orders/:id/accept", post(not_impl_v1))
+        .route("/api/v1/orders/:id/accept", post(orders::accept_order))
+        .route("/api/v1/signing/eip712", get(orders::signing_domain))
         .route("/api/v1/orders/:id/cancel", post(not_impl_v1))
         .route("/api/v1/orders/:id/confirm-completion", post(not_impl_v1))
-        .route("/api/v1/orders/:id/reviews", get(not_impl_v1).post(not_impl_v1))
-        .route("/api/v1/orders/:id/evidence", get(not_impl_evidence).post(not_impl_v1)) // 04 §三 证据路径占位，实现时与 01 §6 定稿
-        .route("/api/v1/orders/:id/dispute", post(not_impl_v1))
-        .route("/api/v1/disputes", get(not_impl_disputes))
-        .route("/api/v1/disputes/:id", get(not_impl_disputes_id))
-        .route("/api/v1/disputes/:id/resolve", post(not_impl_v1))
-        .route("/auth/register", post(not_impl_auth))
-        .route("/auth/login", post(not_impl_auth))
-        .route("/auth/logout", post(not_impl_auth))
-        .route("/auth/refresh", post(not_impl_auth))
-        .route("/auth/verify-email", post(not_impl_auth))
-        .route("/auth/forgot-password", post(not_impl_auth))
-        .route("/auth/reset-password", post(not_impl_auth))
+        .route("/api/v1/orders/:id/reviews", get(reviews::list_reviews).post(reviews::create_review))
+        .route("/api/v1/orders/:id/evidence", get(evidence::list_evidence)) // 04 §三 证据路径；POST 见 evidence_upload
+        .route("/api/v1/orders/:id/evidence/uploads", post(uploads::create_upload))
+        .route("/api/v1/orders/:id/evidence/uploads/:upload_id", get(uploads::get_upload))
+        .route("/api/v1/orders/:id/evidence/uploads/:upload_id/complete", post(uploads::complete_upload))
+        .route("/api/v1/orders/:id/dispute", post(disputes::create_dispute))
+        .route("/api/v1/orders/:id/evidence/:evidence_id/url", get(downloads::issue_download_url))
+        .route("/api/v1/evidence/receipt-keys", get(evidence::receipt_keys))
+        .route("/api/v1/evidence/:evidence_id/download", get(downloads::download_evidence))
+        .route("/api/v1/disputes", get(disputes::list_disputes))
+        .route("/api/v1/disputes/:id", get(disputes::get_dispute))
+        .route("/api/v1/disputes/:id/resolve", post(disputes::resolve_dispute))
+        .route("/api/v1/timestamps", post(tsa::issue_timestamp))
+        .route("/api/v1/timestamps/keys", get(tsa::tsa_keys))
+        .route("/api/v1/ops/freezes", get(ops::list_freezes))
+        .route("/api/v1/ops/freezes/:id/lift", post(ops::lift_freeze))
+        .route("/api/v1/ops/arbitrator-freezes", get(ops::list_arbitrator_freezes))
+        .route("/api/v1/ops/arbitrator-freezes/:id/lift", post(ops::lift_arbitrator_freeze))
+        .route("/api/v1/ops/disputes/:id/assign", post(disputes::assign_dispute))
+        .route("/api/v1/ops/orders/:id/escrow", post(settlements::record_escrow))
+        .route("/api/v1/ops/orders/:id/settlement", post(settlements::record_settlement))
+        .route("/api/v1/ops/second-reviews", get(ops::list_second_reviews))
+        .route("/api/v1/ops/dispute-capacity", get(ops::get_dispute_capacity).put(ops::update_dispute_capacity))
+        .route("/api/v1/ops/sla", get(ops::list_sla))
+        .route("/api/v1/ops/stablecoin-freeze", post(ops::set_stablecoin_freeze))
+        .route("/api/v1/ops/evidence-access-log/export", get(audit::export_access_log))
+        .route("/api/v1/ops/security-log/export", get(audit::export_security_log))
+        .route("/api/v1/ops/legal-holds", get(ops::list_legal_holds).post(ops::place_legal_hold))
+        .route("/api/v1/ops/legal-holds/:id/release", post(ops::release_legal_hold))
+        .route("/api/v1/ops/evidence-deletions", get(ops::list_evidence_deletions))
+        .route("/api/v1/ops/evidence-storage", get(ops::get_evidence_storage))
+        .route("/api/v1/ops/anchors/flush", post(anchoring::flush_anchors))
+        .route("/api/v1/ops/sybil-signals", get(ops::list_sybil_signals))
+        .route("/api/v1/ops/users/:id/role", put(ops::set_user_role))
+        .route("/api/v1/anchors", get(anchoring::list_anchors))
+        .route("/api/v1/anchors/proof", get(anchoring::get_inclusion_proof))
+        .route("/api/v1/anchors/tx/:tx_hash", get(anchoring::get_chain_root))
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/refresh", post(auth::refresh))
+        .route("/auth/verify-email", post(auth::verify_email))
+        .route("/auth/forgot-password", post(auth::forgot_password))
+        .route("/auth/reset-password", post(auth::reset_password))
+        .route("/auth/siwe/nonce", post(siwe::issue_nonce))
+        .route("/auth/siwe/login", post(siwe::login))
+        .with_state(state.clone())
         .layer(TimeoutLayer::new(Duration::from_secs(30))) // 04 §四 请求超时，实现时可从配置读取
         .layer(RequestBodyLimitLayer::new(1024 * 1024)) // 1MB，与 04 风控一致
-        .layer(cors)
-        .layer(axum::middleware::from_fn(request_id_layer))
+        .merge(evidence_upload)
+        .merge(evidence_chunks)
+        // 鉴权在 request_id 之内（401 亦带 x-request-id），幂等在鉴权之后（缓存键含 Authorization 摘要）
         .layer(axum::middleware::from_fn(move |req, next| idempotency_key_layer(idem_cache_clone.clone(), req, next)))
-        .layer(axum::middleware::from_fn(auth_placeholder_layer));
+        .layer(axum::middleware::from_fn_with_state(state, auth::auth_layer))
+        .layer(axum::middleware::from_fn(request_id_layer))
+        .layer(cors);
 
     let port: u16 = env::var("PORT")
         .ok()
@@ -109,15 +237,11 @@ async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     println!("TravelTrust API listening on http://{}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // 连接对端地址供会话设备记录与 Sybil 信号（见 sessions）
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
     Ok(())
 }
 
-/// 鉴权占位：当前透传；实现时在此校验 JWT/session，需登录路由返回 401。04 §三 多处「需登录」。
-async fn auth_placeholder_layer(req: Request<axum::body::Body>, next: Next<axum::body::Body>) -> Response {
-    next.run(req).await
-}
-
 #[derive(Default)]
 struct IdempotencyCache {
     store: HashMap<String, (StatusCode, Vec<u8>)>,
@@ -151,11 +275,17 @@ async fn idempotency_key_layer(
         .map(String::from);
     let method = req.method().clone();
     let path = req.uri().path().to_string();
-    let is_write = method == Method::POST || method == Method::PUT;
+    let is_write = (method == Method::POST || method == Method::PUT) && !path.starts_with("/auth/");
+    // 同一 key 在不同账号间不复用响应
+    let principal_tag = req
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .map(|v| hex::encode(&Sha256::digest(v.as_bytes())[..8]))
+        .unwrap_or_default();
 
     if is_write {
         if let Some(ref k) = key {
-            let cache_key = format!("{}:{}:{}", method, path, k);
+            let cache_key = format!("{}:{}:{}:{}", method, path, principal_tag, k);
             {
                 let guard = cache.read().await;
                 if let Some((status, body)) = guard.get(&cache_key) {
@@ -188,7 +318,7 @@ async fn idempotency_key_layer(
 
     if is_write {
         if let Some(ref k) = key {
-            let cache_key = format!("{}:{}:{}", method, path, k);
+            let cache_key = format!("{}:{}:{}:{}", method, path, principal_tag, k);
             let (parts, body) = res.into_parts();
             match BodyExt::collect(body).await {
                 Ok(collected) => {
@@ -225,13 +355,17 @@ async fn idempotency_key_layer(
 }
 
 /// traceId：与 01 §9 贯通 requestId→txHash→logIndex 一致；响应头 x-request-id 供审计与资损排查。可观测：每请求打印 request_id + path + status（实现时可按 01 §9 SLO 接入结构化日志）。
-async fn request_id_layer(req: Request<axum::body::Body>, next: Next<axum::body::Body>) -> Response {
+async fn request_id_layer(mut req: Request<axum::body::Body>, next: Next<axum::body::Body>) -> Response {
     let id = req
         .headers()
         .get("x-request-id")
         .and_then(|v| v.to_str().ok())
         .map(String::from)
         .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    // 生成的 id 写回请求头，供 handler（证据访问审计等）取用
+    if let Ok(val) = HeaderValue::try_from(id.as_str()) {
+        req.headers_mut().insert("x-request-id", val);
+    }
     let path = req.uri().path().to_string();
     let mut res = next.run(req).await;
     eprintln!("[req] x-request-id={} path={} status={}", id, path, res.status().as_u16());
@@ -263,30 +397,15 @@ fn not_impl_json(path: &str) -> impl IntoResponse {
     )
 }
 
-async fn not_impl_me() -> impl IntoResponse {
-    not_impl_json("/api/v1/me")
-}
 async fn not_impl_orders() -> impl IntoResponse {
     not_impl_json("/api/v1/orders")
 }
 async fn not_impl_orders_id(Path(id): Path<String>) -> impl IntoResponse {
     not_impl_json(&format!("/api/v1/orders/{}", id))
 }
-async fn not_impl_disputes() -> impl IntoResponse {
-    not_impl_json("/api/v1/disputes")
-}
-async fn not_impl_disputes_id(Path(id): Path<String>) -> impl IntoResponse {
-    not_impl_json(&format!("/api/v1/disputes/{}", id))
-}
 async fn not_impl_guides_id(Path(id): Path<String>) -> impl IntoResponse {
     not_impl_json(&format!("/api/v1/guides/{}", id))
 }
-async fn not_impl_evidence(Path(id): Path<String>) -> impl IntoResponse {
-    not_impl_json(&format!("/api/v1/orders/{}/evidence", id))
-}
-async fn not_impl_auth() -> impl IntoResponse {
-    not_impl_json("/auth/*")
-}
 async fn not_impl_v1() -> impl IntoResponse {
     not_impl_json("/api/v1/*")
 }
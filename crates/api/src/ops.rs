@@ -0,0 +1,363 @@
+//! 运营接口：类别冻结查询与人工解除（08-3 异常争议率「自动冻结该类别新单」、Runbook §11）；
+//! 裁决偏差触发的仲裁员冻结查询与人工解除、强制复核列表（08-3「默认裁决连续偏向」、Runbook §8）；
+//! 争议容量查询与在岗仲裁员数维护（W-D3-CAPACITY、Runbook §8）；
+//! 稳定币冻结事件登记（暂停/恢复争议 SLA，08-3 freezeDisputePolicy）与 SLA 时钟/告警查询，时钟另由后台任务定时推进；
+//! 证据法律保全查询、人工加/解除与保留期删除记录查询（08-3 evidenceRetentionDays、Runbook §9）；证据存储各副本健康与中断时长查询；
+//! 账号角色指派（仲裁员、合规、法务不可自助注册，由运营指派，见 auth）；Sybil 关联信号（同 IP / 设备多账号，04 §四 人工复核）查询。
+//! 各接口允许角色见 core::rbac 路由表；操作人取当前登录账号，写入冻结解除与保全记录留痕。
+
+use crate::audit;
+use crate::auth::{AccountView, Principal};
+use crate::error::{ApiError, ApiResult};
+use crate::rbac;
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use traveltrust_core::availability::EVIDENCE_OUTAGE_PAUSE_DAYS;
+use traveltrust_core::capacity::CapacityAssessment;
+use traveltrust_core::sybil::{SYBIL_MIN_ACCOUNTS, SYBIL_WINDOW_DAYS};
+use traveltrust_core::{
+    ArbitratorFreeze, BackendHealth, CapacitySnapshot, CategoryFreeze, DeletionRecord, HoldReason, LegalHold, Ownership,
+    SecondReview, SecurityEvent, SignalKind, SlaAlert, SlaClock, UserRole,
+};
+use uuid::Uuid;
+
+pub async fn list_freezes(State(state): State<AppState>) -> Json<Vec<CategoryFreeze>> {
+    Json(state.risk.read().await.freezes.all().to_vec())
+}
+
+#[derive(Deserialize, Default)]
+pub struct LiftFreezeRequest {
+    pub note: Option<String>,
+}
+
+pub async fn lift_freeze(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    principal: Principal,
+    req: Option<Json<LiftFreezeRequest>>,
+) -> ApiResult<Json<CategoryFreeze>> {
+    let Json(req) = req.unwrap_or_default();
+    let operator = principal.user_id.to_string();
+    let mut risk = state.risk.write().await;
+    let lifted = risk
+        .freezes
+        .lift(id, &operator, req.note, Utc::now())
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("生效中的冻结"))?;
+    eprintln!("[risk] 类别冻结解除 id={} scope={:?} by={}", lifted.id, lifted.scope, operator);
+    Ok(Json(lifted))
+}
+
+pub async fn list_arbitrator_freezes(State(state): State<AppState>) -> Json<Vec<ArbitratorFreeze>> {
+    Json(state.risk.read().await.freezes.arbitrator_freezes().to_vec())
+}
+
+pub async fn lift_arbitrator_freeze(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    principal: Principal,
+    req: Option<Json<LiftFreezeRequest>>,
+) -> ApiResult<Json<ArbitratorFreeze>> {
+    let Json(req) = req.unwrap_or_default();
+    let operator = principal.user_id.to_string();
+    let mut risk = state.risk.write().await;
+    let lifted = risk
+        .freezes
+        .lift_arbitrator(id, &operator, req.note, Utc::now())
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("生效中的仲裁员冻结"))?;
+    eprintln!("[risk] 仲裁员冻结解除 id={} arbitrator={} by={}", lifted.id, lifted.arbitrator_id, operator);
+    Ok(Json(lifted))
+}
+
+pub async fn list_second_reviews(State(state): State<AppState>) -> Json<Vec<SecondReview>> {
+    Json(state.risk.read().await.second_reviews.all().to_vec())
+}
+
+#[derive(Serialize)]
+pub struct CapacityView {
+    pub snapshot: CapacitySnapshot,
+    pub assessment: CapacityAssessment,
+}
+
+pub async fn get_dispute_capacity(State(state): State<AppState>) -> Json<CapacityView> {
+    let mut store = state.store.write().await;
+    let (snapshot, assessment) = state.risk.write().await.assess_capacity(&mut store, Utc::now());
+    Json(CapacityView { snapshot, assessment })
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCapacityRequest {
+    pub active_arbitrators: usize,
+}
+
+pub async fn update_dispute_capacity(
+    State(state): State<AppState>,
+    Json(req): Json<UpdateCapacityRequest>,
+) -> Json<CapacityView> {
+    let mut store = state.store.write().await;
+    let mut risk = state.risk.write().await;
+    risk.active_arbitrators = req.active_arbitrators;
+    let (snapshot, assessment) = risk.assess_capacity(&mut store, Utc::now());
+    Json(CapacityView { snapshot, assessment })
+}
+
+#[derive(Deserialize)]
+pub struct StablecoinFreezeRequest {
+    /// true=冻结开始（暂停 SLA），false=冻结结束（恢复 SLA）
+    pub frozen: bool,
+}
+
+#[derive(Serialize)]
+pub struct StablecoinFreezeResult {
+    pub frozen: bool,
+    pub affected: usize,
+    /// 暂停被拒（次数已达上限，Runbook §11 SLA 暂停滥用）的争议及原因
+    pub rejected: Vec<(Uuid, String)>,
+}
+
+/// 稳定币冻结开始/结束：对全部未裁决争议暂停/恢复 SLA 计时
+pub async fn set_stablecoin_freeze(
+    State(state): State<AppState>,
+    Json(req): Json<StablecoinFreezeRequest>,
+) -> Json<StablecoinFreezeResult> {
+    let now = Utc::now();
+    let mut store = state.store.write().await;
+    let mut risk = state.risk.write().await;
+    risk.stablecoin_frozen_since = if req.frozen { Some(now) } else { None };
+    let mut affected = 0;
+    let mut rejected = Vec::new();
+    for clock in store.sla_clocks.values_mut().filter(|c| c.stopped_at.is_none()) {
+        let result = if req.frozen { clock.pause(now) } else { clock.resume(now) };
+        match result {
+            Ok(()) => affected += 1,
+            Err(e) => rejected.push((clock.dispute_id, e.to_string())),
+        }
+    }
+    eprintln!("[sla] 稳定币冻结={} 影响争议 {} 件，拒绝 {} 件", req.frozen, affected, rejected.len());
+    Json(StablecoinFreezeResult {
+        frozen: req.frozen,
+        affected,
+        rejected,
+    })
+}
+
+#[derive(Serialize)]
+pub struct SlaView {
+    pub clocks: Vec<SlaClock>,
+    pub alerts: Vec<SlaAlert>,
+}
+
+/// 推进全部 SLA 时钟到当前时刻并返回时钟与告警（违约、暂停次数/时长超限）
+pub async fn list_sla(State(state): State<AppState>) -> Json<SlaView> {
+    let mut store = state.store.write().await;
+    advance_sla_clocks(&mut store, Utc::now());
+    let mut clocks: Vec<SlaClock> = store.sla_clocks.values().cloned().collect();
+    clocks.sort_by_key(|c| c.started_at);
+    let alerts = clocks.iter().flat_map(|c| c.alerts.iter().cloned()).collect();
+    Json(SlaView { clocks, alerts })
+}
+
+/// SLA 巡检周期：无人查询 /api/v1/ops/sla 时也按时推进时钟并发出违约告警
+const SLA_TICK_INTERVAL_SECS: u64 = 60;
+
+pub async fn advance_sla_periodically(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(SLA_TICK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        advance_sla_clocks(&mut *state.store.write().await, Utc::now());
+    }
+}
+
+/// 推进全部时钟并打印本次新产生的告警；时钟内违约告警每争议只记一次，故同一违约只打印一次
+fn advance_sla_clocks(store: &mut Store, now: DateTime<Utc>) {
+    for clock in store.sla_clocks.values_mut() {
+        let seen = clock.alerts.len();
+        clock.advance(now);
+        for alert in &clock.alerts[seen..] {
+            eprintln!("[sla] 告警 dispute={} {:?}", alert.dispute_id, alert.kind);
+        }
+    }
+}
+
+pub async fn list_legal_holds(State(state): State<AppState>) -> Json<Vec<LegalHold>> {
+    Json(state.store.read().await.legal_holds.all().to_vec())
+}
+
+#[derive(Deserialize)]
+pub struct PlaceHoldRequest {
+    pub order_id: Uuid,
+    pub note: String,
+}
+
+/// 人工保全（诉讼、司法协助）：订单下证据原文件暂停保留期删除
+pub async fn place_legal_hold(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<PlaceHoldRequest>,
+) -> ApiResult<Json<LegalHold>> {
+    let mut store = state.store.write().await;
+    if !store.orders.contains_key(&req.order_id) {
+        return Err(ApiError::not_found("订单"));
+    }
+    let note = format!("{}: {}", principal.user_id, req.note.trim());
+    let hold = store
+        .legal_holds
+        .place(req.order_id, HoldReason::Manual { note }, Utc::now())
+        .clone();
+    eprintln!("[retention] 法律保全 id={} order={} by={}", hold.id, hold.order_id, principal.user_id);
+    Ok(Json(hold))
+}
+
+/// 解除人工保全；争议保全随裁决自动释放，不可人工解除
+pub async fn release_legal_hold(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    principal: Principal,
+) -> ApiResult<Json<LegalHold>> {
+    let operator = principal.user_id.to_string();
+    let mut store = state.store.write().await;
+    let released = store
+        .legal_holds
+        .release(id, &operator, Utc::now())
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("生效中的人工保全"))?;
+    eprintln!("[retention] 法律保全解除 id={} order={} by={}", released.id, released.order_id, operator);
+    Ok(Json(released))
+}
+
+pub async fn list_evidence_deletions(State(state): State<AppState>) -> Json<Vec<DeletionRecord>> {
+    Json(state.store.read().await.evidence_deletions.clone())
+}
+
+#[derive(Serialize)]
+pub struct EvidenceStorageView {
+    pub backends: Vec<BackendHealth>,
+    pub outage_pause_days: i64,
+    /// 主存储中断已达阈值，新争议暂停受理
+    pub intake_paused: bool,
+}
+
+pub async fn get_evidence_storage(State(state): State<AppState>) -> Json<EvidenceStorageView> {
+    Json(EvidenceStorageView {
+        backends: state.evidence_replicas.health(),
+        outage_pause_days: EVIDENCE_OUTAGE_PAUSE_DAYS,
+        intake_paused: state.risk.read().await.evidence_storage_outage,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct SybilQuery {
+    /// 观察窗口天数（1–365，默认 30）
+    pub days: Option<i64>,
+    /// 至少关联的账号数（默认 2）
+    pub min_accounts: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct LinkedAccountView {
+    pub user_id: Uuid,
+    /// 账号已不存在时为 None
+    pub role: Option<UserRole>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub sessions: u32,
+}
+
+#[derive(Serialize)]
+pub struct SybilSignalView {
+    pub kind: SignalKind,
+    /// IP 原值；设备为设备 id 的 sha256
+    pub value: String,
+    /// 其中导游账号数（同设备/IP 多导游为重点复核对象）
+    pub guide_accounts: usize,
+    pub accounts: Vec<LinkedAccountView>,
+}
+
+#[derive(Serialize)]
+pub struct SybilSignalsView {
+    pub window_days: i64,
+    pub min_accounts: usize,
+    pub signals: Vec<SybilSignalView>,
+}
+
+/// 同 IP / 设备关联多个账号的线索，仅供人工复核（共享出口 IP 会误报）
+pub async fn list_sybil_signals(
+    State(state): State<AppState>,
+    Query(query): Query<SybilQuery>,
+) -> ApiResult<Json<SybilSignalsView>> {
+    let window_days = query.days.unwrap_or(SYBIL_WINDOW_DAYS);
+    if !(1..=365).contains(&window_days) {
+        return Err(ApiError::bad_request("invalid_window", "days 须在 1–365 之间"));
+    }
+    let min_accounts = query.min_accounts.unwrap_or(SYBIL_MIN_ACCOUNTS).max(SYBIL_MIN_ACCOUNTS);
+    let store = state.store.read().await;
+    let signals = store
+        .sybil
+        .shared(Utc::now() - Duration::days(window_days), min_accounts)
+        .into_iter()
+        .map(|signal| {
+            let accounts: Vec<LinkedAccountView> = signal
+                .accounts
+                .into_iter()
+                .map(|a| LinkedAccountView {
+                    role: store.users.get(&a.user_id).map(|u| u.user.role.clone()),
+                    user_id: a.user_id,
+                    first_seen: a.first_seen,
+                    last_seen: a.last_seen,
+                    sessions: a.sessions,
+                })
+                .collect();
+            SybilSignalView {
+                kind: signal.kind,
+                value: signal.value,
+                guide_accounts: accounts.iter().filter(|a| a.role == Some(UserRole::Guide)).count(),
+                accounts,
+            }
+        })
+        .collect();
+    Ok(Json(SybilSignalsView {
+        window_days,
+        min_accounts,
+        signals,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct SetRoleRequest {
+    pub role: UserRole,
+}
+
+/// 指派账号角色；新角色在下一次请求即生效（鉴权中间件按当前账号取角色）。不可改本人角色，防止运营自我提权或误锁
+pub async fn set_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<SetRoleRequest>,
+) -> ApiResult<Json<AccountView>> {
+    rbac::ensure_owner(id != principal.user_id, Ownership::NotSelf, &principal.role)?;
+    let mut store = state.store.write().await;
+    let account = store.users.get_mut(&id).ok_or_else(|| ApiError::not_found("用户"))?;
+    let previous = std::mem::replace(&mut account.user.role, req.role);
+    account.user.updated_at = Utc::now();
+    let view = AccountView::from(&*account);
+    drop(store);
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::RoleChanged,
+        format!("user={} from={} to={}", id, previous.as_str(), view.role.as_str()),
+        audit::request_id(&headers),
+    )
+    .await;
+    Ok(Json(view))
+}
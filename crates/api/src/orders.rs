@@ -0,0 +1,227 @@
+//! 下单（04 §三 3.3 POST /api/v1/orders）：订单以 Created 入库，资金状态后续仅由链上事件驱动（01 §3）。
+//! 游客为当前登录账号（不可预订本人的导游资料）；导游接单 POST /api/v1/orders/:id/accept 仅限订单指派导游，Created → Accepted，
+//! 被暂停（suspended）的导游不可接单（01 §7 冻结）。
+//! 类别冻结（08-3 异常争议率、Runbook §11）：订单所属服务类型/导游城市/国家任一被冻结时拒绝新单，直至运营解除。
+//! EIP-712 签名（03 §1.1、04 §二「签名钱包与主身份校验」，结构见 traveltrust_core::eip712）：下单须附游客钱包对 OrderIntent 的签名，
+//! 接单须附导游钱包对 OrderAcceptance 的签名；恢复出的签名人须为账号当前绑定钱包（未绑定 409 wallet_not_bound，不符 403 signer_mismatch），
+//! 签名与摘要写入订单留痕。orderId 由客户端生成（UUID，签名前即确定），已存在即 409；签名域与类型定义经 GET /api/v1/signing/eip712 公开。
+
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::rbac;
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::env;
+use traveltrust_core::eip712::{self, Eip712Error, TypedStruct, DOMAIN_FIELDS, TERMS_VERSION};
+use traveltrust_core::{
+    Eip712Domain, GuideStatus, Order, OrderAcceptance, OrderIntent, OrderSignature, OrderState, Ownership, RiskScope,
+    ServiceType, SignedAction, TokenAmount,
+};
+use uuid::Uuid;
+
+/// 未配置时的本地链 chain_id（与 anvil/hardhat 一致）
+const DEFAULT_CHAIN_ID: u64 = 31337;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// CHAIN_ID（默认 31337）与 ESCROW_FACTORY_ADDRESS（EscrowFactory，未设为零地址，仅开发）
+pub fn signing_domain_from_env() -> Eip712Domain {
+    let chain_id = env::var("CHAIN_ID")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_CHAIN_ID);
+    let factory = env::var("ESCROW_FACTORY_ADDRESS").unwrap_or_else(|_| ZERO_ADDRESS.to_string());
+    Eip712Domain::new(chain_id, &factory).unwrap_or_else(|_| {
+        eprintln!("ESCROW_FACTORY_ADDRESS 格式无效（{}），签名域使用零地址（仅建议用于开发）", factory);
+        Eip712Domain::new(chain_id, ZERO_ADDRESS).expect("zero address")
+    })
+}
+
+/// 签名域、域分隔值、eth_signTypedData_v4 类型定义与当前条款版本
+pub async fn signing_domain(State(state): State<AppState>) -> ApiResult<Json<Value>> {
+    let separator = state.eip712.separator().map_err(signature_error)?;
+    let fields = |f: &[(&str, &str)]| -> Vec<Value> { f.iter().map(|(n, t)| json!({ "name": n, "type": t })).collect() };
+    Ok(Json(json!({
+        "domain": *state.eip712,
+        "domain_separator": eip712::hex32(&separator),
+        "types": {
+            "EIP712Domain": fields(DOMAIN_FIELDS),
+            OrderIntent::TYPE_NAME: fields(OrderIntent::FIELDS),
+            OrderAcceptance::TYPE_NAME: fields(OrderAcceptance::FIELDS),
+        },
+        "terms_version": TERMS_VERSION,
+        "terms_version_hash": eip712::terms_version_hash(),
+        "order_id_encoding": "bytes32 = 16 字节 0 ‖ UUID 16 字节",
+    })))
+}
+
+fn signature_error(e: Eip712Error) -> ApiError {
+    let code = match e {
+        Eip712Error::InvalidField(_) => "invalid_typed_data",
+        Eip712Error::Expired => "signature_expired",
+        Eip712Error::NonceNotIncreasing { .. } => "nonce_reused",
+        Eip712Error::TermsVersionMismatch => "terms_version_mismatch",
+        Eip712Error::Signature(_) => "invalid_signature",
+    };
+    ApiError::bad_request(code, e.to_string())
+}
+
+/// 账号当前绑定钱包
+fn bound_wallet(store: &Store, user_id: Uuid) -> ApiResult<String> {
+    store
+        .users
+        .get(&user_id)
+        .and_then(|a| a.user.default_wallet_address.clone())
+        .ok_or_else(|| ApiError::conflict("wallet_not_bound", "须先绑定钱包（/api/v1/me/wallet）后再签名下单或接单"))
+}
+
+/// 恢复签名人并与绑定钱包比对、校验 nonce 单调；通过后登记 nonce
+fn verify_order_signature<T: TypedStruct>(
+    state: &AppState,
+    store: &mut Store,
+    value: &T,
+    action: SignedAction,
+    signature: &str,
+    wallet: &str,
+) -> ApiResult<OrderSignature> {
+    let signed = eip712::recover_signer(&state.eip712, value, action, signature, Utc::now()).map_err(signature_error)?;
+    if signed.signer != wallet {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "signer_mismatch", "签名钱包不是当前账号绑定的钱包")
+            .with_detail(json!({ "recovered": signed.signer, "bound_wallet": wallet })));
+    }
+    store.signer_nonces.check(&signed.signer, signed.nonce).map_err(signature_error)?;
+    store.signer_nonces.commit(&signed.signer, signed.nonce);
+    Ok(signed)
+}
+
+/// 下单/接单请求中的签名部分
+#[derive(Deserialize)]
+pub struct TypedSignature {
+    pub nonce: u64,
+    /// Unix 秒
+    pub expiry: i64,
+    pub terms_version_hash: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateOrderRequest {
+    /// 客户端生成，与 OrderIntent.orderId 一致
+    pub order_id: Uuid,
+    pub guide_id: Uuid,
+    pub service_type: ServiceType,
+    pub amount: String,
+    pub currency: String,
+    #[serde(flatten)]
+    pub signed: TypedSignature,
+}
+
+pub async fn create_order(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<CreateOrderRequest>,
+) -> ApiResult<(StatusCode, Json<Order>)> {
+    if !req.amount.parse::<TokenAmount>().is_ok_and(|a| !a.is_zero()) {
+        return Err(ApiError::bad_request("invalid_amount", "amount 须为正的十进制字符串（至多 18 位小数）"));
+    }
+    let mut store = state.store.write().await;
+    let guide = store.guides.get(&req.guide_id).ok_or_else(|| ApiError::not_found("导游"))?;
+    if guide.user_id == principal.user_id {
+        return Err(ApiError::bad_request("self_booking", "不可预订本人的导游服务"));
+    }
+    if !guide.service_types.contains(&req.service_type) {
+        return Err(ApiError::bad_request("service_type_not_offered", "导游未提供该服务类型"));
+    }
+    let scopes = RiskScope::for_guide(guide, &req.service_type);
+    if store.orders.contains_key(&req.order_id) {
+        return Err(ApiError::conflict("order_exists", "订单 id 已存在"));
+    }
+    let wallet = bound_wallet(&store, principal.user_id)?;
+    let intent = OrderIntent {
+        order_id: req.order_id,
+        tourist: wallet.clone(),
+        guide_id: req.guide_id,
+        service_type: eip712::service_type_code(&req.service_type),
+        amount: req.amount.clone(),
+        currency: req.currency.clone(),
+        nonce: req.signed.nonce,
+        expiry: req.signed.expiry,
+        terms_version_hash: req.signed.terms_version_hash.clone(),
+    };
+
+    let mut risk = state.risk.write().await;
+    if let Some(freeze) = risk.freezes.active_for(&scopes) {
+        return Err(ApiError::new(StatusCode::LOCKED, "category_frozen", "该类别新单已冻结，待运营解除")
+            .with_detail(json!(freeze)));
+    }
+
+    let intent_signature =
+        verify_order_signature(&state, &mut store, &intent, SignedAction::Intent, &req.signed.signature, &wallet)?;
+    let now = Utc::now();
+    let order = Order {
+        id: req.order_id,
+        tourist_id: principal.user_id,
+        guide_id: req.guide_id,
+        service_type: req.service_type,
+        amount: req.amount,
+        currency: req.currency,
+        state: OrderState::Created,
+        escrow_at: None,
+        completed_at: None,
+        created_at: now,
+        intent_signature: Some(intent_signature),
+        acceptance_signature: None,
+    };
+    risk.dispute_rates.record_order(now, scopes);
+    store.orders.insert(order.id, order.clone());
+    store.invalidate_order_stats(order.id);
+    Ok((StatusCode::CREATED, Json(order)))
+}
+
+/// 导游接单：仅订单指派导游，Created → Accepted；须附导游绑定钱包对 OrderAcceptance 的签名
+pub async fn accept_order(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    principal: Principal,
+    Json(req): Json<TypedSignature>,
+) -> ApiResult<Json<Order>> {
+    let mut store = state.store.write().await;
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    let guide = store.guides.get(&order.guide_id).ok_or_else(|| ApiError::not_found("导游"))?;
+    rbac::ensure_owner(guide.user_id == principal.user_id, Ownership::AssignedGuide, &principal.role)?;
+    if guide.status == GuideStatus::Suspended {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "guide_suspended", "导游资格已暂停，不可接单"));
+    }
+    if order.state != OrderState::Created {
+        return Err(ApiError::conflict("invalid_order_state", "仅待接单（created）的订单可接单"));
+    }
+    let intent_hash = order
+        .intent_signature
+        .as_ref()
+        .map(|s| s.struct_hash.clone())
+        .ok_or_else(|| ApiError::conflict("intent_signature_missing", "订单缺少下单签名，不可接单"))?;
+    let wallet = bound_wallet(&store, principal.user_id)?;
+    let acceptance = OrderAcceptance {
+        order_id,
+        guide: wallet.clone(),
+        intent_hash,
+        nonce: req.nonce,
+        expiry: req.expiry,
+        terms_version_hash: req.terms_version_hash,
+    };
+    let acceptance_signature =
+        verify_order_signature(&state, &mut store, &acceptance, SignedAction::Acceptance, &req.signature, &wallet)?;
+    let order = store.orders.get_mut(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    order.state = OrderState::Accepted;
+    order.acceptance_signature = Some(acceptance_signature);
+    let order = order.clone();
+    store.invalidate_order_stats(order_id);
+    Ok(Json(order))
+}
@@ -0,0 +1,144 @@
+//! 当前用户资料（04 §三 3.2 /api/v1/me、04 §二 2.1 users；校验规则见 traveltrust_core::profile）。
+//!
+//! 1. GET /api/v1/me                       账号资料、角色列表、两步验证状态；已注册导游资料时附 guide
+//! 2. PUT /api/v1/me {nickname?, avatar_url?} 部分更新：字段缺省为不变，null 或空串为清除；返回更新后的资料
+//!
+//! 响应为 DTO（ProfileView / GuideProfileView），不直接序列化存储类型：凭证与内部关联字段（password_hash、guide.user_id）不出库。
+//! 昵称超长或含控制字符返回 400 invalid_nickname；头像 URL 非 https 或格式无效返回 400 invalid_avatar_url。
+
+use crate::auth::{unauthenticated, Principal};
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use traveltrust_core::profile;
+use traveltrust_core::{Guide, GuideStatus, KycStatus, ServiceType, UserRole};
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct GuideProfileView {
+    pub id: Uuid,
+    pub city: String,
+    pub country_code: String,
+    pub languages: Vec<String>,
+    pub service_types: Vec<ServiceType>,
+    pub bio: Option<String>,
+    pub stake_amount: String,
+    pub status: GuideStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Guide> for GuideProfileView {
+    fn from(g: &Guide) -> Self {
+        Self {
+            id: g.id,
+            city: g.city.clone(),
+            country_code: g.country_code.clone(),
+            languages: g.languages.clone(),
+            service_types: g.service_types.clone(),
+            bio: g.bio.clone(),
+            stake_amount: g.stake_amount.clone(),
+            status: g.status.clone(),
+            created_at: g.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProfileView {
+    pub id: Uuid,
+    /// 仅以钱包（SIWE）登录、尚未关联邮箱的账号为 None
+    pub email: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub nickname: Option<String>,
+    pub avatar_url: Option<String>,
+    /// 当前绑定钱包（见 wallets）
+    pub default_wallet_address: Option<String>,
+    /// 鉴权所用角色（01 §7）
+    pub role: UserRole,
+    /// 账号具备的全部角色：role 之外，已有导游资料的账号（如后被指派为仲裁员）含 guide
+    pub roles: Vec<UserRole>,
+    pub kyc_status: KycStatus,
+    /// 已启用 TOTP（见 totp）
+    pub totp_enabled: bool,
+    pub guide: Option<GuideProfileView>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn profile_view(store: &Store, user_id: Uuid) -> ApiResult<ProfileView> {
+    let user = &store.users.get(&user_id).ok_or_else(unauthenticated)?.user;
+    let guide = store.guides.values().find(|g| g.user_id == user_id);
+    let mut roles = vec![user.role.clone()];
+    if guide.is_some() && user.role != UserRole::Guide {
+        roles.push(UserRole::Guide);
+    }
+    Ok(ProfileView {
+        id: user.id,
+        email: Some(user.email.clone()).filter(|e| !e.is_empty()),
+        email_verified_at: user.email_verified_at,
+        nickname: user.nickname.clone(),
+        avatar_url: user.avatar_url.clone(),
+        default_wallet_address: user.default_wallet_address.clone(),
+        role: user.role.clone(),
+        roles,
+        kyc_status: user.kyc_status.clone(),
+        totp_enabled: store.totp.is_enabled(user_id),
+        guide: guide.map(GuideProfileView::from),
+        created_at: user.created_at,
+        updated_at: user.updated_at,
+    })
+}
+
+pub async fn get_profile(State(state): State<AppState>, principal: Principal) -> ApiResult<Json<ProfileView>> {
+    profile_view(&*state.store.read().await, principal.user_id).map(Json)
+}
+
+/// 区分字段缺省（None）与显式 null（Some(None)）
+fn present<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::<String>::deserialize(d).map(Some)
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProfileRequest {
+    #[serde(default, deserialize_with = "present")]
+    pub nickname: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub avatar_url: Option<Option<String>>,
+}
+
+pub async fn update_profile(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<UpdateProfileRequest>,
+) -> ApiResult<Json<ProfileView>> {
+    let nickname = req
+        .nickname
+        .map(|n| profile::normalize_nickname(n.as_deref().unwrap_or_default()))
+        .transpose()
+        .map_err(|e| ApiError::bad_request("invalid_nickname", e.to_string()))?;
+    let avatar_url = req
+        .avatar_url
+        .map(|u| profile::normalize_avatar_url(u.as_deref().unwrap_or_default()))
+        .transpose()
+        .map_err(|e| ApiError::bad_request("invalid_avatar_url", e.to_string()))?;
+
+    let mut store = state.store.write().await;
+    let user = &mut store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?.user;
+    let mut changed = Vec::new();
+    if let Some(nickname) = nickname.filter(|n| *n != user.nickname) {
+        user.nickname = nickname;
+        changed.push("nickname");
+    }
+    if let Some(avatar_url) = avatar_url.filter(|u| *u != user.avatar_url) {
+        user.avatar_url = avatar_url;
+        changed.push("avatar_url");
+    }
+    if !changed.is_empty() {
+        user.updated_at = Utc::now();
+        eprintln!("[auth] 资料更新 user={} fields={}", principal.user_id, changed.join(","));
+    }
+    profile_view(&store, principal.user_id).map(Json)
+}
@@ -0,0 +1,43 @@
+//! 路由级授权（01 §7 权限与角色、P0-2）：角色校验在鉴权中间件按 traveltrust_core::rbac 路由表执行，
+//! 资源归属（订单双方、指派导游、被指派仲裁员等）由 handler 加载资源后经 ensure_owner 校验。
+//! 两类拒绝均为结构化 403：status 为 forbidden_role / forbidden_owner，detail 给出路由、当前角色与允许角色或归属规则，便于前端提示与审计。
+
+use crate::error::ApiError;
+use axum::http::StatusCode;
+use serde_json::json;
+use traveltrust_core::{Ownership, RoutePolicy, UserRole};
+
+/// 角色不在路由允许列表
+pub fn forbidden_role(policy: &RoutePolicy, role: &UserRole) -> ApiError {
+    let allowed: Vec<&str> = match policy.audience {
+        traveltrust_core::Audience::Roles(roles) => roles.iter().map(UserRole::as_str).collect(),
+        _ => Vec::new(),
+    };
+    ApiError::new(StatusCode::FORBIDDEN, "forbidden_role", "当前角色无权访问该接口").with_detail(json!({
+        "route": policy.route(),
+        "role": role.as_str(),
+        "allowed_roles": allowed,
+    }))
+}
+
+/// 不满足资源归属规则
+pub fn forbidden_owner(rule: Ownership, role: &UserRole) -> ApiError {
+    ApiError::new(
+        StatusCode::FORBIDDEN,
+        "forbidden_owner",
+        format!("不满足资源归属规则：{}", rule.description()),
+    )
+    .with_detail(json!({
+        "rule": rule.as_str(),
+        "role": role.as_str(),
+    }))
+}
+
+/// 归属校验：allowed 为 false 时按 rule 拒绝
+pub fn ensure_owner(allowed: bool, rule: Ownership, role: &UserRole) -> Result<(), ApiError> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(forbidden_owner(rule, role))
+    }
+}
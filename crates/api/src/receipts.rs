@@ -0,0 +1,192 @@
+//! 证据上传回执签名（Runbook §5「上传成功必出 receipt…服务器签名」）：ed25519，按 key_id 轮换。
+//! 密钥来自 env RECEIPT_SIGNING_KEYS（逗号分隔，最后一项为当前签发密钥 `key_id:32 字节种子十六进制`，其余为已轮换旧钥，仅公布公钥供验签）。
+//! 已轮换旧钥只需公钥：写作 `key_id:pub:32 字节公钥十六进制`，私钥种子即可从配置中删除（轮换出泄露密钥时必须如此）；
+//! 仍写作种子的旧项在启动时只取其公钥，种子不保留在内存，并打印提示改为公钥形式。
+//! 轮换：在列表末尾追加新钥、把原签发项改为公钥形式并重启；旧钥须保留在列表中，否则其签发的回执无法按 key_id 找到公钥。
+//! 未设时启动生成临时密钥，仅用于开发（重启后旧回执无法验签）。验签见 traveltrust_core::evidence::verify_receipt。
+//! 时间戳服务（tsa）使用同格式的独立密钥环。
+
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use std::env;
+use traveltrust_core::evidence::RECEIPT_ALGORITHM;
+use traveltrust_core::{DerivedEvidence, EvidenceReceipt, ReceiptPublicKey, SignedEvidenceReceipt};
+
+/// 可轮换的 ed25519 密钥环
+pub struct KeyRing {
+    /// 当前签发密钥
+    active: (String, SigningKey),
+    /// 已轮换旧钥（只有公钥，用于公布）
+    retired: Vec<(String, VerifyingKey)>,
+}
+
+impl KeyRing {
+    /// 从 env `var` 读取；未设或格式错误时生成 `{dev_prefix}-xxxxxxxx` 临时密钥
+    pub fn from_env(var: &str, dev_prefix: &str) -> Self {
+        match env::var(var).ok().and_then(|s| parse_keys(&s)) {
+            Some(ring) => {
+                if ring.1 {
+                    eprintln!("{} 的已轮换旧钥仍为私钥种子，已只取公钥；请改为 key_id:pub:公钥十六进制", var);
+                }
+                ring.0
+            }
+            None => {
+                eprintln!("{} 未设置或格式错误，使用临时签名密钥（仅建议用于开发）", var);
+                let key = SigningKey::generate(&mut rand::rngs::OsRng);
+                let key_id = format!("{}-{}", dev_prefix, &hex::encode(key.verifying_key().to_bytes())[..8]);
+                Self {
+                    active: (key_id, key),
+                    retired: Vec::new(),
+                }
+            }
+        }
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.active.0
+    }
+
+    /// 用当前密钥签名，返回十六进制签名
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex::encode(self.active.1.sign(message).to_bytes())
+    }
+
+    /// 全部验签公钥（当前 + 已轮换）
+    pub fn public_keys(&self) -> Vec<ReceiptPublicKey> {
+        let public = |key_id: &str, key: &VerifyingKey, active: bool| ReceiptPublicKey {
+            key_id: key_id.to_string(),
+            algorithm: RECEIPT_ALGORITHM.to_string(),
+            public_key: hex::encode(key.to_bytes()),
+            active,
+        };
+        std::iter::once(public(&self.active.0, &self.active.1.verifying_key(), true))
+            .chain(self.retired.iter().map(|(key_id, key)| public(key_id, key, false)))
+            .collect()
+    }
+}
+pub struct ReceiptSigner {
+    keys: KeyRing,
+}
+
+impl ReceiptSigner {
+    pub fn from_env() -> Self {
+        Self {
+            keys: KeyRing::from_env("RECEIPT_SIGNING_KEYS", "dev"),
+        }
+    }
+
+    /// 签发回执；时间戳由调用方另行加盖（见 evidence::issue_receipt）
+    pub fn sign(&self, receipt: EvidenceReceipt) -> SignedEvidenceReceipt {
+        let key_id = self.keys.active_key_id().to_string();
+        SignedEvidenceReceipt {
+            signature: self.keys.sign(&receipt.signing_bytes(&key_id)),
+            key_id,
+            algorithm: RECEIPT_ALGORITHM.to_string(),
+            receipt,
+            timestamp: None,
+        }
+    }
+
+    /// 派生件与来源原文件绑定签名（traveltrust_core::evidence::verify_derivation）
+    pub fn sign_derivation(&self, mut derived: DerivedEvidence) -> DerivedEvidence {
+        derived.key_id = self.keys.active_key_id().to_string();
+        derived.signature = self.keys.sign(&derived.signing_bytes(&derived.key_id));
+        derived
+    }
+
+    pub fn public_keys(&self) -> Vec<ReceiptPublicKey> {
+        self.keys.public_keys()
+    }
+}
+
+enum ParsedKey {
+    Seed(SigningKey),
+    Public(VerifyingKey),
+}
+
+/// 解析 `kid:seedhex,...,kid:pub:pubhex,...,kid:seedhex`：末项须为种子（当前签发密钥），其余可为公钥或种子。
+/// 返回密钥环与「旧钥中是否含种子」；任一项非法则整体视为未配置，避免静默跳过导致用错签发密钥
+fn parse_keys(raw: &str) -> Option<(KeyRing, bool)> {
+    let parsed: Option<Vec<(String, ParsedKey)>> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|item| {
+            let (key_id, rest) = item.split_once(':')?;
+            let key_id = key_id.trim();
+            if key_id.is_empty() {
+                return None;
+            }
+            let key = match rest.trim().strip_prefix("pub:") {
+                Some(public) => {
+                    let bytes: [u8; 32] = hex::decode(public.trim()).ok()?.try_into().ok()?;
+                    ParsedKey::Public(VerifyingKey::from_bytes(&bytes).ok()?)
+                }
+                None => {
+                    let seed: [u8; 32] = hex::decode(rest.trim()).ok()?.try_into().ok()?;
+                    ParsedKey::Seed(SigningKey::from_bytes(&seed))
+                }
+            };
+            Some((key_id.to_string(), key))
+        })
+        .collect();
+    let mut keys = parsed?;
+    let active = match keys.pop()? {
+        (key_id, ParsedKey::Seed(key)) => (key_id, key),
+        (_, ParsedKey::Public(_)) => return None,
+    };
+    let had_seeds = keys.iter().any(|(_, k)| matches!(k, ParsedKey::Seed(_)));
+    let retired = keys
+        .into_iter()
+        .map(|(key_id, key)| match key {
+            ParsedKey::Seed(seed) => (key_id, seed.verifying_key()),
+            ParsedKey::Public(public) => (key_id, public),
+        })
+        .collect();
+    Some((KeyRing { active, retired }, had_seeds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_hex(byte: u8) -> String {
+        hex::encode([byte; 32])
+    }
+
+    fn public_hex(byte: u8) -> String {
+        hex::encode(SigningKey::from_bytes(&[byte; 32]).verifying_key().to_bytes())
+    }
+
+    #[test]
+    fn retired_keys_may_be_public_only() {
+        let raw = format!("k1:pub:{}, k2:{}", public_hex(1), seed_hex(2));
+        let (ring, had_seeds) = parse_keys(&raw).unwrap();
+        assert!(!had_seeds);
+        assert_eq!(ring.active_key_id(), "k2");
+        let keys = ring.public_keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!((keys[0].key_id.as_str(), keys[0].active), ("k2", true));
+        assert_eq!(keys[0].public_key, public_hex(2));
+        assert_eq!((keys[1].key_id.as_str(), keys[1].active), ("k1", false));
+        assert_eq!(keys[1].public_key, public_hex(1));
+    }
+
+    #[test]
+    fn retired_seeds_are_reduced_to_public_keys() {
+        let raw = format!("k1:{},k2:{}", seed_hex(1), seed_hex(2));
+        let (ring, had_seeds) = parse_keys(&raw).unwrap();
+        assert!(had_seeds);
+        assert_eq!(ring.retired.len(), 1);
+        assert_eq!(hex::encode(ring.retired[0].1.to_bytes()), public_hex(1));
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        // 当前签发密钥不能只有公钥
+        assert!(parse_keys(&format!("k1:{},k2:pub:{}", seed_hex(1), public_hex(2))).is_none());
+        assert!(parse_keys(&format!("k1:pub:{}", &seed_hex(1)[..10])).is_none());
+        assert!(parse_keys(&format!(":{}", seed_hex(1))).is_none());
+        assert!(parse_keys(&format!("k1:{},k2", seed_hex(1))).is_none());
+        assert!(parse_keys("").is_none());
+    }
+}
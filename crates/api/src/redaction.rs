@@ -0,0 +1,119 @@
+//! 证据隐私处理流水线（P0-4、01 §6）：归档前对照片生成去元数据副本与缩略图（规则见 traveltrust_core::privacy）。
+//!
+//! 原文件按原字节归档（hash 与回执不变），仅限仲裁员与合规类角色下载；订单双方获取去元数据副本（见 downloads）。
+//! 派生件各自按 sha256 存入 EvidenceStore，并由回执密钥签名绑定来源原文件 hash。处理失败不影响上传，只是不产出派生件。
+
+use crate::evidence::Staged;
+use crate::state::AppState;
+use chrono::Utc;
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::PathBuf;
+use traveltrust_core::privacy::{self, requires_redaction};
+use traveltrust_core::{DerivedEvidence, DerivedKind, StorageLocation};
+use uuid::Uuid;
+
+/// 缩略图最长边（像素）
+const THUMBNAIL_MAX_EDGE: u32 = 320;
+/// 解码内存上限，防图片解压炸弹
+const DECODE_MAX_ALLOC_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 派生件种类、MIME 与字节
+type DerivedOutput = (DerivedKind, &'static str, Vec<u8>);
+
+/// 已写入暂存目录、待归档的派生件
+pub(crate) struct StagedDerivative {
+    pub(crate) derived: DerivedEvidence,
+    pub(crate) tmp_path: PathBuf,
+}
+
+/// 生成派生件并写入暂存文件；非照片或处理失败时返回空
+pub(crate) async fn derive(state: &AppState, staged: &Staged) -> Vec<StagedDerivative> {
+    if !requires_redaction(staged.sniffed_mime) {
+        return Vec::new();
+    }
+    let original = match tokio::fs::read(&staged.tmp_path).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("[evidence] 隐私处理读取暂存失败 sha256={}: {}", staged.sha256, e);
+            return Vec::new();
+        }
+    };
+    let mime = staged.sniffed_mime;
+    let outputs = tokio::task::spawn_blocking(move || process(mime, &original)).await;
+    let outputs = match outputs {
+        Ok(Ok(outputs)) => outputs,
+        Ok(Err(e)) => {
+            eprintln!("[evidence] 隐私处理跳过 sha256={}: {}", staged.sha256, e);
+            return Vec::new();
+        }
+        Err(e) => {
+            eprintln!("[evidence] 隐私处理异常 sha256={}: {}", staged.sha256, e);
+            return Vec::new();
+        }
+    };
+
+    let mut derivatives = Vec::new();
+    for (kind, mime, bytes) in outputs {
+        let sha256 = hex::encode(Sha256::digest(&bytes));
+        let tmp_path = state.evidence_dir.join(format!(".derived-{}", Uuid::new_v4()));
+        if let Err(e) = tokio::fs::write(&tmp_path, &bytes).await {
+            eprintln!("[evidence] 派生件暂存失败 sha256={}: {}", staged.sha256, e);
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            continue;
+        }
+        let derived = state.receipts.sign_derivation(DerivedEvidence {
+            kind,
+            location: StorageLocation {
+                backend: state.evidence_store.backend().to_string(),
+                key: sha256.clone(),
+            },
+            sha256,
+            source_sha256: staged.sha256.clone(),
+            mime: mime.to_string(),
+            size_bytes: bytes.len() as u64,
+            created_at: Utc::now(),
+            key_id: String::new(),
+            signature: String::new(),
+        });
+        derivatives.push(StagedDerivative { derived, tmp_path });
+    }
+    derivatives
+}
+
+pub(crate) async fn cleanup(derivatives: &[StagedDerivative]) {
+    for d in derivatives {
+        let _ = tokio::fs::remove_file(&d.tmp_path).await;
+    }
+}
+
+/// 去元数据副本（无损剔除）+ 由副本解码生成的 JPEG 缩略图；缩略图失败时只返回副本
+fn process(mime: &'static str, original: &[u8]) -> Result<Vec<DerivedOutput>, String> {
+    let redacted = privacy::strip_metadata(mime, original).map_err(|e| e.to_string())?;
+    let mut outputs = Vec::with_capacity(2);
+    match thumbnail(mime, &redacted) {
+        Ok(thumb) => outputs.push((DerivedKind::Thumbnail, "image/jpeg", thumb)),
+        Err(e) => eprintln!("[evidence] 缩略图生成失败: {}", e),
+    }
+    outputs.insert(0, (DerivedKind::Redacted, mime, redacted));
+    Ok(outputs)
+}
+
+fn thumbnail(mime: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let format = ImageFormat::from_mime_type(mime).ok_or_else(|| format!("无法解码 {}", mime))?;
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(DECODE_MAX_ALLOC_BYTES);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| e.to_string())?;
+    let image = if image.width() > THUMBNAIL_MAX_EDGE || image.height() > THUMBNAIL_MAX_EDGE {
+        image.resize(THUMBNAIL_MAX_EDGE, THUMBNAIL_MAX_EDGE, FilterType::Triangle)
+    } else {
+        image
+    };
+    let thumb = image.to_rgb8();
+    let mut out = Cursor::new(Vec::new());
+    thumb.write_to(&mut out, ImageFormat::Jpeg).map_err(|e| e.to_string())?;
+    Ok(out.into_inner())
+}
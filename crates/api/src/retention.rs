@@ -0,0 +1,143 @@
+//! 证据保留期清理（08-3 evidenceRetentionDays=1095）：定时删除保留期满且无法律保全的原文件，元数据、hash 与回执保留。
+//!
+//! 同 hash 对象被多条证据共用（按 sha256 去重存储），仅当引用它的证据全部到期删除后才从 EvidenceStore 删除；派生件随原文件删除。
+//! 每次删除写入 Store.evidence_deletions 并打印 [retention] 日志（Runbook §9 司法协助可据此证明文件曾存在）。
+
+use crate::state::AppState;
+use crate::store::Store;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use traveltrust_core::{DeletionRecord, Evidence, RetentionPolicy};
+use uuid::Uuid;
+
+/// 清理周期
+const RETENTION_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+pub async fn sweep_retention(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(RETENTION_SWEEP_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        run_sweep(&state, Utc::now()).await;
+    }
+}
+
+/// 到期证据的待删对象：original 为 None 表示原文件仍被其他证据引用，只标记删除
+struct PlannedDeletion {
+    evidence: Evidence,
+    original: Option<String>,
+    derived: Vec<String>,
+}
+
+/// 三步清理：持 store 写锁标记到期证据并登记待删对象键（Store.deleting_keys），释放锁后逐副本删除对象，再持锁落删除记录。
+/// 归档中的对象键（Store.archiving_keys）视为在用不删；待删键上的新归档等删除完成后再写入（见 evidence::archive）
+pub async fn run_sweep(state: &AppState, now: DateTime<Utc>) -> Vec<DeletionRecord> {
+    let policy = RetentionPolicy::default();
+    let plans = {
+        let mut store = state.store.write().await;
+        plan_deletions(&mut store, &policy, now)
+    };
+    if plans.is_empty() {
+        return Vec::new();
+    }
+
+    // 原文件删除失败的证据 id（回滚标记，下轮重试）与各证据原文件是否已删除
+    let mut failed = Vec::new();
+    let mut removed = HashMap::new();
+    for plan in &plans {
+        let evidence = &plan.evidence;
+        let file_removed = match &plan.original {
+            None => false,
+            Some(key) => match state.evidence_store.delete(key).await {
+                Ok(()) => true,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+                Err(e) => {
+                    eprintln!("[retention] 删除失败 evidence={} sha256={}: {}", evidence.id, evidence.sha256, e);
+                    failed.push(evidence.id);
+                    continue;
+                }
+            },
+        };
+        removed.insert(evidence.id, file_removed);
+        // 派生件（去元数据副本、缩略图）随原文件删除；失败只记日志，原文件删除已生效
+        for key in &plan.derived {
+            match state.evidence_store.delete(key).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => eprintln!("[retention] 派生件删除失败 evidence={} key={}: {}", evidence.id, key, e),
+            }
+        }
+    }
+
+    let mut store = state.store.write().await;
+    let mut records = Vec::with_capacity(plans.len());
+    for plan in plans {
+        if let Some(key) = &plan.original {
+            store.deleting_keys.remove(key);
+        }
+        for key in &plan.derived {
+            store.deleting_keys.remove(key);
+        }
+        let evidence = plan.evidence;
+        if failed.contains(&evidence.id) {
+            if let Some(ev) = store.evidence.get_mut(&evidence.id) {
+                ev.deleted_at = None;
+            }
+            continue;
+        }
+        let record = DeletionRecord {
+            evidence_id: evidence.id,
+            sha256: evidence.sha256.clone(),
+            order_id: evidence.order_id,
+            size_bytes: evidence.size_bytes,
+            uploaded_at: evidence.uploaded_at,
+            retention_expired_at: policy.expires_at(&evidence),
+            deleted_at: now,
+            file_removed: removed.get(&evidence.id).copied().unwrap_or(false),
+        };
+        eprintln!(
+            "[retention] 原文件到期删除 evidence={} order={} sha256={} file_removed={}",
+            record.evidence_id, record.order_id, record.sha256, record.file_removed
+        );
+        store.evidence_deletions.push(record.clone());
+        records.push(record);
+    }
+    records
+}
+
+/// 标记到期证据为已删除，并挑出不再被引用的对象键登记为待删
+fn plan_deletions(store: &mut Store, policy: &RetentionPolicy, now: DateTime<Utc>) -> Vec<PlannedDeletion> {
+    let due: Vec<Uuid> = store
+        .evidence
+        .values()
+        .filter(|e| policy.is_deletable(e, &store.legal_holds, now))
+        .map(|e| e.id)
+        .collect();
+    let mut plans = Vec::with_capacity(due.len());
+    for id in due {
+        let Some(evidence) = store.evidence.get_mut(&id) else {
+            continue;
+        };
+        evidence.deleted_at = Some(now);
+        let evidence = evidence.clone();
+        let original = claim_key(store, &evidence.location.key);
+        let derived = evidence
+            .derived
+            .iter()
+            .filter_map(|d| claim_key(store, &d.location.key))
+            .collect();
+        plans.push(PlannedDeletion {
+            evidence,
+            original,
+            derived,
+        });
+    }
+    plans
+}
+
+/// 对象键不再被引用且未被本轮其他证据登记时登记为待删
+fn claim_key(store: &mut Store, key: &str) -> Option<String> {
+    if store.object_key_in_use(key) || !store.deleting_keys.insert(key.to_string()) {
+        return None;
+    }
+    Some(key.to_string())
+}
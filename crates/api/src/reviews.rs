@@ -0,0 +1,103 @@
+//! 订单评价（04 §三 3.3 /api/v1/orders/:id/reviews；01 §4 仅资金终态订单可评）。
+//!
+//! 订单双方各可评价对方一次（游客评导游账号、导游评游客），分数 1～5，评语至多 REVIEW_COMMENT_MAX_CHARS 字；
+//! weight 按 reputation::ReviewWeight（订单金额、被评方历史评分、评价人账号年龄）于提交时计算并固定，导游评分按其加权（见 stats）。
+//! GET 为订单评价列表（登录即可查看）；提交后失效订单双方的统计缓存。
+
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::rbac;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use traveltrust_core::reputation;
+use traveltrust_core::{Ownership, Review, ReviewWeight};
+use uuid::Uuid;
+
+const REVIEW_COMMENT_MAX_CHARS: usize = 500;
+
+pub async fn list_reviews(State(state): State<AppState>, Path(order_id): Path<Uuid>) -> ApiResult<Json<Vec<Review>>> {
+    let store = state.store.read().await;
+    if !store.orders.contains_key(&order_id) {
+        return Err(ApiError::not_found("订单"));
+    }
+    let mut reviews: Vec<Review> = store.reviews.values().filter(|r| r.order_id == order_id).cloned().collect();
+    reviews.sort_by_key(|r| r.created_at);
+    Ok(Json(reviews))
+}
+
+#[derive(Deserialize)]
+pub struct CreateReviewRequest {
+    pub score: u8,
+    pub comment: Option<String>,
+}
+
+pub async fn create_review(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    principal: Principal,
+    Json(req): Json<CreateReviewRequest>,
+) -> ApiResult<(StatusCode, Json<Review>)> {
+    if !(1..=5).contains(&req.score) {
+        return Err(ApiError::bad_request("invalid_score", "score 须为 1～5"));
+    }
+    let comment = req.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if comment.as_ref().is_some_and(|c| c.chars().count() > REVIEW_COMMENT_MAX_CHARS) {
+        return Err(ApiError::bad_request(
+            "invalid_comment",
+            format!("评语至多 {} 字", REVIEW_COMMENT_MAX_CHARS),
+        ));
+    }
+
+    let mut store = state.store.write().await;
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    let guide_user_id = store.guides.get(&order.guide_id).map(|g| g.user_id);
+    rbac::ensure_owner(store.is_order_party(order, principal.user_id), Ownership::OrderParty, &principal.role)?;
+    if !reputation::can_submit_review(order.state) {
+        return Err(ApiError::conflict("invalid_order_state", "仅资金终态（已完成/已退款/部分退款/已扣罚）的订单可评价"));
+    }
+    let reviewee_id = if order.tourist_id == principal.user_id {
+        guide_user_id.ok_or_else(|| ApiError::not_found("导游"))?
+    } else {
+        order.tourist_id
+    };
+    if store.reviews.values().any(|r| r.order_id == order_id && r.reviewer_id == principal.user_id) {
+        return Err(ApiError::conflict("review_exists", "已评价过该订单"));
+    }
+
+    let now = Utc::now();
+    let (weighted, weights) = store
+        .reviews
+        .values()
+        .filter(|r| r.reviewee_id == reviewee_id && r.weight > 0.0)
+        .fold((0.0, 0.0), |(sum, w), r| (sum + r.score as f64 * r.weight, w + r.weight));
+    let account_age_days = store
+        .users
+        .get(&principal.user_id)
+        .map(|a| (now - a.user.created_at).num_days().max(0) as u64)
+        .unwrap_or(0);
+    let weight = ReviewWeight {
+        order_amount: order.amount.parse().unwrap_or(0.0),
+        guide_historical_score: if weights > 0.0 { weighted / weights } else { 0.0 },
+        account_age_days,
+    }
+    .weight();
+    let review = Review {
+        id: Uuid::new_v4(),
+        order_id,
+        reviewer_id: principal.user_id,
+        reviewee_id,
+        score: req.score,
+        weight,
+        comment,
+        created_at: now,
+    };
+    store.reviews.insert(review.id, review.clone());
+    store.invalidate_order_stats(order_id);
+    Ok((StatusCode::CREATED, Json(review)))
+}
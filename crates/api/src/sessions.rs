@@ -0,0 +1,229 @@
+//! 会话与设备管理（01 §2 账号设置；会话即刷新令牌 family，规则见 traveltrust_core::auth）。
+//!
+//! 1. GET    /api/v1/me/sessions                   未吊销、未过期的会话：设备标签、登录 IP、User-Agent、创建与最近活跃时间，current 标记当前会话
+//! 2. DELETE /api/v1/me/sessions/:id               吊销一个会话（可为当前会话），该会话的访问令牌与刷新令牌即时失效
+//! 3. DELETE /api/v1/me/sessions?keep_current=true 退出所有设备；keep_current 为 true 时保留当前会话
+//!
+//! 设备信息在登录时记录：IP 取连接对端地址（TRUST_X_FORWARDED_FOR=1 时取 X-Forwarded-For 末项，即可信反代追加的对端地址；前面各项由客户端自填，不可信。仅在可信反代之后开启），
+//! 设备标签取请求头 X-Device-Label，未带时由 User-Agent 推断。最近活跃在刷新令牌或携带访问令牌请求时更新（至多每分钟一次）。
+//! 登录与活跃的 IP、设备 id（请求头 X-Device-Id，只存 sha256）同时上报 Sybil 信号（见 traveltrust_core::sybil，运营经 /api/v1/ops/sybil-signals 复核）。
+
+use crate::audit;
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, Extensions, HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use traveltrust_core::auth::describe_user_agent;
+use traveltrust_core::sybil::device_fingerprint;
+use traveltrust_core::{SecurityEvent, SessionDevice, SignalKind};
+use uuid::Uuid;
+
+const USER_AGENT_MAX_LEN: usize = 256;
+const DEVICE_LABEL_MAX_LEN: usize = 64;
+
+fn trust_forwarded_for() -> bool {
+    static TRUST: OnceLock<bool> = OnceLock::new();
+    *TRUST.get_or_init(|| env::var("TRUST_X_FORWARDED_FOR").as_deref() == Ok("1"))
+}
+
+fn header_text(headers: &HeaderMap, name: &str, max_len: usize) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().chars().take(max_len).collect::<String>())
+        .filter(|v| !v.is_empty())
+}
+
+/// X-Forwarded-For 的末项：由紧邻的可信反代追加，客户端无法伪造
+fn forwarded_client_ip(value: &str) -> Option<String> {
+    value
+        .rsplit(',')
+        .next()
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+/// 请求来源：IP、User-Agent 与客户端自报的设备标签、设备 id
+#[derive(Debug, Clone, Default)]
+pub struct ClientContext {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_label: Option<String>,
+    pub device_id: Option<String>,
+}
+
+impl ClientContext {
+    pub fn from_request_parts(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        let forwarded = trust_forwarded_for()
+            .then(|| header_text(headers, "x-forwarded-for", 256))
+            .flatten()
+            .and_then(|v| forwarded_client_ip(&v));
+        let peer = extensions.get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip().to_string());
+        Self {
+            ip: forwarded.or(peer),
+            user_agent: header_text(headers, header::USER_AGENT.as_str(), USER_AGENT_MAX_LEN),
+            device_label: header_text(headers, "x-device-label", DEVICE_LABEL_MAX_LEN),
+            device_id: header_text(headers, "x-device-id", 128),
+        }
+    }
+
+    /// 会话记录的设备信息
+    pub fn device(&self) -> SessionDevice {
+        SessionDevice {
+            label: self
+                .device_label
+                .clone()
+                .or_else(|| self.user_agent.as_deref().map(describe_user_agent)),
+            ip: self.ip.clone(),
+            user_agent: self.user_agent.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientContext {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Infallible> {
+        Ok(Self::from_request_parts(&parts.headers, &parts.extensions))
+    }
+}
+
+/// 上报 Sybil 信号；new_session 为登录
+pub(crate) fn record_activity(store: &mut Store, user_id: Uuid, client: &ClientContext, new_session: bool, now: DateTime<Utc>) {
+    if let Some(ip) = &client.ip {
+        store.sybil.observe(SignalKind::Ip, ip, user_id, new_session, now);
+    }
+    if let Some(device_id) = &client.device_id {
+        store
+            .sybil
+            .observe(SignalKind::Device, &device_fingerprint(device_id), user_id, new_session, now);
+    }
+}
+
+#[derive(Serialize)]
+pub struct SessionView {
+    pub id: Uuid,
+    pub device_label: Option<String>,
+    /// 登录时 IP
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub last_seen_ip: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    /// 发起本请求的会话
+    pub current: bool,
+}
+
+pub async fn list_sessions(State(state): State<AppState>, principal: Principal) -> Json<Vec<SessionView>> {
+    let sessions = state.store.read().await.refresh_tokens.active_sessions(principal.user_id, Utc::now());
+    Json(
+        sessions
+            .into_iter()
+            .map(|s| SessionView {
+                current: s.id == principal.session_id,
+                id: s.id,
+                device_label: s.device.label,
+                ip: s.device.ip,
+                user_agent: s.device.user_agent,
+                created_at: s.created_at,
+                last_seen_at: s.last_seen_at,
+                last_seen_ip: s.last_seen_ip,
+                expires_at: s.expires_at,
+            })
+            .collect(),
+    )
+}
+
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let mut store = state.store.write().await;
+    // 他人会话与不存在同样 404，不暴露会话 id 是否存在
+    if store.refresh_tokens.session(id).is_none_or(|s| s.user_id != principal.user_id) {
+        return Err(ApiError::not_found("会话"));
+    }
+    if !store.refresh_tokens.revoke_family(id, "revoked_by_user", Utc::now()) {
+        return Err(ApiError::not_found("会话"));
+    }
+    drop(store);
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::SessionRevoked,
+        format!("scope=one session={} current={}", id, id == principal.session_id),
+        audit::request_id(&headers),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, Default)]
+pub struct RevokeAllQuery {
+    /// 保留当前会话
+    #[serde(default)]
+    pub keep_current: bool,
+}
+
+#[derive(Serialize)]
+pub struct RevokedSessions {
+    pub revoked: usize,
+}
+
+/// 退出所有设备
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Query(query): Query<RevokeAllQuery>,
+) -> Json<RevokedSessions> {
+    let keep = query.keep_current.then_some(principal.session_id);
+    let revoked = state.store.write().await.refresh_tokens.revoke_user_except(
+        principal.user_id,
+        keep,
+        "logout_everywhere",
+        Utc::now(),
+    );
+    audit::record_security(
+        &state,
+        principal.user_id,
+        &principal.role,
+        SecurityEvent::SessionRevoked,
+        format!("scope=all revoked={} keep_current={}", revoked, query.keep_current),
+        audit::request_id(&headers),
+    )
+    .await;
+    Json(RevokedSessions { revoked })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_for_takes_the_proxy_appended_entry() {
+        assert_eq!(forwarded_client_ip("203.0.113.7").as_deref(), Some("203.0.113.7"));
+        // 客户端自填的前置项被忽略
+        assert_eq!(forwarded_client_ip("1.1.1.1, 10.0.0.2 , 198.51.100.9").as_deref(), Some("198.51.100.9"));
+        assert_eq!(forwarded_client_ip("spoofed,2001:db8::1").as_deref(), Some("2001:db8::1"));
+        assert_eq!(forwarded_client_ip("1.1.1.1, "), None);
+        assert_eq!(forwarded_client_ip(""), None);
+    }
+}
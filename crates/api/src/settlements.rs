@@ -0,0 +1,119 @@
+//! 链上托管与结算事件登记（01 §5 对账三段式：DB 资金状态仅由链上事件驱动）。
+//!
+//! 执行器（链上事件对账进程）经 POST /api/v1/ops/orders/:id/escrow 登记托管锁定（Accepted → Escrowed，写 escrow_at），
+//! 经 POST /api/v1/ops/orders/:id/settlement 登记放款/退款结果（写 Store::settlements，订单转资金终态，见 escrow::Settlement::final_state）。
+//! 争议中的订单须已裁决才可结算，扣罚与否取裁决结果；每单只结算一次。登记后失效订单双方的统计缓存（见 stats）。
+
+use crate::audit::Actor;
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use traveltrust_core::{DisputeStatus, Order, OrderState, Settlement, SettlementError, TokenAmount};
+use uuid::Uuid;
+
+impl From<SettlementError> for ApiError {
+    fn from(e: SettlementError) -> Self {
+        match e {
+            SettlementError::InvalidState(_) => ApiError::conflict("invalid_order_state", e.to_string()),
+            SettlementError::AmountMismatch => ApiError::bad_request("settlement_amount_mismatch", e.to_string()),
+        }
+    }
+}
+
+fn validate_tx_hash(tx_hash: &str) -> ApiResult<String> {
+    let tx_hash = tx_hash.trim().to_ascii_lowercase();
+    let valid = tx_hash.len() == 66 && tx_hash.starts_with("0x") && tx_hash[2..].bytes().all(|b| b.is_ascii_hexdigit());
+    if !valid {
+        return Err(ApiError::bad_request("invalid_tx_hash", "tx_hash 须为 0x 开头的 32 字节十六进制"));
+    }
+    Ok(tx_hash)
+}
+
+#[derive(Deserialize)]
+pub struct EscrowEventRequest {
+    pub tx_hash: String,
+}
+
+/// 托管锁定：Accepted → Escrowed
+pub async fn record_escrow(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    actor: Actor,
+    Json(req): Json<EscrowEventRequest>,
+) -> ApiResult<Json<Order>> {
+    let tx_hash = validate_tx_hash(&req.tx_hash)?;
+    let mut store = state.store.write().await;
+    let order = store.orders.get_mut(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    if order.state != OrderState::Accepted {
+        return Err(ApiError::conflict("invalid_order_state", "仅已接单（accepted）的订单可登记托管锁定"));
+    }
+    order.state = OrderState::Escrowed;
+    order.escrow_at = Some(Utc::now());
+    let order = order.clone();
+    store.invalidate_order_stats(order_id);
+    eprintln!("[escrow] 托管锁定 order={} tx={} executor={}", order_id, tx_hash, actor.id);
+    Ok(Json(order))
+}
+
+#[derive(Deserialize)]
+pub struct SettlementEventRequest {
+    pub guide_payout: TokenAmount,
+    pub tourist_refund: TokenAmount,
+    pub tx_hash: String,
+}
+
+/// 放款/退款结果：订单转资金终态
+pub async fn record_settlement(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    actor: Actor,
+    Json(req): Json<SettlementEventRequest>,
+) -> ApiResult<Json<Settlement>> {
+    let tx_hash = validate_tx_hash(&req.tx_hash)?;
+    let mut store = state.store.write().await;
+    if store.settlements.contains_key(&order_id) {
+        return Err(ApiError::conflict("already_settled", "订单已登记结算"));
+    }
+    let order = store.orders.get(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    let mut slash_guide = false;
+    if order.state == OrderState::Disputed {
+        let disputes: Vec<_> = store.disputes.values().filter(|d| d.order_id == order_id).collect();
+        if disputes.iter().any(|d| d.status != DisputeStatus::Resolved) {
+            return Err(ApiError::conflict("dispute_unresolved", "订单争议尚未裁决，不可结算"));
+        }
+        slash_guide = disputes
+            .iter()
+            .filter_map(|d| d.resolution.as_ref())
+            .any(|r| r.slash_guide);
+    }
+    let now = Utc::now();
+    let settlement = Settlement {
+        order_id,
+        currency: order.currency.clone(),
+        guide_payout: req.guide_payout,
+        tourist_refund: req.tourist_refund,
+        tx_hash: Some(tx_hash),
+        settled_at: now,
+    };
+    let order_amount: TokenAmount = order
+        .amount
+        .parse()
+        .map_err(|_| ApiError::conflict("invalid_order_amount", "订单金额无法解析"))?;
+    let final_state = settlement.final_state(order.state, order_amount, slash_guide)?;
+
+    let order = store.orders.get_mut(&order_id).ok_or_else(|| ApiError::not_found("订单"))?;
+    order.state = final_state;
+    order.completed_at = Some(now);
+    store.settlements.insert(order_id, settlement.clone());
+    store.invalidate_order_stats(order_id);
+    eprintln!(
+        "[escrow] 结算 order={} state={:?} payout={} refund={} executor={}",
+        order_id, final_state, settlement.guide_payout, settlement.tourist_refund, actor.id
+    );
+    Ok(Json(settlement))
+}
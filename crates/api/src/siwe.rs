@@ -0,0 +1,162 @@
+//! 钱包登录（EIP-4361 Sign-In with Ethereum；消息解析与校验见 traveltrust_core::siwe）。
+//!
+//! 1. POST /auth/siwe/nonce                      签发一次性 nonce（10 分钟），附本站 domain、uri 与 chain_id 供客户端构造消息
+//! 2. POST /auth/siwe/login {message, signature}  校验消息与 personal_sign 签名，返回与邮箱登录相同的令牌对及账号
+//!
+//! 签名地址已绑定账号即登录该账号；未绑定则新建游客账号（无邮箱）并以本次签名登记绑定，响应 created=true。
+//! 账号已启用 TOTP 时请求须附 totp_code 或 recovery_code（同邮箱登录，见 totp）。
+//! 校验失败一律 401：格式错误 siwe_malformed，domain / Chain ID / 时间窗口不符 siwe_invalid，nonce 未签发、已用或过期 siwe_nonce_invalid，
+//! 签名地址不符 invalid_signature。nonce 在验签前核销，失败须重新获取。
+//! domain 取 SIWE_DOMAIN，未设时取 APP_BASE_URL 的 host[:port]（默认 localhost:5173）；chain_id 与订单签名域一致（CHAIN_ID）。
+
+use crate::audit;
+use crate::auth::{self, AccountView, TokenPair, UserAccount};
+use crate::error::{ApiError, ApiResult};
+use crate::mailer::app_base_url;
+use crate::sessions::ClientContext;
+use crate::state::AppState;
+use crate::totp::{self, SecondFactorInput};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::env;
+use traveltrust_core::siwe::SiweError;
+use traveltrust_core::wallet;
+use traveltrust_core::{KycStatus, SecurityEvent, SiweMessage, User, UserRole};
+use uuid::Uuid;
+
+/// SIWE 消息中应出现的 domain（RFC 3986 authority）
+fn siwe_domain() -> String {
+    env::var("SIWE_DOMAIN").unwrap_or_else(|_| {
+        let base = app_base_url();
+        let authority = base.split_once("://").map_or(base.as_str(), |(_, rest)| rest);
+        authority.split('/').next().unwrap_or_default().to_string()
+    })
+}
+
+fn siwe_rejected(code: &'static str, message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED, code, message)
+}
+
+#[derive(Serialize)]
+pub struct SiweNonceView {
+    pub nonce: String,
+    pub domain: String,
+    pub uri: String,
+    pub chain_id: u64,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn issue_nonce(State(state): State<AppState>) -> Json<SiweNonceView> {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    let nonce = hex::encode(bytes);
+    let now = Utc::now();
+    let expires_at = state.store.write().await.siwe_nonces.issue(nonce.clone(), now);
+    Json(SiweNonceView {
+        nonce,
+        domain: siwe_domain(),
+        uri: app_base_url(),
+        chain_id: state.eip712.chain_id,
+        issued_at: now,
+        expires_at,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct SiweLoginRequest {
+    pub message: String,
+    pub signature: String,
+    /// 已启用 TOTP 时必填其一
+    #[serde(flatten)]
+    pub second_factor: SecondFactorInput,
+}
+
+#[derive(Serialize)]
+pub struct SiweLoginResponse {
+    #[serde(flatten)]
+    pub tokens: TokenPair,
+    pub account: AccountView,
+    /// 本次登录新建了账号
+    pub created: bool,
+}
+
+pub async fn login(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    client: ClientContext,
+    Json(req): Json<SiweLoginRequest>,
+) -> ApiResult<Json<SiweLoginResponse>> {
+    let message = SiweMessage::parse(&req.message).map_err(|e| siwe_rejected("siwe_malformed", e.to_string()))?;
+    let now = Utc::now();
+    message
+        .validate(&siwe_domain(), state.eip712.chain_id, now)
+        .map_err(|e: SiweError| siwe_rejected("siwe_invalid", e.to_string()))?;
+    if !state.store.write().await.siwe_nonces.consume(&message.nonce, now) {
+        return Err(siwe_rejected("siwe_nonce_invalid", "nonce 未签发、已使用或已过期，请重新获取"));
+    }
+    wallet::verify_personal_sign(&req.message, &req.signature, &message.address)
+        .map_err(|e| siwe_rejected("invalid_signature", e.to_string()))?;
+
+    let existing = {
+        let store = state.store.read().await;
+        store.wallets.owner_of(&message.address).and_then(|id| store.users.get(&id)).map(|a| a.user.clone())
+    };
+    let factor = match existing {
+        Some(user) => totp::verify_login_factor(&state, &user, &req.second_factor, audit::request_id(&headers)).await?,
+        None => None,
+    };
+
+    let mut store = state.store.write().await;
+    let (account, created) = match store.wallets.owner_of(&message.address).and_then(|id| store.users.get(&id)) {
+        Some(account) => (account.clone(), false),
+        None => {
+            let account = UserAccount {
+                user: User {
+                    id: Uuid::new_v4(),
+                    email: String::new(),
+                    role: UserRole::Tourist,
+                    kyc_status: KycStatus::None,
+                    created_at: now,
+                    email_verified_at: None,
+                    default_wallet_address: Some(message.address.clone()),
+                    nickname: None,
+                    avatar_url: None,
+                    updated_at: now,
+                },
+                password_hash: String::new(),
+            };
+            store.wallets.bind(
+                account.user.id,
+                message.address.clone(),
+                req.signature.trim().to_string(),
+                message.nonce.clone(),
+                now,
+            );
+            store.users.insert(account.user.id, account.clone());
+            (account, true)
+        }
+    };
+    let tokens = auth::start_session(&state, &mut store, &account.user, factor, &client, now);
+    drop(store);
+    audit::record_security(
+        &state,
+        account.user.id,
+        &account.user.role,
+        SecurityEvent::LoggedIn,
+        format!("method=siwe address={} session={} created={}", message.address, tokens.session_id, created),
+        audit::request_id(&headers),
+    )
+    .await;
+    Ok(Json(SiweLoginResponse {
+        tokens,
+        account: AccountView::from(&account),
+        created,
+    }))
+}
@@ -0,0 +1,179 @@
+//! 共享状态：存储、访问令牌签发、外发邮件、订单 EIP-712 签名域、风控（争议率、类别冻结、争议容量）、证据暂存目录与对象存储、回执与下载链接签名、时间戳服务、上链锚定、证据访问与账号安全事件审计日志。handler 经 axum State 取用。
+//! 锁顺序：store → risk → audit / security_log（两条审计日志不同时持有）。
+
+use crate::anchoring::Anchoring;
+use crate::auth::TokenIssuer;
+use crate::downloads::UrlSigner;
+use crate::evidence_store::{EvidenceStore, LocalFsStore, ReplicatedStore};
+use crate::mailer::{Mailer, OutboxMailer};
+use crate::orders;
+use crate::receipts::ReceiptSigner;
+use crate::store::Store;
+use crate::tsa::TimestampAuthority;
+use chrono::{DateTime, Duration, Utc};
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use traveltrust_core::capacity::{CapacityAssessment, MIN_ARBITRATOR_COUNT};
+use traveltrust_core::{
+    AccessLog, BiasMonitor, CapacityController, CapacitySnapshot, DisputeMode, DisputeRateThresholds, DisputeRateTracker,
+    Eip712Domain, FreezeRegistry, RateRule, SecondReviewQueue, SecurityLog,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub store: Arc<RwLock<Store>>,
+    pub risk: Arc<RwLock<RiskState>>,
+    /// JWT 访问令牌签发与校验
+    pub tokens: Arc<TokenIssuer>,
+    /// 邮箱验证、密码重置等外发邮件（默认写本地发件箱）
+    pub mailer: Arc<dyn Mailer>,
+    /// 下单/接单签名域（CHAIN_ID、ESCROW_FACTORY_ADDRESS）
+    pub eip712: Arc<Eip712Domain>,
+    /// 证据上传暂存目录（EVIDENCE_DIR，默认 data/evidence）；本地对象存储亦以此为根
+    pub evidence_dir: PathBuf,
+    pub evidence_store: Arc<dyn EvidenceStore>,
+    /// 与 evidence_store 同一实例，供健康巡检与副本修复
+    pub evidence_replicas: Arc<ReplicatedStore>,
+    pub receipts: Arc<ReceiptSigner>,
+    pub url_signer: Arc<UrlSigner>,
+    /// 内置时间戳服务（证据 hash、回执、裁决盖章）
+    pub tsa: Arc<TimestampAuthority>,
+    /// 批量上链锚定（链接口与封批互斥）
+    pub anchoring: Arc<Anchoring>,
+    /// 证据访问审计日志（只追加哈希链）
+    pub audit: Arc<RwLock<AccessLog>>,
+    /// 账号安全事件审计日志（登录与会话、凭据变更、角色变更、TOTP 与 step-up，只追加哈希链）
+    pub security_log: Arc<RwLock<SecurityLog>>,
+}
+
+impl AppState {
+    pub fn from_env() -> Self {
+        let evidence_dir = env::var("EVIDENCE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/evidence"));
+        let evidence_replicas = Arc::new(ReplicatedStore::new(evidence_replicas_from_env(&evidence_dir)));
+        Self {
+            store: Arc::new(RwLock::new(Store::default())),
+            risk: Arc::new(RwLock::new(RiskState::from_env())),
+            tokens: Arc::new(TokenIssuer::from_env()),
+            mailer: Arc::new(OutboxMailer::from_env()),
+            eip712: Arc::new(orders::signing_domain_from_env()),
+            evidence_store: evidence_replicas.clone(),
+            evidence_replicas,
+            evidence_dir,
+            receipts: Arc::new(ReceiptSigner::from_env()),
+            url_signer: Arc::new(UrlSigner::from_env()),
+            tsa: Arc::new(TimestampAuthority::from_env()),
+            anchoring: Arc::new(Anchoring::from_env()),
+            audit: Arc::new(RwLock::new(AccessLog::default())),
+            security_log: Arc::new(RwLock::new(SecurityLog::default())),
+        }
+    }
+}
+
+/// 主存储为 EVIDENCE_DIR；EVIDENCE_REPLICA_DIRS（逗号分隔）为其余副本目录，生产应指向不同区域/云的挂载
+fn evidence_replicas_from_env(evidence_dir: &std::path::Path) -> Vec<Arc<dyn EvidenceStore>> {
+    let mut replicas: Vec<Arc<dyn EvidenceStore>> = vec![Arc::new(LocalFsStore::new("local", evidence_dir.to_path_buf()))];
+    if let Ok(dirs) = env::var("EVIDENCE_REPLICA_DIRS") {
+        for (i, dir) in dirs.split(',').map(str::trim).filter(|d| !d.is_empty()).enumerate() {
+            replicas.push(Arc::new(LocalFsStore::new(format!("local-replica-{}", i + 1), PathBuf::from(dir))));
+        }
+    }
+    replicas
+}
+
+/// 异常争议率、裁决偏差与类别/仲裁员冻结、争议容量模式（08-3、Runbook §8/§11）
+pub struct RiskState {
+    pub rate_thresholds: DisputeRateThresholds,
+    pub dispute_rates: DisputeRateTracker,
+    pub freezes: FreezeRegistry,
+    /// 每次裁决后对全部已裁决争议重算偏差（见 disputes::resolve_dispute）
+    pub bias: BiasMonitor,
+    /// 连续同向裁决触发的强制复核
+    pub second_reviews: SecondReviewQueue,
+    pub capacity: CapacityController,
+    /// 在岗仲裁员数（运营经 /api/v1/ops/dispute-capacity 维护）
+    pub active_arbitrators: usize,
+    /// 最近一次评估的争议模式，用于记录模式切换
+    pub dispute_mode: DisputeMode,
+    /// 稳定币冻结开始时间；冻结期间争议 SLA 暂停计时（08-3 freezeDisputePolicy）
+    pub stablecoin_frozen_since: Option<DateTime<Utc>>,
+    /// 证据主存储中断已达暂停阈值（evidence_store::monitor_evidence_storage 维护）
+    pub evidence_storage_outage: bool,
+}
+
+impl RiskState {
+    /// DISPUTE_RATE_RULE=zscore:3 或 ratio:2.5；未设或解析失败用默认（z-score 3.0、30 天窗口、最少 50 单）。
+    /// ARBITRATORS_ON_DUTY 为启动时在岗仲裁员数，默认 minArbitratorCount。
+    pub fn from_env() -> Self {
+        let mut rate_thresholds = DisputeRateThresholds::default();
+        if let Ok(s) = env::var("DISPUTE_RATE_RULE") {
+            match parse_rate_rule(&s) {
+                Some(rule) => rate_thresholds.rule = rule,
+                None => eprintln!("DISPUTE_RATE_RULE 解析失败（{}），使用默认 {:?}", s, rate_thresholds.rule),
+            }
+        }
+        Self {
+            dispute_rates: DisputeRateTracker::new(Duration::days(rate_thresholds.window_days)),
+            rate_thresholds,
+            freezes: FreezeRegistry::default(),
+            bias: BiasMonitor::default(),
+            second_reviews: SecondReviewQueue::default(),
+            capacity: CapacityController::default(),
+            active_arbitrators: env::var("ARBITRATORS_ON_DUTY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(MIN_ARBITRATOR_COUNT),
+            dispute_mode: DisputeMode::Normal,
+            stablecoin_frozen_since: None,
+            evidence_storage_outage: false,
+        }
+    }
+
+    /// 按积压与窗口争议率评估争议模式（W-D3-CAPACITY）；模式切换时打印留痕，并把未裁决争议的 SLA 目标延长到新模式的目标
+    /// （Runbook §8：Paused 下存量按延长 SLA 处理；只延长不缩短，见 SlaClock::extend_target）。调用方须先持 store 写锁
+    pub fn assess_capacity(&mut self, store: &mut Store, now: DateTime<Utc>) -> (CapacitySnapshot, CapacityAssessment) {
+        let snapshot = CapacitySnapshot {
+            backlog: store.dispute_backlog(),
+            active_arbitrators: self.active_arbitrators,
+            dispute_rate: self.dispute_rates.global_rate(now, self.rate_thresholds.min_orders),
+            evidence_storage_outage: self.evidence_storage_outage,
+        };
+        let assessment = self.capacity.assess(&snapshot);
+        if assessment.mode != self.dispute_mode {
+            eprintln!(
+                "[risk] 争议模式切换 {:?} -> {:?} backlog={} arbitrators={} rate={:?} storage_outage={}",
+                self.dispute_mode,
+                assessment.mode,
+                snapshot.backlog,
+                snapshot.active_arbitrators,
+                snapshot.dispute_rate,
+                snapshot.evidence_storage_outage
+            );
+            self.dispute_mode = assessment.mode;
+            let target = self.capacity.sla(assessment.mode);
+            let mut extended = 0;
+            for clock in store.sla_clocks.values_mut() {
+                if clock.extend_target(target) {
+                    extended += 1;
+                }
+            }
+            if extended > 0 {
+                eprintln!("[risk] 存量争议 SLA 目标调整为 {} 小时，共 {} 单", target.num_hours(), extended);
+            }
+        }
+        (snapshot, assessment)
+    }
+}
+
+fn parse_rate_rule(s: &str) -> Option<RateRule> {
+    let (kind, value) = s.trim().split_once(':')?;
+    let value: f64 = value.trim().parse().ok().filter(|v: &f64| *v > 0.0)?;
+    match kind.trim() {
+        "zscore" => Some(RateRule::ZScore(value)),
+        "ratio" => Some(RateRule::Ratio(value)),
+        _ => None,
+    }
+}
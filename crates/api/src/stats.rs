@@ -0,0 +1,64 @@
+//! 按角色统计摘要（04 §三 3.2 GET /api/v1/me/stats；聚合规则见 traveltrust_core::stats）。
+//!
+//! 按当前角色返回 tourist / guide / arbitrator 其一；金额为按币种汇总的十进制字符串。
+//! 结果缓存于 Store::stats，下单、接单、发起争议、裁决、提交评价（见 reviews）、登记托管与结算（见 settlements）及注册导游资料时失效；
+//! computed_at 为本次结果的计算时间。新增写入订单、评价或结算的路径须同时经 Store::invalidate_order_stats 失效订单双方的缓存。
+
+use crate::auth::{unauthenticated, Principal};
+use crate::error::ApiResult;
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use traveltrust_core::stats::{self, UserStats};
+use traveltrust_core::UserRole;
+use uuid::Uuid;
+
+fn compute(store: &Store, user_id: Uuid, now: DateTime<Utc>) -> ApiResult<UserStats> {
+    let role = store.users.get(&user_id).ok_or_else(unauthenticated)?.user.role.clone();
+    let mut summary = UserStats {
+        role: role.clone(),
+        tourist: None,
+        guide: None,
+        arbitrator: None,
+        computed_at: now,
+    };
+    match role {
+        UserRole::Tourist => {
+            summary.tourist = Some(stats::tourist_stats(
+                user_id,
+                store.orders.values(),
+                store.reviews.values(),
+                &store.settlements,
+            ));
+        }
+        UserRole::Guide => {
+            let guide_id = store.guides.values().find(|g| g.user_id == user_id).map(|g| g.id);
+            summary.guide = Some(stats::guide_stats(
+                user_id,
+                guide_id,
+                store.orders.values(),
+                store.reviews.values(),
+                &store.settlements,
+            ));
+        }
+        UserRole::Arbitrator => summary.arbitrator = Some(stats::arbitrator_stats(user_id, store.disputes.values())),
+        UserRole::Operator | UserRole::Executor | UserRole::Compliance | UserRole::Legal => {}
+    }
+    Ok(summary)
+}
+
+pub async fn get_stats(State(state): State<AppState>, principal: Principal) -> ApiResult<Json<UserStats>> {
+    {
+        let store = state.store.read().await;
+        let role = &store.users.get(&principal.user_id).ok_or_else(unauthenticated)?.user.role;
+        if let Some(cached) = store.stats.get(principal.user_id, role) {
+            return Ok(Json(cached.clone()));
+        }
+    }
+    // 写锁内计算并写回，避免与失效交错而缓存旧结果
+    let mut store = state.store.write().await;
+    let summary = compute(&store, principal.user_id, Utc::now())?;
+    store.stats.insert(principal.user_id, summary.clone());
+    Ok(Json(summary))
+}
@@ -0,0 +1,162 @@
+//! 内存存储（MVP）：账号、刷新令牌与邮箱验证/密码重置令牌、钱包绑定挑战与历史、SIWE nonce、TOTP 启用与会话 step-up、会话设备/IP 的 Sybil 信号、导游、订单与下单/接单签名 nonce、评价、链上结算、按角色统计缓存、争议、争议 SLA 时钟、证据元数据与归档中、待删除的对象键、分片上传会话、法律保全与原文件删除记录、上链锚定账本（证据原文件见 evidence 模块）。04 §四 持久化以 trait 抽象接 PostgreSQL/分布式 DB，落库前以本结构承载。
+
+use crate::auth::UserAccount;
+use crate::uploads::UploadSession;
+use std::collections::{HashMap, HashSet};
+use traveltrust_core::{
+    AnchorLedger, DeletionRecord, Dispute, DisputeStatus, Evidence, Guide, LegalHoldRegistry, OneTimeTokenRegistry, Order, OrderState,
+    RebindBlocker, RefreshRegistry, Review, RiskScope, Settlement, SignerNonces, SiweNonces, SlaClock, StatsCache, SybilSignals,
+    TotpRegistry, UserRole, WalletRegistry,
+};
+use uuid::Uuid;
+
+#[derive(Default)]
+pub struct Store {
+    pub users: HashMap<Uuid, UserAccount>,
+    /// 规范化邮箱 → 用户 id
+    pub user_emails: HashMap<String, Uuid>,
+    /// 刷新令牌与会话（见 auth）
+    pub refresh_tokens: RefreshRegistry,
+    /// 邮箱验证与密码重置令牌
+    pub one_time_tokens: OneTimeTokenRegistry,
+    /// 钱包绑定挑战与绑定历史（见 wallets）
+    pub wallets: WalletRegistry,
+    /// SIWE 登录 nonce（见 siwe）
+    pub siwe_nonces: SiweNonces,
+    /// TOTP 启用、恢复码与会话 step-up（见 totp）
+    pub totp: TotpRegistry,
+    /// 登录与活跃的 IP、设备关联（见 sessions）
+    pub sybil: SybilSignals,
+    pub guides: HashMap<Uuid, Guide>,
+    pub orders: HashMap<Uuid, Order>,
+    /// 下单/接单签名钱包已用 nonce（见 orders）
+    pub signer_nonces: SignerNonces,
+    /// 订单评价（见 reviews）
+    pub reviews: HashMap<Uuid, Review>,
+    /// 订单 id → 链上放款/退款对账结果（见 settlements）
+    pub settlements: HashMap<Uuid, Settlement>,
+    /// GET /api/v1/me/stats 缓存（见 stats）
+    pub stats: StatsCache,
+    pub disputes: HashMap<Uuid, Dispute>,
+    pub sla_clocks: HashMap<Uuid, SlaClock>,
+    pub evidence: HashMap<Uuid, Evidence>,
+    /// 正在锁外写入对象存储的对象键 → 并发写入数（新证据归档见 evidence::archive，副本修复见 evidence_store）；保留期清理不删除这些键
+    pub archiving_keys: HashMap<String, usize>,
+    /// 保留期清理已标记、正在锁外删除的对象键（见 retention）；删除完成前不得再归档同键
+    pub deleting_keys: HashSet<String>,
+    pub upload_sessions: HashMap<Uuid, UploadSession>,
+    pub legal_holds: LegalHoldRegistry,
+    /// 保留期满的原文件删除记录（只追加）
+    pub evidence_deletions: Vec<DeletionRecord>,
+    /// 待上链队列与已封批次（见 anchoring）
+    pub anchors: AnchorLedger,
+}
+
+impl Store {
+    /// 未裁决争议数（争议容量积压）
+    pub fn dispute_backlog(&self) -> usize {
+        self.disputes.values().filter(|d| d.status != DisputeStatus::Resolved).count()
+    }
+
+    /// 登记归档中的对象键（同 hash 并发上传各计一次）；任一键正被保留期清理删除时不登记并返回 false，调用方稍后重试
+    pub fn pin_object_keys(&mut self, keys: &[String]) -> bool {
+        if keys.iter().any(|k| self.deleting_keys.contains(k)) {
+            return false;
+        }
+        for key in keys {
+            *self.archiving_keys.entry(key.clone()).or_default() += 1;
+        }
+        true
+    }
+
+    pub fn unpin_object_keys(&mut self, keys: &[String]) {
+        for key in keys {
+            if let Some(count) = self.archiving_keys.get_mut(key) {
+                *count -= 1;
+                if *count == 0 {
+                    self.archiving_keys.remove(key);
+                }
+            }
+        }
+    }
+
+    /// 对象键是否仍被未删除的证据（原文件或派生件）引用，或正在归档、修复中
+    pub fn object_key_in_use(&self, key: &str) -> bool {
+        self.archiving_keys.contains_key(key)
+            || self
+                .evidence
+                .values()
+                .filter(|e| e.deleted_at.is_none())
+                .any(|e| e.location.key == key || e.derived.iter().any(|d| d.location.key == key))
+    }
+
+    /// 订单变更（状态、评价、结算、裁决）后失效双方的统计缓存
+    pub fn invalidate_order_stats(&mut self, order_id: Uuid) {
+        let Some(order) = self.orders.get(&order_id) else {
+            return;
+        };
+        let tourist_id = order.tourist_id;
+        let guide_user_id = self.guides.get(&order.guide_id).map(|g| g.user_id);
+        self.stats.invalidate(tourist_id);
+        if let Some(user_id) = guide_user_id {
+            self.stats.invalidate(user_id);
+        }
+    }
+
+    /// 自动指派：已启用 TOTP、未被冻结的仲裁员中，指派中（未裁决）争议最少者；并列取 id 最小，无可用仲裁员为 None
+    pub fn pick_arbitrator(&self, is_frozen: impl Fn(Uuid) -> bool) -> Option<Uuid> {
+        self.users
+            .values()
+            .filter(|a| a.user.role == UserRole::Arbitrator && self.totp.is_enabled(a.user.id) && !is_frozen(a.user.id))
+            .map(|a| {
+                let load = self
+                    .disputes
+                    .values()
+                    .filter(|d| d.status == DisputeStatus::Assigned && d.arbitrator_id == Some(a.user.id))
+                    .count();
+                (load, a.user.id)
+            })
+            .min()
+            .map(|(_, id)| id)
+    }
+
+    /// 订单上有指派给该仲裁员（含已由其裁决）的争议
+    pub fn is_order_arbitrator(&self, order_id: Uuid, user_id: Uuid) -> bool {
+        self.disputes.values().any(|d| d.order_id == order_id && d.arbitrator_id == Some(user_id))
+    }
+
+    /// 订单游客或订单指派导游
+    pub fn is_order_party(&self, order: &Order, user_id: Uuid) -> bool {
+        order.tourist_id == user_id || self.guides.get(&order.guide_id).is_some_and(|g| g.user_id == user_id)
+    }
+
+    /// 阻止换绑/解绑钱包的未终结事项（01 §10 12 缝 #11）：本人为一方、未到资金终态且未取消的订单，以及本人发起的进行中证据上传。
+    /// 争议中的订单状态为 Disputed（非资金终态），已列为 OpenOrder，结算前始终阻止换绑，无需另查争议
+    pub fn rebind_blockers(&self, user_id: Uuid) -> Vec<RebindBlocker> {
+        let mut blockers: Vec<RebindBlocker> = self
+            .orders
+            .values()
+            .filter(|o| !o.state.is_final_financial_state() && o.state != OrderState::Cancelled)
+            .filter(|o| self.is_order_party(o, user_id))
+            .map(|o| RebindBlocker::OpenOrder { order_id: o.id })
+            .collect();
+        blockers.extend(
+            self.upload_sessions
+                .values()
+                .filter(|u| u.uploader_id == user_id)
+                .map(|u| RebindBlocker::PendingEvidenceUpload {
+                    upload_id: u.id,
+                    order_id: u.order_id,
+                }),
+        );
+        blockers
+    }
+
+    /// 订单所属风控类别（服务类型 + 导游城市/国家）；导游缺失时仅按服务类型归类
+    pub fn order_scopes(&self, order: &Order) -> Vec<RiskScope> {
+        match self.guides.get(&order.guide_id) {
+            Some(guide) => RiskScope::for_guide(guide, &order.service_type),
+            None => vec![RiskScope::ServiceType(order.service_type.clone())],
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! TOTP 两步验证（RFC 6238；密钥、防重放、恢复码与锁定规则见 traveltrust_core::totp）：仲裁员与运营必须启用，高风险操作前 step-up。
+//!
+//! 1. GET    /api/v1/me/totp                        启用状态、剩余恢复码、锁定与当前会话 step-up 有效期
+//! 2. POST   /api/v1/me/totp/enroll                 生成密钥，返回 base32 secret 与 otpauth_uri（待确认；已启用 409 totp_already_enabled）
+//! 3. POST   /api/v1/me/totp/confirm {code}          以首个验证码确认启用，返回 10 个恢复码（仅此一次明文），当前会话同时获得 step-up
+//! 4. POST   /api/v1/me/totp/step-up {code}          TOTP 校验，当前会话 5 分钟内可执行路由表标记 step_up 的操作
+//! 5. POST   /api/v1/me/totp/recovery-codes {code}   重新生成恢复码，旧码作废
+//! 6. DELETE /api/v1/me/totp {code}                  停用；仲裁员与运营不可停用（403 totp_required_for_role）
+//!
+//! 已启用账号登录（邮箱或 SIWE）须附 totp_code 或 recovery_code：缺少返回 401 totp_required，错误 401 invalid_totp；
+//! 以 TOTP 登录的会话同时获得 step-up，恢复码登录不获得。会话内校验错误返回 400 invalid_totp，连续失败锁定返回 429 totp_locked。
+//! 鉴权中间件经 enforce 校验：仲裁员/运营未启用时访问角色专属路由返回 403 totp_enrollment_required（任意已登录路由不受限，
+//! 以便自助启用），step_up 路由缺少有效 step-up 返回 403 step_up_required。
+//! 启用、停用、恢复码重置、每次校验成败与 step-up 拒绝均记入账号安全事件审计日志（见 audit）。
+
+use crate::audit::{self, Actor};
+use crate::auth::{unauthenticated, Principal};
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use traveltrust_core::rbac::Audience;
+use traveltrust_core::totp::{
+    self, TotpError, RECOVERY_CODE_COUNT, STEP_UP_WINDOW_MINUTES, TOTP_DIGITS, TOTP_SECRET_BYTES, TOTP_STEP_SECS,
+};
+use traveltrust_core::{RoutePolicy, SecondFactor, SecurityEvent, User};
+
+fn totp_error(e: TotpError, invalid_status: StatusCode) -> ApiError {
+    match e {
+        TotpError::Locked { until } => ApiError::new(StatusCode::TOO_MANY_REQUESTS, "totp_locked", e.to_string())
+            .with_detail(json!({ "locked_until": until })),
+        TotpError::NotEnabled => ApiError::conflict("totp_not_enabled", e.to_string()),
+        TotpError::AlreadyEnabled => ApiError::conflict("totp_already_enabled", e.to_string()),
+        TotpError::NoPendingEnrollment => ApiError::conflict("totp_not_enrolled", e.to_string()),
+        TotpError::MalformedCode | TotpError::InvalidCode | TotpError::CodeReused | TotpError::InvalidRecoveryCode => {
+            ApiError::new(invalid_status, "invalid_totp", e.to_string())
+        }
+    }
+}
+
+fn failure_reason(e: &TotpError) -> &'static str {
+    match e {
+        TotpError::MalformedCode => "malformed",
+        TotpError::InvalidCode => "invalid_code",
+        TotpError::CodeReused => "code_reused",
+        TotpError::InvalidRecoveryCode => "invalid_recovery_code",
+        TotpError::Locked { .. } => "locked",
+        TotpError::NotEnabled => "not_enabled",
+        TotpError::AlreadyEnabled => "already_enabled",
+        TotpError::NoPendingEnrollment => "no_pending_enrollment",
+    }
+}
+
+/// 校验结果记审计：成功记 success，失败记 verification_failed（附原因）
+async fn audit_outcome(
+    state: &AppState,
+    actor: &Actor,
+    purpose: &str,
+    factor: SecondFactor,
+    success: SecurityEvent,
+    error: Option<&TotpError>,
+) {
+    let detail = format!("purpose={} method={}", purpose, factor.as_str());
+    let (event, detail) = match error {
+        None => (success, detail),
+        Some(e) => (SecurityEvent::VerificationFailed, format!("{} reason={}", detail, failure_reason(e))),
+    };
+    audit::record_security(state, actor.id, &actor.role, event, detail, actor.request_id.clone()).await;
+}
+
+fn session_actor(principal: &Principal, headers: &HeaderMap) -> Actor {
+    Actor {
+        id: principal.user_id,
+        role: principal.role.clone(),
+        request_id: audit::request_id(headers),
+    }
+}
+
+/// 随机恢复码：10 位小写 base32，xxxxx-xxxxx
+fn new_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 7];
+            rand::rngs::OsRng.fill_bytes(&mut bytes);
+            let code = totp::base32_encode(&bytes).to_ascii_lowercase();
+            format!("{}-{}", &code[..5], &code[5..10])
+        })
+        .collect()
+}
+
+/// 登录时附带的第二因素（二选一）
+#[derive(Debug, Default, Deserialize)]
+pub struct SecondFactorInput {
+    #[serde(default)]
+    pub totp_code: Option<String>,
+    #[serde(default)]
+    pub recovery_code: Option<String>,
+}
+
+/// 登录第二因素：未启用 TOTP 返回 None（忽略附带的码）；已启用须附验证码或恢复码，返回所用因素
+pub(crate) async fn verify_login_factor(
+    state: &AppState,
+    user: &User,
+    input: &SecondFactorInput,
+    request_id: String,
+) -> ApiResult<Option<SecondFactor>> {
+    let (factor, code) = match (&input.totp_code, &input.recovery_code) {
+        (Some(code), _) => (SecondFactor::Totp, code.as_str()),
+        (None, Some(code)) => (SecondFactor::RecoveryCode, code.as_str()),
+        (None, None) => (SecondFactor::Totp, ""),
+    };
+    let result = {
+        let mut store = state.store.write().await;
+        if !store.totp.is_enabled(user.id) {
+            return Ok(None);
+        }
+        if code.is_empty() {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "totp_required", "账号已启用两步验证，请提供 totp_code 或 recovery_code")
+                .with_detail(json!({ "methods": [SecondFactor::Totp.as_str(), SecondFactor::RecoveryCode.as_str()] })));
+        }
+        store.totp.verify(user.id, factor, code, Utc::now())
+    };
+    let actor = Actor {
+        id: user.id,
+        role: user.role.clone(),
+        request_id,
+    };
+    audit_outcome(state, &actor, "login", factor, SecurityEvent::LoginVerified, result.as_ref().err()).await;
+    result.map_err(|e| totp_error(e, StatusCode::UNAUTHORIZED))?;
+    Ok(Some(factor))
+}
+
+/// 鉴权中间件调用：仲裁员/运营须已启用 TOTP 才能访问角色专属路由；step_up 路由须有有效 step-up
+pub(crate) async fn enforce(state: &AppState, principal: &Principal, policy: &RoutePolicy, request_id: String) -> ApiResult<()> {
+    if totp::requires_totp(&principal.role) && !principal.totp_enabled && matches!(policy.audience, Audience::Roles(_)) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "totp_enrollment_required",
+            "仲裁员与运营账号须先启用 TOTP 两步验证",
+        )
+        .with_detail(json!({
+            "route": policy.route(),
+            "role": principal.role.as_str(),
+            "enroll": "/api/v1/me/totp/enroll",
+        })));
+    }
+    if policy.step_up && principal.step_up_until.is_none() {
+        audit::record_security(
+            state,
+            principal.user_id,
+            &principal.role,
+            SecurityEvent::StepUpDenied,
+            format!("route={}", policy.route()),
+            request_id,
+        )
+        .await;
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "step_up_required",
+            format!("该操作须在 {} 分钟内完成 TOTP 校验", STEP_UP_WINDOW_MINUTES),
+        )
+        .with_detail(json!({
+            "route": policy.route(),
+            "window_minutes": STEP_UP_WINDOW_MINUTES,
+            "step_up": "/api/v1/me/totp/step-up",
+        })));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct TotpStatusView {
+    pub enabled: bool,
+    /// 已发起启用、尚未确认
+    pub pending: bool,
+    /// 当前角色必须启用
+    pub required: bool,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub recovery_codes_remaining: usize,
+    pub locked_until: Option<DateTime<Utc>>,
+    /// 当前会话 step-up 有效期截止
+    pub step_up_valid_until: Option<DateTime<Utc>>,
+}
+
+pub async fn get_status(State(state): State<AppState>, principal: Principal) -> Json<TotpStatusView> {
+    let now = Utc::now();
+    let store = state.store.read().await;
+    let enrollment = store.totp.get(principal.user_id);
+    Json(TotpStatusView {
+        enabled: enrollment.is_some_and(|e| e.enabled_at.is_some()),
+        pending: enrollment.is_some_and(|e| e.enabled_at.is_none()),
+        required: totp::requires_totp(&principal.role),
+        enabled_at: enrollment.and_then(|e| e.enabled_at),
+        recovery_codes_remaining: enrollment.map_or(0, |e| e.recovery_codes_remaining()),
+        locked_until: enrollment.and_then(|e| e.locked_until).filter(|until| now < *until),
+        step_up_valid_until: store.totp.step_up_valid_until(principal.session_id, now),
+    })
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollmentView {
+    /// Base32 密钥（手动录入认证器）
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub algorithm: &'static str,
+    pub digits: u32,
+    pub period: i64,
+}
+
+pub async fn enroll(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+) -> ApiResult<(StatusCode, Json<TotpEnrollmentView>)> {
+    let mut secret = vec![0u8; TOTP_SECRET_BYTES];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    let label = {
+        let mut store = state.store.write().await;
+        let account = store.users.get(&principal.user_id).ok_or_else(unauthenticated)?;
+        // 钱包账号无邮箱，以账号 id 标识
+        let label = Some(account.user.email.clone())
+            .filter(|e| !e.is_empty())
+            .unwrap_or_else(|| principal.user_id.to_string());
+        store
+            .totp
+            .begin(principal.user_id, secret.clone(), Utc::now())
+            .map_err(|e| totp_error(e, StatusCode::BAD_REQUEST))?;
+        label
+    };
+    let actor = session_actor(&principal, &headers);
+    audit::record_security(
+        &state,
+        actor.id,
+        &actor.role,
+        SecurityEvent::TotpEnrollmentStarted,
+        String::new(),
+        actor.request_id,
+    )
+    .await;
+    Ok((
+        StatusCode::CREATED,
+        Json(TotpEnrollmentView {
+            secret: totp::base32_encode(&secret),
+            otpauth_uri: totp::otpauth_uri(&secret, &label),
+            algorithm: "SHA1",
+            digits: TOTP_DIGITS,
+            period: TOTP_STEP_SECS,
+        }),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CodeRequest {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodesView {
+    /// 一次性恢复码，仅此一次返回明文
+    pub recovery_codes: Vec<String>,
+    pub enabled_at: DateTime<Utc>,
+}
+
+pub async fn confirm(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<CodeRequest>,
+) -> ApiResult<Json<RecoveryCodesView>> {
+    let recovery_codes = new_recovery_codes();
+    let hashes = recovery_codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+    let now = Utc::now();
+    let result = {
+        let mut store = state.store.write().await;
+        let result = store.totp.confirm(principal.user_id, &req.code, hashes, now);
+        if result.is_ok() {
+            store.totp.record_step_up(principal.session_id, now);
+        }
+        result
+    };
+    let actor = session_actor(&principal, &headers);
+    audit_outcome(&state, &actor, "enroll", SecondFactor::Totp, SecurityEvent::TotpEnabled, result.as_ref().err()).await;
+    let enabled_at = result.map_err(|e| totp_error(e, StatusCode::BAD_REQUEST))?;
+    Ok(Json(RecoveryCodesView {
+        recovery_codes,
+        enabled_at,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct StepUpView {
+    pub verified_at: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+}
+
+pub async fn step_up(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<CodeRequest>,
+) -> ApiResult<Json<StepUpView>> {
+    let now = Utc::now();
+    let result = {
+        let mut store = state.store.write().await;
+        let result = store.totp.verify(principal.user_id, SecondFactor::Totp, &req.code, now);
+        if result.is_ok() {
+            store.totp.record_step_up(principal.session_id, now);
+        }
+        result
+    };
+    let actor = session_actor(&principal, &headers);
+    audit_outcome(&state, &actor, "step_up", SecondFactor::Totp, SecurityEvent::StepUpVerified, result.as_ref().err()).await;
+    result.map_err(|e| totp_error(e, StatusCode::BAD_REQUEST))?;
+    Ok(Json(StepUpView {
+        verified_at: now,
+        valid_until: now + chrono::Duration::minutes(STEP_UP_WINDOW_MINUTES),
+    }))
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<CodeRequest>,
+) -> ApiResult<Json<RecoveryCodesView>> {
+    let recovery_codes = new_recovery_codes();
+    let hashes = recovery_codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+    let result = {
+        let mut store = state.store.write().await;
+        store
+            .totp
+            .verify(principal.user_id, SecondFactor::Totp, &req.code, Utc::now())
+            .and_then(|()| store.totp.replace_recovery_codes(principal.user_id, hashes))
+            .map(|()| store.totp.get(principal.user_id).and_then(|e| e.enabled_at))
+    };
+    let actor = session_actor(&principal, &headers);
+    audit_outcome(
+        &state,
+        &actor,
+        "recovery_codes",
+        SecondFactor::Totp,
+        SecurityEvent::RecoveryCodesRegenerated,
+        result.as_ref().err(),
+    )
+    .await;
+    let enabled_at = result.map_err(|e| totp_error(e, StatusCode::BAD_REQUEST))?;
+    Ok(Json(RecoveryCodesView {
+        recovery_codes,
+        enabled_at: enabled_at.unwrap_or_else(Utc::now),
+    }))
+}
+
+pub async fn disable(
+    State(state): State<AppState>,
+    principal: Principal,
+    headers: HeaderMap,
+    Json(req): Json<CodeRequest>,
+) -> ApiResult<StatusCode> {
+    if totp::requires_totp(&principal.role) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "totp_required_for_role",
+            "仲裁员与运营账号不可停用两步验证",
+        ));
+    }
+    let result = {
+        let mut store = state.store.write().await;
+        store
+            .totp
+            .verify(principal.user_id, SecondFactor::Totp, &req.code, Utc::now())
+            .map(|()| store.totp.disable(principal.user_id))
+    };
+    let actor = session_actor(&principal, &headers);
+    audit_outcome(&state, &actor, "disable", SecondFactor::Totp, SecurityEvent::TotpDisabled, result.as_ref().err()).await;
+    result.map_err(|e| totp_error(e, StatusCode::BAD_REQUEST))?;
+    Ok(StatusCode::NO_CONTENT)
+}
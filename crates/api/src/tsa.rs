@@ -0,0 +1,179 @@
+//! 内置时间戳服务（Runbook §11 时间源可验证、08-4 第 3 章证据链司法要求）：仿 RFC 3161 对 sha256 摘要签发时间戳令牌。
+//!
+//! POST /api/v1/timestamps 为任意摘要盖章（请求体 {hash_algorithm?, hashed_message, nonce?}），GET /api/v1/timestamps/keys 公布 TSA 公钥。
+//! 平台自身对证据原文件 hash、签发的回执与争议裁决自动盖章；令牌格式与验签见 traveltrust_core::timestamp。
+//! 密钥来自 env TSA_SIGNING_KEYS（格式同 RECEIPT_SIGNING_KEYS，独立轮换）；未设时启动生成临时密钥，仅用于开发。
+//! 时间回滚检测（Runbook §11）：系统时间早于上一枚令牌超过精度时拒绝签发并告警，依赖时间戳的裁决随之暂停，直至运维确认时间源；
+//! 正常情况下 gen_time 单调不减、序号严格递增。
+
+use crate::audit::Actor;
+use crate::error::{ApiError, ApiResult};
+use crate::receipts::KeyRing;
+use crate::state::AppState;
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::sync::Mutex;
+use traveltrust_core::evidence::RECEIPT_ALGORITHM;
+use traveltrust_core::timestamp::{self, TimestampError, TSA_POLICY, TST_HASH_ALGORITHM, TST_VERSION};
+use traveltrust_core::{MessageImprint, ReceiptPublicKey, TimeStampToken};
+
+/// 时间精度：本机时钟经 NTP 同步的标称误差
+const TSA_ACCURACY_MS: u32 = 1000;
+
+#[derive(Debug)]
+pub enum TsaError {
+    Imprint(TimestampError),
+    /// 系统时间早于上一枚令牌时间
+    ClockRollback { last: DateTime<Utc>, now: DateTime<Utc> },
+}
+
+impl From<TsaError> for ApiError {
+    fn from(e: TsaError) -> Self {
+        match e {
+            TsaError::Imprint(e) => ApiError::bad_request("invalid_imprint", e.to_string()),
+            TsaError::ClockRollback { last, now } => ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "time_rollback_detected",
+                "检测到服务器时间回滚，暂停时间戳签发与依赖时间戳的裁决，待运维确认时间源（Runbook §11）",
+            )
+            .with_detail(serde_json::json!({ "last_gen_time": last, "now": now })),
+        }
+    }
+}
+
+struct TsaClock {
+    serial: u64,
+    last_gen_time: Option<DateTime<Utc>>,
+}
+
+pub struct TimestampAuthority {
+    keys: KeyRing,
+    clock: Mutex<TsaClock>,
+}
+
+impl TimestampAuthority {
+    pub fn from_env() -> Self {
+        Self {
+            keys: KeyRing::from_env("TSA_SIGNING_KEYS", "tsa-dev"),
+            clock: Mutex::new(TsaClock {
+                serial: 0,
+                last_gen_time: None,
+            }),
+        }
+    }
+
+    /// 对 sha256 十六进制摘要盖章
+    pub fn stamp_sha256(&self, hashed_message: &str) -> Result<TimeStampToken, TsaError> {
+        let imprint = timestamp::normalize_imprint(TST_HASH_ALGORITHM, hashed_message).map_err(TsaError::Imprint)?;
+        self.stamp(imprint, None)
+    }
+
+    pub fn stamp(&self, imprint: MessageImprint, nonce: Option<String>) -> Result<TimeStampToken, TsaError> {
+        self.stamp_at(imprint, nonce, Utc::now())
+    }
+
+    /// 以 now 为系统时间盖章；回滚检测与单调化在此完成
+    fn stamp_at(&self, imprint: MessageImprint, nonce: Option<String>, now: DateTime<Utc>) -> Result<TimeStampToken, TsaError> {
+        let (serial_number, gen_time) = {
+            let mut clock = self.clock.lock().unwrap_or_else(|e| e.into_inner());
+            let gen_time = match clock.last_gen_time {
+                Some(last) if now < last - Duration::milliseconds(TSA_ACCURACY_MS.into()) => {
+                    eprintln!("[risk] 检测到服务器时间回滚 last_gen_time={} now={}，拒绝签发时间戳", last, now);
+                    return Err(TsaError::ClockRollback { last, now });
+                }
+                // 精度内的回拨按上一枚时间签发，保持单调
+                Some(last) => now.max(last),
+                None => now,
+            };
+            clock.serial += 1;
+            clock.last_gen_time = Some(gen_time);
+            (clock.serial, gen_time)
+        };
+        let mut token = TimeStampToken {
+            version: TST_VERSION,
+            policy: TSA_POLICY.to_string(),
+            message_imprint: imprint,
+            serial_number,
+            gen_time,
+            accuracy_ms: TSA_ACCURACY_MS,
+            nonce,
+            tsa_key_id: self.keys.active_key_id().to_string(),
+            algorithm: RECEIPT_ALGORITHM.to_string(),
+            signature: String::new(),
+        };
+        token.signature = self.keys.sign(&token.signing_bytes());
+        Ok(token)
+    }
+
+    pub fn public_keys(&self) -> Vec<ReceiptPublicKey> {
+        self.keys.public_keys()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TimestampRequest {
+    #[serde(default = "default_hash_algorithm")]
+    pub hash_algorithm: String,
+    pub hashed_message: String,
+    #[serde(default)]
+    pub nonce: Option<String>,
+}
+
+fn default_hash_algorithm() -> String {
+    TST_HASH_ALGORITHM.to_string()
+}
+
+/// 为调用方摘要盖章（仅登录用户，防匿名刷号）
+pub async fn issue_timestamp(
+    State(state): State<AppState>,
+    _actor: Actor,
+    Json(req): Json<TimestampRequest>,
+) -> ApiResult<Json<TimeStampToken>> {
+    let imprint = timestamp::normalize_imprint(&req.hash_algorithm, &req.hashed_message).map_err(TsaError::Imprint)?;
+    Ok(Json(state.tsa.stamp(imprint, req.nonce)?))
+}
+
+/// TSA 验签公钥列表（traveltrust_core::timestamp::verify_timestamp_with_keys）
+pub async fn tsa_keys(State(state): State<AppState>) -> Json<Vec<ReceiptPublicKey>> {
+    Json(state.tsa.public_keys())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traveltrust_core::timestamp::verify_timestamp_with_keys;
+
+    fn imprint() -> MessageImprint {
+        timestamp::normalize_imprint(TST_HASH_ALGORITHM, &"ab".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn tokens_are_monotonic_and_verify_against_published_keys() {
+        let tsa = TimestampAuthority::from_env();
+        let now = Utc::now();
+        let first = tsa.stamp_at(imprint(), Some("n".into()), now).unwrap();
+        // 精度内回拨按上一枚时间签发
+        let second = tsa.stamp_at(imprint(), None, now - Duration::milliseconds(500)).unwrap();
+        assert_eq!((first.serial_number, second.serial_number), (1, 2));
+        assert_eq!(second.gen_time, first.gen_time);
+        assert_eq!(first.nonce.as_deref(), Some("n"));
+        assert!(verify_timestamp_with_keys(&first, &"ab".repeat(32), &tsa.public_keys()).is_ok());
+        assert!(verify_timestamp_with_keys(&second, &"ab".repeat(32), &tsa.public_keys()).is_ok());
+    }
+
+    #[test]
+    fn rollback_beyond_accuracy_is_refused() {
+        let tsa = TimestampAuthority::from_env();
+        let now = Utc::now();
+        tsa.stamp_at(imprint(), None, now).unwrap();
+        let rolled_back = now - Duration::milliseconds(TSA_ACCURACY_MS as i64 + 1);
+        let err = tsa.stamp_at(imprint(), None, rolled_back).unwrap_err();
+        assert!(matches!(err, TsaError::ClockRollback { last, now: at } if last == now && at == rolled_back));
+        assert_eq!(ApiError::from(err).code, "time_rollback_detected");
+
+        // 拒签不消耗序号；时钟恢复后继续签发
+        let next = tsa.stamp_at(imprint(), None, now + Duration::seconds(1)).unwrap();
+        assert_eq!(next.serial_number, 2);
+    }
+}
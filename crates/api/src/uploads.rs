@@ -0,0 +1,464 @@
+//! 可续传分片上传（01 §6 证据可用性）：弱网环境下单次 50MB 请求易失败，改为会话 + 编号分片 + 完成校验。
+//!
+//! 1. POST   /api/v1/orders/:id/evidence/uploads                       建会话（声明总大小、整文件 sha256、类型）
+//! 2. PUT    /api/v1/orders/:id/evidence/uploads/:upload_id/chunks/:n  上传第 n 片（请求头 x-chunk-sha256），可重传
+//! 3. GET    /api/v1/orders/:id/evidence/uploads/:upload_id            查询已收分片，断点续传
+//! 4. POST   /api/v1/orders/:id/evidence/uploads/:upload_id/complete   拼接并校验整文件 sha256、类型，登记证据并返回签名回执
+//!
+//! 会话发起人为当前登录账号（须为订单双方），后续查询、分片与完成仅限发起人；每个账号同时未过期的会话不超过 UPLOAD_SESSIONS_PER_USER_MAX。
+//! 分片暂存于 EVIDENCE_DIR/.uploads/<upload_id>/；会话无活动超过 UPLOAD_SESSION_TTL_HOURS 即过期，由后台任务清理（含重启后无主目录）。
+//! 完成期间会话标记为 completing：并发的重复完成与分片重传返回 409，清理任务跳过该会话；完成失败则清除标记，可修正后重试。
+
+use crate::auth::Principal;
+use crate::error::{ApiError, ApiResult};
+use crate::evidence::{self as upload, Staged, SNIFF_LEN};
+use crate::rbac;
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use traveltrust_core::{evidence, Ownership, SignedEvidenceReceipt};
+use uuid::Uuid;
+
+/// 分片大小上限（分片路由的请求体上限另加少量余量）
+pub const UPLOAD_CHUNK_MAX_BYTES: u64 = 4 * 1024 * 1024;
+/// 默认分片大小
+const UPLOAD_CHUNK_DEFAULT_BYTES: u64 = 1024 * 1024;
+/// 会话无活动过期时长
+const UPLOAD_SESSION_TTL_HOURS: i64 = 24;
+/// 过期清理周期
+const UPLOAD_SWEEP_INTERVAL_SECS: u64 = 600;
+/// 每个账号同时未过期的上传会话上限（每个会话占用暂存目录直至完成或过期）
+const UPLOAD_SESSIONS_PER_USER_MAX: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub uploader_id: Uuid,
+    pub dispute_id: Option<Uuid>,
+    pub declared_mime: String,
+    pub total_size: u64,
+    pub sha256: String,
+    pub chunk_size: u64,
+    pub chunk_count: u64,
+    pub received: BTreeSet<u64>,
+    /// 正在拼接、登记证据
+    pub completing: bool,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UploadSession {
+    /// 第 index 片应有的字节数（末片可短）
+    fn expected_len(&self, index: u64) -> u64 {
+        if index + 1 == self.chunk_count {
+            self.total_size - self.chunk_size * (self.chunk_count - 1)
+        } else {
+            self.chunk_size
+        }
+    }
+
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.expires_at = now + Duration::hours(UPLOAD_SESSION_TTL_HOURS);
+    }
+
+    fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+
+    fn missing_chunks(&self) -> Vec<u64> {
+        (0..self.chunk_count).filter(|i| !self.received.contains(i)).collect()
+    }
+
+    /// 分片序号与长度校验；完成期间拒绝重传
+    fn check_chunk(&self, index: u64, len: u64) -> ApiResult<()> {
+        if self.completing {
+            return Err(completing_error());
+        }
+        if index >= self.chunk_count {
+            return Err(ApiError::bad_request("invalid_chunk_index", format!("分片序号须小于 {}", self.chunk_count)));
+        }
+        if len != self.expected_len(index) {
+            return Err(ApiError::bad_request(
+                "invalid_chunk_length",
+                format!("第 {} 片应为 {} 字节", index, self.expected_len(index)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 分片收齐且未在完成中时标记 completing
+    fn begin_complete(&mut self) -> ApiResult<()> {
+        if self.completing {
+            return Err(completing_error());
+        }
+        let missing = self.missing_chunks();
+        if !missing.is_empty() {
+            return Err(ApiError::conflict("upload_incomplete", "仍有分片未上传").with_detail(serde_json::json!({ "missing": missing })));
+        }
+        self.completing = true;
+        Ok(())
+    }
+}
+
+fn completing_error() -> ApiError {
+    ApiError::conflict("upload_completing", "上传会话正在完成，请勿重复提交")
+}
+
+/// 账号名下未过期的上传会话数
+fn open_sessions(sessions: &HashMap<Uuid, UploadSession>, uploader_id: Uuid, now: DateTime<Utc>) -> usize {
+    sessions.values().filter(|s| s.uploader_id == uploader_id && s.is_live(now)).count()
+}
+
+fn session_dir(state: &AppState, id: Uuid) -> PathBuf {
+    state.evidence_dir.join(".uploads").join(id.to_string())
+}
+
+#[derive(Deserialize)]
+pub struct CreateUploadRequest {
+    pub dispute_id: Option<Uuid>,
+    pub declared_mime: String,
+    pub total_size: u64,
+    /// 整文件 sha256（小写十六进制），完成时校验
+    pub sha256: String,
+    pub chunk_size: Option<u64>,
+}
+
+pub async fn create_upload(
+    State(state): State<AppState>,
+    Path(order_id): Path<Uuid>,
+    principal: Principal,
+    Json(req): Json<CreateUploadRequest>,
+) -> ApiResult<(StatusCode, Json<UploadSession>)> {
+    evidence::validate_size(req.total_size)?;
+    if !evidence::is_allowed_mime(&req.declared_mime) {
+        return Err(evidence::EvidenceError::TypeNotAllowed(req.declared_mime).into());
+    }
+    let sha256 = req.sha256.trim().to_ascii_lowercase();
+    evidence::validate_sha256(&sha256)?;
+    let chunk_size = req.chunk_size.unwrap_or(UPLOAD_CHUNK_DEFAULT_BYTES);
+    if chunk_size == 0 || chunk_size > UPLOAD_CHUNK_MAX_BYTES {
+        return Err(ApiError::bad_request(
+            "invalid_chunk_size",
+            format!("chunk_size 须在 1..={} 字节之间", UPLOAD_CHUNK_MAX_BYTES),
+        ));
+    }
+
+    let mut store = state.store.write().await;
+    upload::ensure_order_party(&store, order_id, &principal)?;
+    let now = Utc::now();
+    if open_sessions(&store.upload_sessions, principal.user_id, now) >= UPLOAD_SESSIONS_PER_USER_MAX {
+        return Err(ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too_many_open_uploads",
+            format!("未完成的上传会话不得超过 {} 个，请先完成或等待过期", UPLOAD_SESSIONS_PER_USER_MAX),
+        ));
+    }
+    let mut session = UploadSession {
+        id: Uuid::new_v4(),
+        order_id,
+        uploader_id: principal.user_id,
+        dispute_id: req.dispute_id,
+        declared_mime: req.declared_mime,
+        total_size: req.total_size,
+        sha256,
+        chunk_size,
+        chunk_count: req.total_size.div_ceil(chunk_size),
+        received: BTreeSet::new(),
+        completing: false,
+        created_at: now,
+        expires_at: now,
+    };
+    session.touch(now);
+    tokio::fs::create_dir_all(session_dir(&state, session.id))
+        .await
+        .map_err(upload::storage_error)?;
+    store.upload_sessions.insert(session.id, session.clone());
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+pub async fn get_upload(
+    State(state): State<AppState>,
+    Path((order_id, upload_id)): Path<(Uuid, Uuid)>,
+    principal: Principal,
+) -> ApiResult<Json<UploadSession>> {
+    let store = state.store.read().await;
+    let session = live_session(&store.upload_sessions, order_id, upload_id, &principal)?;
+    Ok(Json(session.clone()))
+}
+
+pub async fn put_chunk(
+    State(state): State<AppState>,
+    Path((order_id, upload_id, index)): Path<(Uuid, Uuid, u64)>,
+    principal: Principal,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<Json<UploadSession>> {
+    let expected_hash = headers
+        .get("x-chunk-sha256")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .ok_or_else(|| ApiError::bad_request("missing_chunk_hash", "缺少请求头 x-chunk-sha256"))?;
+    {
+        let store = state.store.read().await;
+        live_session(&store.upload_sessions, order_id, upload_id, &principal)?.check_chunk(index, body.len() as u64)?;
+    }
+    if hex::encode(Sha256::digest(&body)) != expected_hash {
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "chunk_checksum_mismatch", "分片 sha256 不一致，请重传"));
+    }
+    // 先写临时名再 rename，重传同一分片时不会留下半写文件
+    let dir = session_dir(&state, upload_id);
+    let tmp = dir.join(format!("{}.part", index));
+    tokio::fs::write(&tmp, &body).await.map_err(upload::storage_error)?;
+    tokio::fs::rename(&tmp, dir.join(index.to_string())).await.map_err(upload::storage_error)?;
+
+    let mut store = state.store.write().await;
+    let session = store
+        .upload_sessions
+        .get_mut(&upload_id)
+        .ok_or_else(|| ApiError::not_found("上传会话"))?;
+    // 写盘期间会话可能已进入完成：不再登记，由客户端按 409 处理
+    if session.completing {
+        return Err(completing_error());
+    }
+    session.received.insert(index);
+    session.touch(Utc::now());
+    Ok(Json(session.clone()))
+}
+
+pub async fn complete_upload(
+    State(state): State<AppState>,
+    Path((order_id, upload_id)): Path<(Uuid, Uuid)>,
+    principal: Principal,
+) -> ApiResult<(StatusCode, Json<SignedEvidenceReceipt>)> {
+    let session = {
+        let mut store = state.store.write().await;
+        live_session(&store.upload_sessions, order_id, upload_id, &principal)?;
+        let session = store
+            .upload_sessions
+            .get_mut(&upload_id)
+            .ok_or_else(|| ApiError::not_found("上传会话"))?;
+        session.begin_complete()?;
+        session.clone()
+    };
+
+    let tmp_path = state.evidence_dir.join(format!(".upload-{}", Uuid::new_v4()));
+    let assembled = assemble(&state, &session, &tmp_path).await;
+    let outcome = match assembled {
+        Ok(staged) => upload::finalize(&state, order_id, session.uploader_id, session.dispute_id, &staged).await,
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&tmp_path).await;
+    let evidence = match outcome {
+        Ok(evidence) => evidence,
+        Err(e) => {
+            if let Some(session) = state.store.write().await.upload_sessions.get_mut(&upload_id) {
+                session.completing = false;
+                session.touch(Utc::now());
+            }
+            return Err(e);
+        }
+    };
+
+    state.store.write().await.upload_sessions.remove(&upload_id);
+    let _ = tokio::fs::remove_dir_all(session_dir(&state, upload_id)).await;
+    Ok((StatusCode::CREATED, Json(upload::issue_receipt(&state, &evidence))))
+}
+
+/// 按序拼接分片到临时文件，边拼边算整文件 sha256，并校验类型与声明 hash
+async fn assemble(state: &AppState, session: &UploadSession, tmp_path: &std::path::Path) -> ApiResult<Staged> {
+    let dir = session_dir(state, session.id);
+    let mut file = tokio::fs::File::create(tmp_path).await.map_err(upload::storage_error)?;
+    let mut hasher = Sha256::new();
+    let mut head: Vec<u8> = Vec::with_capacity(SNIFF_LEN);
+    let mut size: u64 = 0;
+    for index in 0..session.chunk_count {
+        let chunk = tokio::fs::read(dir.join(index.to_string())).await.map_err(upload::storage_error)?;
+        if head.len() < SNIFF_LEN {
+            head.extend(chunk.iter().take(SNIFF_LEN - head.len()));
+        }
+        size += chunk.len() as u64;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(upload::storage_error)?;
+    }
+    file.sync_all().await.map_err(upload::storage_error)?;
+    let sha256 = hex::encode(hasher.finalize());
+    if sha256 != session.sha256 {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "checksum_mismatch",
+            "整文件 sha256 与会话声明不一致",
+        ));
+    }
+    let sniffed_mime = upload::check_type(&session.declared_mime, &head)?;
+    Ok(Staged {
+        tmp_path: tmp_path.to_path_buf(),
+        sha256,
+        size,
+        declared_mime: session.declared_mime.clone(),
+        sniffed_mime,
+    })
+}
+
+fn live_session<'a>(
+    sessions: &'a HashMap<Uuid, UploadSession>,
+    order_id: Uuid,
+    upload_id: Uuid,
+    principal: &Principal,
+) -> ApiResult<&'a UploadSession> {
+    let session = sessions
+        .get(&upload_id)
+        .filter(|s| s.order_id == order_id && s.is_live(Utc::now()))
+        .ok_or_else(|| ApiError::not_found("上传会话"))?;
+    rbac::ensure_owner(session.uploader_id == principal.user_id, Ownership::UploadOwner, &principal.role)?;
+    Ok(session)
+}
+
+/// 后台清理：周期性移除过期会话及其分片目录（完成中的会话除外）
+pub async fn sweep_expired_uploads(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(UPLOAD_SWEEP_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let now = Utc::now();
+        let expired: Vec<Uuid> = {
+            let mut store = state.store.write().await;
+            let ids: Vec<Uuid> = store
+                .upload_sessions
+                .values()
+                .filter(|s| !s.completing && !s.is_live(now))
+                .map(|s| s.id)
+                .collect();
+            for id in &ids {
+                store.upload_sessions.remove(id);
+            }
+            ids
+        };
+        for id in expired {
+            let _ = tokio::fs::remove_dir_all(session_dir(&state, id)).await;
+            eprintln!("[evidence] 分片上传会话过期清理 upload={}", id);
+        }
+        remove_orphan_dirs(&state).await;
+    }
+}
+
+/// 进程重启后内存会话丢失，其分片目录无主，一并清理
+async fn remove_orphan_dirs(state: &AppState) {
+    let Ok(mut entries) = tokio::fs::read_dir(state.evidence_dir.join(".uploads")).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let id = entry.file_name().to_str().and_then(|n| Uuid::parse_str(n).ok());
+        let live = match id {
+            Some(id) => state.store.read().await.upload_sessions.contains_key(&id),
+            None => false,
+        };
+        if !live {
+            let _ = tokio::fs::remove_dir_all(entry.path()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traveltrust_core::UserRole;
+
+    /// 2.5 片：两片整 1MB，末片 512KB
+    fn session(uploader_id: Uuid, now: DateTime<Utc>) -> UploadSession {
+        let total_size = 2 * UPLOAD_CHUNK_DEFAULT_BYTES + UPLOAD_CHUNK_DEFAULT_BYTES / 2;
+        let mut session = UploadSession {
+            id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            uploader_id,
+            dispute_id: None,
+            declared_mime: "image/jpeg".into(),
+            total_size,
+            sha256: "0".repeat(64),
+            chunk_size: UPLOAD_CHUNK_DEFAULT_BYTES,
+            chunk_count: total_size.div_ceil(UPLOAD_CHUNK_DEFAULT_BYTES),
+            received: BTreeSet::new(),
+            completing: false,
+            created_at: now,
+            expires_at: now,
+        };
+        session.touch(now);
+        session
+    }
+
+    fn principal(user_id: Uuid) -> Principal {
+        Principal {
+            user_id,
+            role: UserRole::Tourist,
+            session_id: Uuid::new_v4(),
+            totp_enabled: false,
+            step_up_until: None,
+        }
+    }
+
+    #[test]
+    fn resume_reports_missing_chunks_until_complete() {
+        let mut s = session(Uuid::new_v4(), Utc::now());
+        assert_eq!(s.chunk_count, 3);
+        s.received.insert(0);
+        s.received.insert(2);
+        assert_eq!(s.missing_chunks(), vec![1]);
+        let err = s.begin_complete().unwrap_err();
+        assert_eq!((err.status, err.code), (StatusCode::CONFLICT, "upload_incomplete"));
+        assert!(!s.completing);
+
+        // 重传已收分片与补传缺片均可
+        assert!(s.check_chunk(0, UPLOAD_CHUNK_DEFAULT_BYTES).is_ok());
+        s.received.insert(1);
+        assert!(s.missing_chunks().is_empty());
+        assert!(s.begin_complete().is_ok());
+    }
+
+    #[test]
+    fn completing_session_rejects_second_complete_and_chunks() {
+        let mut s = session(Uuid::new_v4(), Utc::now());
+        s.received.extend(0..s.chunk_count);
+        s.begin_complete().unwrap();
+        assert_eq!(s.begin_complete().unwrap_err().code, "upload_completing");
+        assert_eq!(s.check_chunk(0, UPLOAD_CHUNK_DEFAULT_BYTES).unwrap_err().code, "upload_completing");
+    }
+
+    #[test]
+    fn out_of_range_or_mis_sized_chunks_are_rejected() {
+        let s = session(Uuid::new_v4(), Utc::now());
+        assert_eq!(s.check_chunk(3, UPLOAD_CHUNK_DEFAULT_BYTES / 2).unwrap_err().code, "invalid_chunk_index");
+        assert_eq!(s.check_chunk(u64::MAX, 1).unwrap_err().code, "invalid_chunk_index");
+        assert_eq!(s.check_chunk(0, UPLOAD_CHUNK_DEFAULT_BYTES - 1).unwrap_err().code, "invalid_chunk_length");
+        assert_eq!(s.check_chunk(2, UPLOAD_CHUNK_DEFAULT_BYTES).unwrap_err().code, "invalid_chunk_length");
+        assert!(s.check_chunk(2, UPLOAD_CHUNK_DEFAULT_BYTES / 2).is_ok());
+    }
+
+    #[test]
+    fn expired_sessions_are_not_found_and_not_counted() {
+        let user = Uuid::new_v4();
+        let now = Utc::now();
+        let live = session(user, now);
+        let expired = session(user, now - Duration::hours(UPLOAD_SESSION_TTL_HOURS + 1));
+        let (live_id, live_order) = (live.id, live.order_id);
+        let (expired_id, expired_order) = (expired.id, expired.order_id);
+        let sessions: HashMap<Uuid, UploadSession> = [(live.id, live), (expired.id, expired)].into();
+
+        assert!(live_session(&sessions, live_order, live_id, &principal(user)).is_ok());
+        assert_eq!(live_session(&sessions, expired_order, expired_id, &principal(user)).unwrap_err().status, StatusCode::NOT_FOUND);
+        // 订单不符同样视为不存在；他人会话拒绝
+        assert_eq!(live_session(&sessions, expired_order, live_id, &principal(user)).unwrap_err().status, StatusCode::NOT_FOUND);
+        assert!(live_session(&sessions, live_order, live_id, &principal(Uuid::new_v4())).is_err());
+
+        assert_eq!(open_sessions(&sessions, user, now), 1);
+        assert_eq!(open_sessions(&sessions, Uuid::new_v4(), now), 0);
+    }
+}
@@ -0,0 +1,160 @@
+//! 钱包绑定（01 §2 账号设置「绑定/解绑钱包」、P0-1、01 §10 12 缝 #11；签名与换绑规则见 traveltrust_core::wallet）。
+//!
+//! 1. POST   /api/v1/me/wallet/challenge {address}   签发一次性挑战（10 分钟），返回待签消息 message 与 nonce
+//! 2. PUT    /api/v1/me/wallet {nonce, signature}    钱包对 message 做 personal_sign，服务端恢复地址一致后登记绑定，原绑定关闭（rebind）
+//! 3. DELETE /api/v1/me/wallet                       解绑当前钱包
+//! 4. GET    /api/v1/me/wallets                      绑定历史（含已换绑、已解绑）
+//!
+//! 地址已绑定其他账号返回 409 wallet_bound_elsewhere；已有绑定且存在未终结订单、未裁决争议或进行中证据上传时，
+//! 换绑与解绑返回 409 wallet_rebind_blocked，detail.blockers 列出阻止项（旧订单签名钱包不可变更，Paid 后换钱包=争议或新单）。
+//! 绑定钱包即可用于 SIWE 登录（见 siwe）；未关联邮箱的钱包账号不可解绑（409 last_login_method）。
+
+use crate::auth::{unauthenticated, AccountView, Principal};
+use crate::error::{ApiError, ApiResult};
+use crate::state::AppState;
+use crate::store::Store;
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::json;
+use traveltrust_core::wallet::{self, WalletError};
+use traveltrust_core::{WalletBinding, WalletChallenge};
+use uuid::Uuid;
+
+fn wallet_error(e: WalletError) -> ApiError {
+    let code = match e {
+        WalletError::InvalidAddress => "invalid_address",
+        WalletError::InvalidSignature | WalletError::HighS => "invalid_signature",
+        WalletError::AddressMismatch => "signature_address_mismatch",
+        WalletError::UnknownChallenge => "challenge_invalid",
+        WalletError::ChallengeExpired => "challenge_expired",
+    };
+    ApiError::bad_request(code, e.to_string())
+}
+
+/// 地址可否绑定到 user_id：不得已绑定其他账号；已有绑定时须无未终结事项
+fn ensure_bindable(store: &Store, user_id: Uuid, address: &str) -> ApiResult<()> {
+    match store.wallets.owner_of(address) {
+        Some(owner) if owner == user_id => {
+            return Err(ApiError::conflict("wallet_already_bound", "该钱包已绑定当前账号"));
+        }
+        Some(_) => return Err(ApiError::conflict("wallet_bound_elsewhere", "该钱包已绑定其他账号")),
+        None => {}
+    }
+    if store.wallets.current(user_id).is_some() {
+        ensure_no_blockers(store, user_id)?;
+    }
+    Ok(())
+}
+
+fn ensure_no_blockers(store: &Store, user_id: Uuid) -> ApiResult<()> {
+    let blockers = store.rebind_blockers(user_id);
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::conflict(
+            "wallet_rebind_blocked",
+            "存在未终结的订单、争议或证据上传，暂不可换绑或解绑钱包；已支付订单请走争议或新单",
+        )
+        .with_detail(json!({ "blockers": blockers })))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChallengeRequest {
+    pub address: String,
+}
+
+pub async fn create_challenge(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<ChallengeRequest>,
+) -> ApiResult<(StatusCode, Json<WalletChallenge>)> {
+    let address = wallet::normalize_address(&req.address).map_err(wallet_error)?;
+    let mut nonce = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let mut store = state.store.write().await;
+    ensure_bindable(&store, principal.user_id, &address)?;
+    let challenge = store
+        .wallets
+        .issue_challenge(principal.user_id, address, hex::encode(nonce), Utc::now());
+    Ok((StatusCode::CREATED, Json(challenge)))
+}
+
+#[derive(Deserialize)]
+pub struct BindWalletRequest {
+    pub nonce: String,
+    /// 65 字节 r‖s‖v 十六进制（personal_sign 返回值）
+    pub signature: String,
+}
+
+pub async fn bind_wallet(
+    State(state): State<AppState>,
+    principal: Principal,
+    Json(req): Json<BindWalletRequest>,
+) -> ApiResult<Json<AccountView>> {
+    // 挑战先取出（一次性），验签失败须重新获取
+    let challenge = state
+        .store
+        .write()
+        .await
+        .wallets
+        .take_challenge(&req.nonce, principal.user_id, Utc::now())
+        .map_err(wallet_error)?;
+    wallet::verify_personal_sign(&challenge.message, &req.signature, &challenge.address).map_err(wallet_error)?;
+
+    let mut store = state.store.write().await;
+    // 签名期间可能已被其他账号绑定或新建订单
+    ensure_bindable(&store, principal.user_id, &challenge.address)?;
+    let previous = store.wallets.current(principal.user_id).map(|b| b.address.clone());
+    let binding = store.wallets.bind(
+        principal.user_id,
+        challenge.address.clone(),
+        req.signature,
+        challenge.nonce.clone(),
+        Utc::now(),
+    );
+    let account = store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?;
+    account.user.default_wallet_address = Some(binding.address.clone());
+    account.user.updated_at = binding.bound_at;
+    let view = AccountView::from(&*account);
+    drop(store);
+    eprintln!(
+        "[wallet] 绑定 user={} address={} previous={}",
+        principal.user_id,
+        binding.address,
+        previous.as_deref().unwrap_or("-")
+    );
+    Ok(Json(view))
+}
+
+pub async fn unbind_wallet(State(state): State<AppState>, principal: Principal) -> ApiResult<Json<AccountView>> {
+    let mut store = state.store.write().await;
+    if store.wallets.current(principal.user_id).is_none() {
+        return Err(ApiError::not_found("当前钱包绑定"));
+    }
+    ensure_no_blockers(&store, principal.user_id)?;
+    if store.users.get(&principal.user_id).is_some_and(|a| a.user.email.is_empty()) {
+        return Err(ApiError::conflict(
+            "last_login_method",
+            "钱包是该账号唯一的登录方式，请先关联邮箱（POST /api/v1/me/email）再解绑",
+        ));
+    }
+    let now = Utc::now();
+    let closed = store.wallets.unbind(principal.user_id, now);
+    let account = store.users.get_mut(&principal.user_id).ok_or_else(unauthenticated)?;
+    account.user.default_wallet_address = None;
+    account.user.updated_at = now;
+    let view = AccountView::from(&*account);
+    drop(store);
+    if let Some(b) = closed {
+        eprintln!("[wallet] 解绑 user={} address={}", principal.user_id, b.address);
+    }
+    Ok(Json(view))
+}
+
+pub async fn list_wallets(State(state): State<AppState>, principal: Principal) -> Json<Vec<WalletBinding>> {
+    Json(state.store.read().await.wallets.history(principal.user_id))
+}
@@ -0,0 +1,175 @@
+//! 代币金额（04 §二 2.2 orders.amount、stakes.amount）：十进制字符串与链上最小单位一一对应，汇总不经浮点。
+//!
+//! 内部以 18 位小数定点（u128）表示，覆盖 USDC/USDT（6 位）与 18 位小数代币；超出精度或溢出即拒绝，不截断。
+//! 序列化为去尾零的十进制字符串（"12.5"），与订单 amount、EIP-712 OrderIntent.amount 的写法一致。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// 定点小数位数
+pub const TOKEN_DECIMALS: u32 = 18;
+const SCALE: u128 = 10u128.pow(TOKEN_DECIMALS);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("金额须为非负十进制字符串")]
+    Invalid,
+    #[error("金额小数位不得超过 {TOKEN_DECIMALS} 位")]
+    TooManyDecimals,
+    #[error("金额超出可表示范围")]
+    Overflow,
+}
+
+/// 非负代币金额，精确到 10^-18
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TokenAmount(u128);
+
+impl TokenAmount {
+    pub const ZERO: TokenAmount = TokenAmount(0);
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 汇总用：u128 上限约 3.4×10^20 个代币，实际不可达，溢出时取上限
+    pub fn saturating_add(self, other: TokenAmount) -> TokenAmount {
+        TokenAmount(self.0.saturating_add(other.0))
+    }
+
+    /// 不足时为零（退款不超过原额，防御性处理）
+    pub fn saturating_sub(self, other: TokenAmount) -> TokenAmount {
+        TokenAmount(self.0.saturating_sub(other.0))
+    }
+}
+
+impl FromStr for TokenAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, AmountError> {
+        let s = s.trim();
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        let digits = |p: &str| p.bytes().all(|b| b.is_ascii_digit());
+        if int.is_empty() || !digits(int) || !digits(frac) || (s.contains('.') && frac.is_empty()) {
+            return Err(AmountError::Invalid);
+        }
+        if frac.len() > TOKEN_DECIMALS as usize {
+            return Err(AmountError::TooManyDecimals);
+        }
+        let int: u128 = int.parse().map_err(|_| AmountError::Overflow)?;
+        let frac: u128 = if frac.is_empty() {
+            0
+        } else {
+            frac.parse::<u128>().map_err(|_| AmountError::Invalid)? * 10u128.pow(TOKEN_DECIMALS - frac.len() as u32)
+        };
+        int.checked_mul(SCALE)
+            .and_then(|v| v.checked_add(frac))
+            .map(TokenAmount)
+            .ok_or(AmountError::Overflow)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (int, frac) = (self.0 / SCALE, self.0 % SCALE);
+        if frac == 0 {
+            return write!(f, "{}", int);
+        }
+        let frac = format!("{:0width$}", frac, width = TOKEN_DECIMALS as usize);
+        write!(f, "{}.{}", int, frac.trim_end_matches('0'))
+    }
+}
+
+impl TryFrom<String> for TokenAmount {
+    type Error = AmountError;
+
+    fn try_from(s: String) -> Result<Self, AmountError> {
+        s.parse()
+    }
+}
+
+impl From<TokenAmount> for String {
+    fn from(a: TokenAmount) -> String {
+        a.to_string()
+    }
+}
+
+/// 按币种汇总的金额（币种为订单 currency 原值，序列化为 {"USDC": "12.5"}）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CurrencyTotals(BTreeMap<String, TokenAmount>);
+
+impl CurrencyTotals {
+    pub fn add(&mut self, currency: &str, amount: TokenAmount) {
+        let total = self.0.entry(currency.to_string()).or_default();
+        *total = total.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(s: &str) -> TokenAmount {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays_decimals() {
+        assert_eq!(amount("0"), TokenAmount::ZERO);
+        assert_eq!(amount("12").to_string(), "12");
+        assert_eq!(amount("12.5").to_string(), "12.5");
+        assert_eq!(amount(" 0.000001 ").to_string(), "0.000001");
+        assert_eq!(amount("0.000000000000000001").to_string(), "0.000000000000000001");
+        assert_eq!(amount("1.5"), amount("1.500000"));
+    }
+
+    #[test]
+    fn display_trims_trailing_zeros() {
+        assert_eq!(amount("12.50").to_string(), "12.5");
+        assert_eq!(amount("100.000000").to_string(), "100");
+        assert_eq!(amount("007.10").to_string(), "7.1");
+        assert_eq!(amount("10.01").to_string(), "10.01");
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for s in ["", ".", "1.", ".5", "-1", "+1", "1e6", "1,5", "1.2.3", "0x10", "1 000"] {
+            assert_eq!(s.parse::<TokenAmount>(), Err(AmountError::Invalid), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_excess_precision_and_overflow() {
+        assert_eq!("0.0000000000000000001".parse::<TokenAmount>(), Err(AmountError::TooManyDecimals));
+        // u128::MAX / 10^18 ≈ 3.4×10^20，整数部分再大即溢出
+        let max_int = (u128::MAX / SCALE).to_string();
+        assert_eq!(amount(&max_int).to_string(), max_int);
+        let over = (u128::MAX / SCALE + 1).to_string();
+        assert_eq!(over.parse::<TokenAmount>(), Err(AmountError::Overflow));
+        assert_eq!(format!("{}.999999999999999999", max_int).parse::<TokenAmount>(), Err(AmountError::Overflow));
+        assert_eq!("1".repeat(40).parse::<TokenAmount>(), Err(AmountError::Overflow));
+    }
+
+    #[test]
+    fn string_conversions_round_trip() {
+        assert_eq!(String::from(amount("12.50")), "12.5");
+        assert_eq!(TokenAmount::try_from("0.25".to_string()), Ok(amount("0.25")));
+        assert_eq!(TokenAmount::try_from("abc".to_string()), Err(AmountError::Invalid));
+    }
+
+    #[test]
+    fn totals_are_per_currency_and_saturate() {
+        let mut totals = CurrencyTotals::default();
+        totals.add("USDC", amount("1.25"));
+        totals.add("USDC", amount("2.75"));
+        totals.add("USDT", amount("0.5"));
+        let expected = BTreeMap::from([("USDC".to_string(), amount("4")), ("USDT".to_string(), amount("0.5"))]);
+        assert_eq!(totals, CurrencyTotals(expected));
+        assert_eq!(amount("1").saturating_sub(amount("2")), TokenAmount::ZERO);
+        assert_eq!(TokenAmount(u128::MAX).saturating_add(amount("1")), TokenAmount(u128::MAX));
+    }
+}
@@ -0,0 +1,411 @@
+//! 批量上链锚定（contracts/README Reputation 存证、01 附表「证据时间戳：TSA/区块时间锚定」、Runbook §11 独立时间锚）
+//!
+//! 逐条上链成本过高：证据 hash、评价 hash、裁决摘要先入队，按周期封批构建 Merkle 树，仅把根与批次序号写链。
+//! 树构造取 RFC 6962 做法：叶 = sha256(0x00 ‖ 叶内容)、节点 = sha256(0x01 ‖ 左 ‖ 右)，防第二原像；奇数层末节点直接上提，不复制。
+//! 包含证明（InclusionProof）只含兄弟节点路径与锚定交易，任何一方可用 `InclusionProof::verify` 离线重算根，再对照链上交易，
+//! 证明该条目不晚于锚定区块时间已存在。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorKind {
+    /// 证据原文件 sha256
+    Evidence,
+    /// 评价内容 hash
+    Review,
+    /// 裁决摘要（Dispute::resolution_digest）
+    Ruling,
+}
+
+impl AnchorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnchorKind::Evidence => "evidence",
+            AnchorKind::Review => "review",
+            AnchorKind::Ruling => "ruling",
+        }
+    }
+}
+
+/// 待锚定条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorItem {
+    pub kind: AnchorKind,
+    /// 证据 id / 评价 id / 争议 id
+    pub ref_id: Uuid,
+    /// 被锚定的 sha256（小写十六进制）
+    pub hash: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+impl AnchorItem {
+    /// 叶哈希：sha256(0x00 ‖ "kind\nref_id\nhash")，十六进制
+    pub fn leaf_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update([0x00]);
+        hasher.update(format!("{}\n{}\n{}", self.kind.as_str(), self.ref_id, self.hash).as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// 锚定交易引用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorTx {
+    /// 链名（如 local、polygon）
+    pub chain: String,
+    pub chain_id: u64,
+    pub tx_hash: String,
+    pub block_number: u64,
+    /// 区块时间
+    pub anchored_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorBatch {
+    pub id: Uuid,
+    /// 批次序号（从 1 递增，随根一同写链）
+    pub seq: u64,
+    /// Merkle 根，十六进制
+    pub root: String,
+    pub items: Vec<AnchorItem>,
+    pub sealed_at: DateTime<Utc>,
+    /// 锚定交易；None 表示待上链（失败后下轮重试）
+    pub anchor: Option<AnchorTx>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSide {
+    Left,
+    Right,
+}
+
+/// 证明路径中的一步：兄弟节点及其所在侧
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: ProofSide,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub item: AnchorItem,
+    pub leaf_hash: String,
+    pub leaf_index: usize,
+    pub leaf_count: usize,
+    pub path: Vec<ProofStep>,
+    pub batch_id: Uuid,
+    pub batch_seq: u64,
+    pub root: String,
+    pub anchor: Option<AnchorTx>,
+}
+
+impl InclusionProof {
+    /// 由条目与路径重算根，核对与 root 一致（不含链上核对）
+    pub fn verify(&self) -> bool {
+        self.item.leaf_hash() == self.leaf_hash && verify_inclusion(&self.leaf_hash, &self.path, &self.root)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AnchorError {
+    #[error("条目未登记锚定")]
+    NotFound,
+    #[error("条目已入队，待下一批次封批")]
+    Queued,
+    #[error("批次不存在")]
+    UnknownBatch,
+}
+
+fn node_hash(left: &[u8], right: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// 由叶哈希（十六进制）逐层构造，返回各层（第 0 层为叶）；非法十六进制按空字节参与（调用方保证合法）
+fn build_levels(leaves: &[String]) -> Vec<Vec<[u8; 32]>> {
+    let mut level: Vec<[u8; 32]> = leaves
+        .iter()
+        .map(|l| hex::decode(l).ok().and_then(|b| b.try_into().ok()).unwrap_or([0u8; 32]))
+        .collect();
+    let mut levels = Vec::new();
+    while level.len() > 1 {
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                [lone] => *lone,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(std::mem::replace(&mut level, next));
+    }
+    levels.push(level);
+    levels
+}
+
+/// Merkle 根；空集为 sha256("")
+pub fn merkle_root(leaves: &[String]) -> String {
+    if leaves.is_empty() {
+        return hex::encode(Sha256::digest([]));
+    }
+    let levels = build_levels(leaves);
+    hex::encode(levels[levels.len() - 1][0])
+}
+
+/// 第 index 个叶的包含路径
+pub fn merkle_path(leaves: &[String], index: usize) -> Vec<ProofStep> {
+    let mut path = Vec::new();
+    if index >= leaves.len() {
+        return path;
+    }
+    let levels = build_levels(leaves);
+    let mut idx = index;
+    for level in &levels[..levels.len() - 1] {
+        let sibling = idx ^ 1;
+        if let Some(hash) = level.get(sibling) {
+            path.push(ProofStep {
+                side: if sibling < idx { ProofSide::Left } else { ProofSide::Right },
+                hash: hex::encode(hash),
+            });
+        }
+        idx /= 2;
+    }
+    path
+}
+
+/// 由叶哈希与路径重算根并比较
+pub fn verify_inclusion(leaf_hash: &str, path: &[ProofStep], root: &str) -> bool {
+    let Some(mut acc) = hex::decode(leaf_hash).ok().and_then(|b| <[u8; 32]>::try_from(b).ok()) else {
+        return false;
+    };
+    for step in path {
+        let Some(sibling) = hex::decode(&step.hash).ok().and_then(|b| <[u8; 32]>::try_from(b).ok()) else {
+            return false;
+        };
+        acc = match step.side {
+            ProofSide::Left => node_hash(&sibling, &acc),
+            ProofSide::Right => node_hash(&acc, &sibling),
+        };
+    }
+    hex::encode(acc) == root.to_ascii_lowercase()
+}
+
+/// 锚定账本：待封批队列 + 已封批次（只追加）
+#[derive(Debug, Default)]
+pub struct AnchorLedger {
+    queue: Vec<AnchorItem>,
+    batches: Vec<AnchorBatch>,
+    /// (kind, ref_id) → (批次下标, 叶下标)
+    index: HashMap<(AnchorKind, Uuid), (usize, usize)>,
+}
+
+impl AnchorLedger {
+    /// 入队；同一 (kind, ref_id) 已入队或已封批时忽略
+    pub fn enqueue(&mut self, kind: AnchorKind, ref_id: Uuid, hash: impl Into<String>, now: DateTime<Utc>) {
+        if self.index.contains_key(&(kind, ref_id)) || self.queue.iter().any(|i| i.kind == kind && i.ref_id == ref_id) {
+            return;
+        }
+        self.queue.push(AnchorItem {
+            kind,
+            ref_id,
+            hash: hash.into().to_ascii_lowercase(),
+            queued_at: now,
+        });
+    }
+
+    pub fn queued(&self) -> &[AnchorItem] {
+        &self.queue
+    }
+
+    pub fn batches(&self) -> &[AnchorBatch] {
+        &self.batches
+    }
+
+    pub fn batch(&self, id: Uuid) -> Option<&AnchorBatch> {
+        self.batches.iter().find(|b| b.id == id)
+    }
+
+    /// 把队列封为新批次并计算根；队列为空时返回 None
+    pub fn seal(&mut self, now: DateTime<Utc>) -> Option<&AnchorBatch> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        let items = std::mem::take(&mut self.queue);
+        let leaves: Vec<String> = items.iter().map(AnchorItem::leaf_hash).collect();
+        let batch_idx = self.batches.len();
+        for (leaf_idx, item) in items.iter().enumerate() {
+            self.index.insert((item.kind, item.ref_id), (batch_idx, leaf_idx));
+        }
+        self.batches.push(AnchorBatch {
+            id: Uuid::new_v4(),
+            seq: batch_idx as u64 + 1,
+            root: merkle_root(&leaves),
+            items,
+            sealed_at: now,
+            anchor: None,
+        });
+        self.batches.last()
+    }
+
+    /// 待上链批次 (id, seq, root)，按序号升序
+    pub fn unanchored(&self) -> Vec<(Uuid, u64, String)> {
+        self.batches
+            .iter()
+            .filter(|b| b.anchor.is_none())
+            .map(|b| (b.id, b.seq, b.root.clone()))
+            .collect()
+    }
+
+    pub fn mark_anchored(&mut self, batch_id: Uuid, tx: AnchorTx) -> Result<(), AnchorError> {
+        let batch = self.batches.iter_mut().find(|b| b.id == batch_id).ok_or(AnchorError::UnknownBatch)?;
+        batch.anchor = Some(tx);
+        Ok(())
+    }
+
+    /// 条目的包含证明；批次尚未上链时 anchor 为 None
+    pub fn proof(&self, kind: AnchorKind, ref_id: Uuid) -> Result<InclusionProof, AnchorError> {
+        let Some(&(batch_idx, leaf_idx)) = self.index.get(&(kind, ref_id)) else {
+            return Err(if self.queue.iter().any(|i| i.kind == kind && i.ref_id == ref_id) {
+                AnchorError::Queued
+            } else {
+                AnchorError::NotFound
+            });
+        };
+        let batch = &self.batches[batch_idx];
+        let leaves: Vec<String> = batch.items.iter().map(AnchorItem::leaf_hash).collect();
+        Ok(InclusionProof {
+            item: batch.items[leaf_idx].clone(),
+            leaf_hash: leaves[leaf_idx].clone(),
+            leaf_index: leaf_idx,
+            leaf_count: leaves.len(),
+            path: merkle_path(&leaves, leaf_idx),
+            batch_id: batch.id,
+            batch_seq: batch.seq,
+            root: batch.root.clone(),
+            anchor: batch.anchor.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<String> {
+        (0..n).map(|i| hex::encode(Sha256::digest(format!("leaf-{}", i)))).collect()
+    }
+
+    fn node(left: &str, right: &str) -> String {
+        hex::encode(node_hash(&hex::decode(left).unwrap(), &hex::decode(right).unwrap()))
+    }
+
+    #[test]
+    fn merkle_root_promotes_odd_node_without_duplicating() {
+        let l = leaves(5);
+        assert_eq!(merkle_root(&l[..1]), l[0]);
+        assert_eq!(merkle_root(&l[..2]), node(&l[0], &l[1]));
+        assert_eq!(merkle_root(&l[..3]), node(&node(&l[0], &l[1]), &l[2]));
+        assert_eq!(
+            merkle_root(&l),
+            node(&node(&node(&l[0], &l[1]), &node(&l[2], &l[3])), &l[4])
+        );
+        assert_eq!(merkle_root(&[]), hex::encode(Sha256::digest([])));
+    }
+
+    #[test]
+    fn every_leaf_proves_inclusion() {
+        for n in [1, 2, 3, 5, 9, 17] {
+            let l = leaves(n);
+            let root = merkle_root(&l);
+            for (i, leaf) in l.iter().enumerate() {
+                let path = merkle_path(&l, i);
+                assert!(verify_inclusion(leaf, &path, &root), "n={} i={}", n, i);
+                assert!(verify_inclusion(leaf, &path, &root.to_ascii_uppercase()));
+            }
+            assert!(merkle_path(&l, n).is_empty());
+        }
+        // 单叶无路径，根即叶
+        assert!(merkle_path(&leaves(1), 0).is_empty());
+        // 5 叶时末叶直接上提两层，路径只有一步
+        assert_eq!(merkle_path(&leaves(5), 4).len(), 1);
+    }
+
+    #[test]
+    fn tampered_proof_is_rejected() {
+        let l = leaves(5);
+        let root = merkle_root(&l);
+        let path = merkle_path(&l, 2);
+        assert!(verify_inclusion(&l[2], &path, &root));
+
+        assert!(!verify_inclusion(&l[3], &path, &root));
+        let mut flipped = path.clone();
+        flipped[0].hash = l[4].clone();
+        assert!(!verify_inclusion(&l[2], &flipped, &root));
+        let mut swapped = path.clone();
+        swapped[0].side = ProofSide::Left;
+        assert!(!verify_inclusion(&l[2], &swapped, &root));
+        assert!(!verify_inclusion(&l[2], &path[..1], &root));
+        let mut malformed = path.clone();
+        malformed[1].hash = "zz".into();
+        assert!(!verify_inclusion(&l[2], &malformed, &root));
+        assert!(!verify_inclusion("not-hex", &path, &root));
+    }
+
+    #[test]
+    fn ledger_seals_queue_and_serves_proofs() {
+        let now = Utc::now();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut ledger = AnchorLedger::default();
+        assert!(ledger.seal(now).is_none());
+
+        ledger.enqueue(AnchorKind::Evidence, a, "AA".repeat(32), now);
+        ledger.enqueue(AnchorKind::Evidence, a, "bb".repeat(32), now);
+        ledger.enqueue(AnchorKind::Ruling, a, "cc".repeat(32), now);
+        ledger.enqueue(AnchorKind::Review, b, "dd".repeat(32), now);
+        assert_eq!(ledger.queued().len(), 3);
+        assert_eq!(ledger.queued()[0].hash, "aa".repeat(32));
+        assert_eq!(ledger.proof(AnchorKind::Evidence, a).unwrap_err(), AnchorError::Queued);
+        assert_eq!(ledger.proof(AnchorKind::Evidence, c).unwrap_err(), AnchorError::NotFound);
+
+        let first = ledger.seal(now).unwrap().id;
+        assert!(ledger.queued().is_empty());
+        // 已封批条目不再入队
+        ledger.enqueue(AnchorKind::Evidence, a, "ee".repeat(32), now);
+        assert!(ledger.queued().is_empty());
+        ledger.enqueue(AnchorKind::Evidence, c, "ff".repeat(32), now);
+        let second = ledger.seal(now).unwrap().id;
+        assert_eq!(ledger.batch(second).unwrap().seq, 2);
+        assert_eq!(ledger.unanchored().iter().map(|u| u.1).collect::<Vec<_>>(), vec![1, 2]);
+
+        let proof = ledger.proof(AnchorKind::Ruling, a).unwrap();
+        assert!(proof.verify());
+        assert_eq!((proof.batch_id, proof.leaf_index, proof.leaf_count), (first, 1, 3));
+        assert!(proof.anchor.is_none());
+        let mut tampered = proof.clone();
+        tampered.item.hash = "00".repeat(32);
+        assert!(!tampered.verify());
+
+        let tx = AnchorTx {
+            chain: "local".into(),
+            chain_id: 31337,
+            tx_hash: format!("0x{}", "12".repeat(32)),
+            block_number: 7,
+            anchored_at: now,
+        };
+        ledger.mark_anchored(first, tx.clone()).unwrap();
+        assert_eq!(ledger.mark_anchored(Uuid::new_v4(), tx.clone()), Err(AnchorError::UnknownBatch));
+        assert_eq!(ledger.unanchored().len(), 1);
+        assert_eq!(ledger.proof(AnchorKind::Review, b).unwrap().anchor, Some(tx));
+        assert!(ledger.proof(AnchorKind::Evidence, c).unwrap().verify());
+    }
+}
@@ -0,0 +1,422 @@
+//! 证据访问审计（Runbook §5「访问留痕：谁查看/导出/分享证据 → 不可篡改审计日志，可导出法务」、04 §四「每次读审计」）
+//!
+//! 只追加日志：每条记录含上一条的 hash，自身 hash 覆盖全部字段与 prev_hash，形成哈希链。
+//! `verify_chain` 从创世值逐条重算，任何删除（seq 断号）、插入或改写都会使链断开。
+//! 尾部截断需对照外部留存的链头 hash（export 附带 head_hash，后续可上链锚定）。
+//! 账号安全事件（注册、登录、登出与会话吊销、刷新令牌重用、密码与邮箱变更、角色变更，TOTP 启用、停用、校验与 step-up 拒绝等）另记一条同构的哈希链（SecurityLog），与证据访问日志分开导出。
+
+use crate::UserRole;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 创世记录的 prev_hash
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessAction {
+    /// 查看元数据或内容
+    View,
+    /// 下载原文件
+    Download,
+    /// 导出（法务、司法协助）
+    Export,
+    /// 签发下载链接（链接可离开平台传递，故单独留痕；取用时另记 Download）
+    Share,
+}
+
+impl AccessAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessAction::View => "view",
+            AccessAction::Download => "download",
+            AccessAction::Export => "export",
+            AccessAction::Share => "share",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessLogEntry {
+    /// 从 0 连续递增
+    pub seq: u64,
+    pub evidence_id: Uuid,
+    pub actor_id: Uuid,
+    pub role: UserRole,
+    pub action: AccessAction,
+    /// 请求的 x-request-id（01 §9 traceId）
+    pub request_id: String,
+    pub at: DateTime<Utc>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AccessLogEntry {
+    /// 按字段重算本条 hash：sha256(逐行 `字段=值`)，时间固定为 RFC 3339 纳秒 UTC
+    pub fn compute_hash(&self) -> String {
+        let line = format!(
+            "seq={}\nevidence_id={}\nactor_id={}\nrole={}\naction={}\nrequest_id={}\nat={}\nprev_hash={}",
+            self.seq,
+            self.evidence_id,
+            self.actor_id,
+            self.role.as_str(),
+            self.action.as_str(),
+            self.request_id,
+            self.at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.prev_hash,
+        );
+        hex::encode(Sha256::digest(line.as_bytes()))
+    }
+}
+
+/// 哈希链记录：seq、prev_hash、hash 与按字段重算的 hash
+pub trait ChainEntry {
+    fn seq(&self) -> u64;
+    fn prev_hash(&self) -> &str;
+    fn hash(&self) -> &str;
+    fn compute_hash(&self) -> String;
+}
+
+impl ChainEntry for AccessLogEntry {
+    fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    fn prev_hash(&self) -> &str {
+        &self.prev_hash
+    }
+
+    fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    fn compute_hash(&self) -> String {
+        AccessLogEntry::compute_hash(self)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AuditChainError {
+    #[error("seq 断号：期望 {expected}，实际 {found}")]
+    SequenceGap { expected: u64, found: u64 },
+    #[error("第 {seq} 条 prev_hash 与上一条 hash 不一致")]
+    BrokenLink { seq: u64 },
+    #[error("第 {seq} 条内容与 hash 不一致（记录被改写）")]
+    HashMismatch { seq: u64 },
+}
+
+/// 证据访问日志：只提供追加与只读访问
+#[derive(Debug, Clone, Default)]
+pub struct AccessLog {
+    entries: Vec<AccessLogEntry>,
+}
+
+impl AccessLog {
+    pub fn append(
+        &mut self,
+        evidence_id: Uuid,
+        actor_id: Uuid,
+        role: UserRole,
+        action: AccessAction,
+        request_id: String,
+        at: DateTime<Utc>,
+    ) -> &AccessLogEntry {
+        let mut entry = AccessLogEntry {
+            seq: self.entries.len() as u64,
+            evidence_id,
+            actor_id,
+            role,
+            action,
+            request_id,
+            at,
+            prev_hash: self.head_hash().to_string(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.entries.push(entry);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    pub fn entries(&self) -> &[AccessLogEntry] {
+        &self.entries
+    }
+
+    /// 链头 hash；空日志为创世值
+    pub fn head_hash(&self) -> &str {
+        self.entries.last().map(|e| e.hash.as_str()).unwrap_or(GENESIS_HASH)
+    }
+
+    pub fn verify(&self) -> Result<(), AuditChainError> {
+        verify_chain(&self.entries)
+    }
+}
+
+/// 从创世值逐条校验 seq 连续、prev_hash 衔接与 hash 完整
+pub fn verify_chain<E: ChainEntry>(entries: &[E]) -> Result<(), AuditChainError> {
+    let mut prev = GENESIS_HASH;
+    for (i, entry) in entries.iter().enumerate() {
+        let expected = i as u64;
+        if entry.seq() != expected {
+            return Err(AuditChainError::SequenceGap {
+                expected,
+                found: entry.seq(),
+            });
+        }
+        if entry.prev_hash() != prev {
+            return Err(AuditChainError::BrokenLink { seq: entry.seq() });
+        }
+        if entry.compute_hash() != entry.hash() {
+            return Err(AuditChainError::HashMismatch { seq: entry.seq() });
+        }
+        prev = entry.hash();
+    }
+    Ok(())
+}
+
+/// 账号安全事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEvent {
+    /// 邮箱注册
+    Registered,
+    /// 登录成功（邮箱密码或 SIWE），新建会话
+    LoggedIn,
+    /// 刷新令牌重复使用，所在会话已吊销
+    RefreshTokenReused,
+    /// 登出当前或全部会话
+    LoggedOut,
+    /// 经会话管理吊销一个或全部会话
+    SessionRevoked,
+    EmailVerified,
+    /// 钱包账号关联邮箱与密码
+    EmailLinked,
+    EmailChanged,
+    PasswordResetRequested,
+    /// 经重置令牌设置新密码
+    PasswordReset,
+    /// 已登录改密
+    PasswordChanged,
+    /// 运营指派或初始运营邮箱验证后授予角色
+    RoleChanged,
+    /// 发起 TOTP 启用（生成密钥）
+    TotpEnrollmentStarted,
+    /// 以验证码确认启用
+    TotpEnabled,
+    TotpDisabled,
+    /// 重新生成恢复码
+    RecoveryCodesRegenerated,
+    /// 登录时第二因素校验通过
+    LoginVerified,
+    /// step-up 校验通过
+    StepUpVerified,
+    /// 第二因素校验失败（含锁定）
+    VerificationFailed,
+    /// 高风险操作因缺少 step-up 被拒绝
+    StepUpDenied,
+}
+
+impl SecurityEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEvent::Registered => "registered",
+            SecurityEvent::LoggedIn => "logged_in",
+            SecurityEvent::RefreshTokenReused => "refresh_token_reused",
+            SecurityEvent::LoggedOut => "logged_out",
+            SecurityEvent::SessionRevoked => "session_revoked",
+            SecurityEvent::EmailVerified => "email_verified",
+            SecurityEvent::EmailLinked => "email_linked",
+            SecurityEvent::EmailChanged => "email_changed",
+            SecurityEvent::PasswordResetRequested => "password_reset_requested",
+            SecurityEvent::PasswordReset => "password_reset",
+            SecurityEvent::PasswordChanged => "password_changed",
+            SecurityEvent::RoleChanged => "role_changed",
+            SecurityEvent::TotpEnrollmentStarted => "totp_enrollment_started",
+            SecurityEvent::TotpEnabled => "totp_enabled",
+            SecurityEvent::TotpDisabled => "totp_disabled",
+            SecurityEvent::RecoveryCodesRegenerated => "recovery_codes_regenerated",
+            SecurityEvent::LoginVerified => "login_verified",
+            SecurityEvent::StepUpVerified => "step_up_verified",
+            SecurityEvent::VerificationFailed => "verification_failed",
+            SecurityEvent::StepUpDenied => "step_up_denied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityLogEntry {
+    /// 从 0 连续递增
+    pub seq: u64,
+    pub actor_id: Uuid,
+    pub role: UserRole,
+    pub event: SecurityEvent,
+    /// 补充信息（key=value，空格分隔），如 method=totp、route=POST /api/v1/disputes/:id/resolve
+    pub detail: String,
+    /// 请求的 x-request-id（01 §9 traceId）
+    pub request_id: String,
+    pub at: DateTime<Utc>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl SecurityLogEntry {
+    /// 同 AccessLogEntry::compute_hash
+    pub fn compute_hash(&self) -> String {
+        let line = format!(
+            "seq={}\nactor_id={}\nrole={}\nevent={}\ndetail={}\nrequest_id={}\nat={}\nprev_hash={}",
+            self.seq,
+            self.actor_id,
+            self.role.as_str(),
+            self.event.as_str(),
+            self.detail,
+            self.request_id,
+            self.at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.prev_hash,
+        );
+        hex::encode(Sha256::digest(line.as_bytes()))
+    }
+}
+
+impl ChainEntry for SecurityLogEntry {
+    fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    fn prev_hash(&self) -> &str {
+        &self.prev_hash
+    }
+
+    fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    fn compute_hash(&self) -> String {
+        SecurityLogEntry::compute_hash(self)
+    }
+}
+
+/// 账号安全事件日志：只提供追加与只读访问
+#[derive(Debug, Clone, Default)]
+pub struct SecurityLog {
+    entries: Vec<SecurityLogEntry>,
+}
+
+impl SecurityLog {
+    pub fn append(
+        &mut self,
+        actor_id: Uuid,
+        role: UserRole,
+        event: SecurityEvent,
+        detail: String,
+        request_id: String,
+        at: DateTime<Utc>,
+    ) -> &SecurityLogEntry {
+        let mut entry = SecurityLogEntry {
+            seq: self.entries.len() as u64,
+            actor_id,
+            role,
+            event,
+            detail,
+            request_id,
+            at,
+            prev_hash: self.head_hash().to_string(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.entries.push(entry);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    pub fn entries(&self) -> &[SecurityLogEntry] {
+        &self.entries
+    }
+
+    /// 链头 hash；空日志为创世值
+    pub fn head_hash(&self) -> &str {
+        self.entries.last().map(|e| e.hash.as_str()).unwrap_or(GENESIS_HASH)
+    }
+
+    pub fn verify(&self) -> Result<(), AuditChainError> {
+        verify_chain(&self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn access_log(n: usize) -> AccessLog {
+        let mut log = AccessLog::default();
+        let start = Utc::now();
+        for i in 0..n {
+            log.append(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                UserRole::Arbitrator,
+                AccessAction::View,
+                format!("req-{}", i),
+                start + Duration::seconds(i as i64),
+            );
+        }
+        log
+    }
+
+    #[test]
+    fn appended_chain_verifies() {
+        assert_eq!(AccessLog::default().head_hash(), GENESIS_HASH);
+        let log = access_log(4);
+        assert_eq!(log.verify(), Ok(()));
+        assert_eq!(log.entries()[0].prev_hash, GENESIS_HASH);
+        assert_eq!(log.head_hash(), log.entries()[3].hash);
+    }
+
+    #[test]
+    fn rewritten_entry_is_detected() {
+        let mut entries = access_log(4).entries().to_vec();
+        entries[1].action = AccessAction::Download;
+        assert_eq!(verify_chain(&entries), Err(AuditChainError::HashMismatch { seq: 1 }));
+
+        // 改写后重算本条 hash 仍会与下一条的 prev_hash 断开
+        entries[1].hash = entries[1].compute_hash();
+        assert_eq!(verify_chain(&entries), Err(AuditChainError::BrokenLink { seq: 2 }));
+    }
+
+    #[test]
+    fn deleted_or_inserted_entry_is_detected() {
+        let entries = access_log(4).entries().to_vec();
+        let mut deleted = entries.clone();
+        deleted.remove(2);
+        assert_eq!(
+            verify_chain(&deleted),
+            Err(AuditChainError::SequenceGap { expected: 2, found: 3 })
+        );
+
+        // 插入一条自洽的记录并顺延 seq：prev_hash 衔接不上
+        let mut inserted = entries.clone();
+        let mut forged = entries[1].clone();
+        forged.seq = 2;
+        forged.prev_hash = entries[0].hash.clone();
+        forged.hash = forged.compute_hash();
+        inserted.insert(2, forged);
+        for (i, e) in inserted.iter_mut().enumerate().skip(3) {
+            e.seq = i as u64;
+        }
+        assert_eq!(verify_chain(&inserted), Err(AuditChainError::BrokenLink { seq: 2 }));
+    }
+
+    #[test]
+    fn security_log_chain_detects_tampering() {
+        let mut log = SecurityLog::default();
+        let actor = Uuid::new_v4();
+        for event in [SecurityEvent::TotpEnabled, SecurityEvent::StepUpVerified, SecurityEvent::StepUpDenied] {
+            log.append(actor, UserRole::Operator, event, "method=totp".into(), "req".into(), Utc::now());
+        }
+        assert_eq!(log.verify(), Ok(()));
+        let mut entries = log.entries().to_vec();
+        entries[2].detail = "method=recovery_code".into();
+        assert_eq!(verify_chain(&entries), Err(AuditChainError::HashMismatch { seq: 2 }));
+    }
+}
@@ -0,0 +1,592 @@
+//! 账号凭证规则（04 §二 2.1「邮箱 + 密码，密码强度校验」、01 §2 用户与账号）：邮箱规范化、密码强度与刷新令牌轮换
+//!
+//! 刷新令牌按会话（family）轮换：每次刷新作废旧令牌、签发同 family 新令牌；已轮换的旧令牌再次出现即视为泄露，
+//! 整个 family 吊销（该会话所有令牌失效，需重新登录）。库内只存令牌 sha256，不存明文。
+//! 访问令牌（JWT）携带 family 作为会话 id，登出或吊销后即时失效。
+//! 会话记录登录设备（标签、IP、User-Agent）与最近活跃时间，供用户查看与逐个吊销（01 §2 账号设置）。
+//! 邮箱验证与密码重置令牌（04 §三 3.1）：一次性、限时，同样只存 sha256；同一用户同一用途签发新令牌时旧令牌作废。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const PASSWORD_MIN_LEN: usize = 10;
+pub const PASSWORD_MAX_LEN: usize = 128;
+/// 小写、大写、数字、符号四类中至少包含的类数
+pub const PASSWORD_MIN_CLASSES: usize = 3;
+
+/// 会话最近活跃时间的更新间隔（避免每个请求都写存储）
+pub const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+
+/// 邮箱验证令牌有效期
+pub const VERIFY_EMAIL_TTL_HOURS: i64 = 24;
+/// 密码重置令牌有效期
+pub const RESET_PASSWORD_TTL_MINUTES: i64 = 30;
+
+/// 常见弱口令（小写比较，含即拒绝）
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "passw0rd", "123456", "12345678", "qwerty", "abc123", "111111", "iloveyou", "admin", "welcome",
+    "letmein", "traveltrust",
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CredentialError {
+    #[error("邮箱格式无效")]
+    InvalidEmail,
+    #[error("密码长度须在 {PASSWORD_MIN_LEN}～{PASSWORD_MAX_LEN} 个字符之间")]
+    PasswordLength,
+    #[error("密码须至少包含小写、大写、数字、符号中的 {PASSWORD_MIN_CLASSES} 类")]
+    PasswordTooSimple,
+    #[error("密码不得包含常见弱口令或邮箱用户名")]
+    PasswordGuessable,
+}
+
+/// 邮箱规范化（去空白、小写）并做基本格式校验
+pub fn normalize_email(email: &str) -> Result<String, CredentialError> {
+    let email = email.trim().to_ascii_lowercase();
+    let valid = email.len() <= 254
+        && email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+                && !domain.contains('@')
+        });
+    if valid {
+        Ok(email)
+    } else {
+        Err(CredentialError::InvalidEmail)
+    }
+}
+
+/// 密码强度：长度、字符类别、弱口令与邮箱用户名
+pub fn validate_password(password: &str, email: &str) -> Result<(), CredentialError> {
+    let len = password.chars().count();
+    if !(PASSWORD_MIN_LEN..=PASSWORD_MAX_LEN).contains(&len) {
+        return Err(CredentialError::PasswordLength);
+    }
+    let classes = [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .iter()
+    .filter(|&&present| present)
+    .count();
+    if classes < PASSWORD_MIN_CLASSES {
+        return Err(CredentialError::PasswordTooSimple);
+    }
+    let lower = password.to_lowercase();
+    let local = email.split('@').next().unwrap_or_default().to_lowercase();
+    if COMMON_PASSWORDS.iter().any(|p| lower.contains(p)) || (local.len() >= 3 && lower.contains(&local)) {
+        return Err(CredentialError::PasswordGuessable);
+    }
+    Ok(())
+}
+
+/// 刷新令牌记录（只存 sha256）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshToken {
+    pub token_hash: String,
+    /// 会话 id：同一次登录的轮换链共享
+    pub family_id: Uuid,
+    pub user_id: Uuid,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 已被轮换（再次使用即为重放）
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RefreshError {
+    #[error("刷新令牌无效")]
+    Unknown,
+    #[error("刷新令牌已过期")]
+    Expired,
+    #[error("会话已吊销")]
+    Revoked,
+    #[error("刷新令牌重复使用，会话已吊销")]
+    Reused { family_id: Uuid, user_id: Uuid },
+}
+
+/// 登录设备（连接信息与客户端自报，仅供展示与风控参考）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionDevice {
+    /// 设备标签：客户端自报，未报时由 User-Agent 推断
+    pub label: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// 由 User-Agent 推断「浏览器 · 系统」标签
+pub fn describe_user_agent(user_agent: &str) -> String {
+    let ua = user_agent.to_ascii_lowercase();
+    let client = [
+        ("edg/", "Edge"),
+        ("opr/", "Opera"),
+        ("chrome/", "Chrome"),
+        ("firefox/", "Firefox"),
+        ("safari/", "Safari"),
+        ("curl/", "curl"),
+        ("okhttp", "Android App"),
+        ("cfnetwork", "iOS App"),
+    ]
+    .iter()
+    .find(|(needle, _)| ua.contains(needle))
+    .map_or("未知客户端", |(_, name)| name);
+    let os = [
+        ("iphone", "iOS"),
+        ("ipad", "iPadOS"),
+        ("android", "Android"),
+        ("windows", "Windows"),
+        ("mac os", "macOS"),
+        ("cros", "ChromeOS"),
+        ("linux", "Linux"),
+    ]
+    .iter()
+    .find(|(needle, _)| ua.contains(needle))
+    .map(|(_, name)| *name);
+    match os {
+        Some(os) => format!("{} · {}", client, os),
+        None => client.to_string(),
+    }
+}
+
+/// 会话（family）状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionFamily {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// 登录时的设备
+    pub device: SessionDevice,
+    /// 最近一次使用（刷新或携带访问令牌请求，精度 SESSION_TOUCH_INTERVAL_SECS）
+    pub last_seen_at: DateTime<Utc>,
+    pub last_seen_ip: Option<String>,
+    /// 最新刷新令牌的过期时间；过期后会话不再列出
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// 吊销原因：logout / reuse_detected / password_changed 等
+    pub revoked_reason: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct RefreshRegistry {
+    tokens: HashMap<String, RefreshToken>,
+    families: HashMap<Uuid, SessionFamily>,
+}
+
+impl RefreshRegistry {
+    /// 新会话的首个令牌
+    pub fn start_family(
+        &mut self,
+        user_id: Uuid,
+        token_hash: String,
+        device: SessionDevice,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Uuid {
+        let family_id = Uuid::new_v4();
+        self.families.insert(
+            family_id,
+            SessionFamily {
+                id: family_id,
+                user_id,
+                created_at: now,
+                last_seen_ip: device.ip.clone(),
+                device,
+                last_seen_at: now,
+                expires_at,
+                revoked_at: None,
+                revoked_reason: None,
+            },
+        );
+        self.insert(family_id, user_id, token_hash, now, expires_at);
+        family_id
+    }
+
+    fn insert(&mut self, family_id: Uuid, user_id: Uuid, token_hash: String, now: DateTime<Utc>, expires_at: DateTime<Utc>) {
+        self.tokens.insert(
+            token_hash.clone(),
+            RefreshToken {
+                token_hash,
+                family_id,
+                user_id,
+                issued_at: now,
+                expires_at,
+                rotated_at: None,
+            },
+        );
+    }
+
+    /// 轮换：校验旧令牌并作废，登记同 family 的新令牌；旧令牌已轮换过则吊销整个 family
+    pub fn rotate(
+        &mut self,
+        old_hash: &str,
+        new_hash: String,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<RefreshToken, RefreshError> {
+        let token = self.tokens.get(old_hash).cloned().ok_or(RefreshError::Unknown)?;
+        if !self.is_active(token.family_id) {
+            return Err(RefreshError::Revoked);
+        }
+        if token.rotated_at.is_some() {
+            self.revoke_family(token.family_id, "reuse_detected", now);
+            return Err(RefreshError::Reused {
+                family_id: token.family_id,
+                user_id: token.user_id,
+            });
+        }
+        if now >= token.expires_at {
+            return Err(RefreshError::Expired);
+        }
+        if let Some(t) = self.tokens.get_mut(old_hash) {
+            t.rotated_at = Some(now);
+        }
+        if let Some(f) = self.families.get_mut(&token.family_id) {
+            f.expires_at = expires_at;
+        }
+        self.insert(token.family_id, token.user_id, new_hash, now, expires_at);
+        Ok(token)
+    }
+
+    /// 记录会话活跃（时间与 IP）
+    pub fn touch(&mut self, family_id: Uuid, ip: Option<String>, now: DateTime<Utc>) {
+        if let Some(f) = self.families.get_mut(&family_id) {
+            f.last_seen_at = now;
+            if ip.is_some() {
+                f.last_seen_ip = ip;
+            }
+        }
+    }
+
+    pub fn session(&self, family_id: Uuid) -> Option<&SessionFamily> {
+        self.families.get(&family_id)
+    }
+
+    /// 用户未吊销、未过期的会话，最近活跃在前
+    pub fn active_sessions(&self, user_id: Uuid, now: DateTime<Utc>) -> Vec<SessionFamily> {
+        let mut sessions: Vec<SessionFamily> = self
+            .families
+            .values()
+            .filter(|f| f.user_id == user_id && f.revoked_at.is_none() && now < f.expires_at)
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_seen_at));
+        sessions
+    }
+
+    /// 会话是否有效（存在且未吊销）
+    pub fn is_active(&self, family_id: Uuid) -> bool {
+        self.families.get(&family_id).is_some_and(|f| f.revoked_at.is_none())
+    }
+
+    pub fn family_of(&self, token_hash: &str) -> Option<Uuid> {
+        self.tokens.get(token_hash).map(|t| t.family_id)
+    }
+
+    pub fn revoke_family(&mut self, family_id: Uuid, reason: &str, now: DateTime<Utc>) -> bool {
+        match self.families.get_mut(&family_id) {
+            Some(f) if f.revoked_at.is_none() => {
+                f.revoked_at = Some(now);
+                f.revoked_reason = Some(reason.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 吊销用户全部会话，返回吊销数
+    pub fn revoke_user(&mut self, user_id: Uuid, reason: &str, now: DateTime<Utc>) -> usize {
+        self.revoke_user_except(user_id, None, reason, now)
+    }
+
+    /// 吊销用户除 keep 外的全部会话，返回吊销数
+    pub fn revoke_user_except(&mut self, user_id: Uuid, keep: Option<Uuid>, reason: &str, now: DateTime<Utc>) -> usize {
+        let ids: Vec<Uuid> = self
+            .families
+            .values()
+            .filter(|f| f.user_id == user_id && f.revoked_at.is_none() && Some(f.id) != keep)
+            .map(|f| f.id)
+            .collect();
+        ids.iter().filter(|id| self.revoke_family(**id, reason, now)).count()
+    }
+
+    /// 清理已过期或所属会话已吊销的令牌记录（会话记录保留）
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let families = &self.families;
+        self.tokens
+            .retain(|_, t| now < t.expires_at && families.get(&t.family_id).is_some_and(|f| f.revoked_at.is_none()));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OneTimePurpose {
+    VerifyEmail,
+    ResetPassword,
+}
+
+/// 一次性令牌记录（只存 sha256）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneTimeToken {
+    pub token_hash: String,
+    pub purpose: OneTimePurpose,
+    pub user_id: Uuid,
+    /// 签发时的邮箱；验证令牌只对该邮箱有效（邮箱变更后旧令牌失效）
+    pub email: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OneTimeTokenError {
+    #[error("令牌无效")]
+    Unknown,
+    #[error("令牌已过期")]
+    Expired,
+    #[error("令牌已使用或已作废")]
+    Used,
+}
+
+#[derive(Debug, Default)]
+pub struct OneTimeTokenRegistry {
+    tokens: HashMap<String, OneTimeToken>,
+}
+
+impl OneTimeTokenRegistry {
+    /// 签发；同一用户同一用途的未用令牌一并作废
+    pub fn issue(
+        &mut self,
+        purpose: OneTimePurpose,
+        user_id: Uuid,
+        email: &str,
+        token_hash: String,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) {
+        self.invalidate(user_id, purpose, now);
+        self.tokens.insert(
+            token_hash.clone(),
+            OneTimeToken {
+                token_hash,
+                purpose,
+                user_id,
+                email: email.to_string(),
+                issued_at: now,
+                expires_at,
+                used_at: None,
+            },
+        );
+    }
+
+    /// 核销：用途须一致、未用、未过期；成功后标记已用
+    pub fn consume(
+        &mut self,
+        token_hash: &str,
+        purpose: OneTimePurpose,
+        now: DateTime<Utc>,
+    ) -> Result<OneTimeToken, OneTimeTokenError> {
+        let token = self
+            .tokens
+            .get_mut(token_hash)
+            .filter(|t| t.purpose == purpose)
+            .ok_or(OneTimeTokenError::Unknown)?;
+        if token.used_at.is_some() {
+            return Err(OneTimeTokenError::Used);
+        }
+        if now >= token.expires_at {
+            return Err(OneTimeTokenError::Expired);
+        }
+        token.used_at = Some(now);
+        Ok(token.clone())
+    }
+
+    /// 不核销地查看有效令牌所属用户（核销前的前置校验）
+    pub fn peek_user(&self, token_hash: &str, purpose: OneTimePurpose) -> Option<Uuid> {
+        self.tokens
+            .get(token_hash)
+            .filter(|t| t.purpose == purpose && t.used_at.is_none())
+            .map(|t| t.user_id)
+    }
+
+    /// 作废用户某用途的全部未用令牌
+    pub fn invalidate(&mut self, user_id: Uuid, purpose: OneTimePurpose, now: DateTime<Utc>) {
+        for t in self.tokens.values_mut() {
+            if t.user_id == user_id && t.purpose == purpose && t.used_at.is_none() {
+                t.used_at = Some(now);
+            }
+        }
+    }
+
+    /// 清理已过期或已用的记录
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.tokens.retain(|_, t| t.used_at.is_none() && now < t.expires_at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const EMAIL: &str = "alice.walker@example.com";
+
+    fn start(registry: &mut RefreshRegistry, user_id: Uuid, token: &str, now: DateTime<Utc>) -> Uuid {
+        registry.start_family(user_id, token.into(), SessionDevice::default(), now, now + Duration::days(30))
+    }
+
+    #[test]
+    fn email_is_normalized_and_checked() {
+        assert_eq!(normalize_email("  Alice@Example.COM ").as_deref(), Ok("alice@example.com"));
+        for bad in ["alice", "@example.com", "alice@example", "alice@.example.com", "alice@example.com.", "a b@example.com", "a@b@example.com"] {
+            assert_eq!(normalize_email(bad), Err(CredentialError::InvalidEmail), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn password_length_bounds() {
+        assert_eq!(validate_password("Ab1!efgh", EMAIL), Err(CredentialError::PasswordLength));
+        assert_eq!(validate_password("Ab1!efghij", EMAIL), Ok(()));
+        let long = format!("Ab1!{}", "x".repeat(PASSWORD_MAX_LEN - 4));
+        assert_eq!(validate_password(&long, EMAIL), Ok(()));
+        assert_eq!(validate_password(&format!("{}x", long), EMAIL), Err(CredentialError::PasswordLength));
+        // 按字符计长，不按字节
+        assert_eq!(validate_password("旅行信任Ab1!旅行信", EMAIL), Ok(()));
+    }
+
+    #[test]
+    fn password_needs_three_character_classes() {
+        assert_eq!(validate_password("abcdefghijk", EMAIL), Err(CredentialError::PasswordTooSimple));
+        assert_eq!(validate_password("abcdefGHIJK", EMAIL), Err(CredentialError::PasswordTooSimple));
+        assert_eq!(validate_password("abcdefGHIJ7", EMAIL), Ok(()));
+        assert_eq!(validate_password("abcdef-hij7", EMAIL), Ok(()));
+    }
+
+    #[test]
+    fn password_rejects_common_words_and_email_local_part() {
+        assert_eq!(validate_password("MyPassword#1", EMAIL), Err(CredentialError::PasswordGuessable));
+        assert_eq!(validate_password("Go-TravelTrust9", EMAIL), Err(CredentialError::PasswordGuessable));
+        assert_eq!(validate_password("Alice.Walker#9", EMAIL), Err(CredentialError::PasswordGuessable));
+        // 过短的邮箱用户名不参与比较
+        assert_eq!(validate_password("Blue-Horse-7x", "bo@example.com"), Ok(()));
+    }
+
+    #[test]
+    fn rotate_replaces_token_and_detects_reuse() {
+        let now = Utc::now();
+        let user = Uuid::new_v4();
+        let mut registry = RefreshRegistry::default();
+        let family = start(&mut registry, user, "t1", now);
+
+        let old = registry.rotate("t1", "t2".into(), now, now + Duration::days(30)).unwrap();
+        assert_eq!((old.family_id, old.user_id), (family, user));
+        assert_eq!(registry.family_of("t2"), Some(family));
+        assert!(registry.is_active(family));
+
+        // 已轮换的 t1 再次出现：整个会话吊销，新令牌随之失效
+        assert_eq!(
+            registry.rotate("t1", "t3".into(), now, now + Duration::days(30)).unwrap_err(),
+            RefreshError::Reused { family_id: family, user_id: user }
+        );
+        assert!(!registry.is_active(family));
+        assert_eq!(registry.session(family).unwrap().revoked_reason.as_deref(), Some("reuse_detected"));
+        assert_eq!(registry.rotate("t2", "t4".into(), now, now + Duration::days(30)).unwrap_err(), RefreshError::Revoked);
+        assert_eq!(registry.rotate("nope", "t5".into(), now, now + Duration::days(30)).unwrap_err(), RefreshError::Unknown);
+    }
+
+    #[test]
+    fn rotate_rejects_expired_token() {
+        let now = Utc::now();
+        let mut registry = RefreshRegistry::default();
+        let family = registry.start_family(Uuid::new_v4(), "t1".into(), SessionDevice::default(), now, now + Duration::hours(1));
+        assert_eq!(
+            registry.rotate("t1", "t2".into(), now + Duration::hours(1), now + Duration::days(30)).unwrap_err(),
+            RefreshError::Expired
+        );
+        assert!(registry.is_active(family));
+    }
+
+    #[test]
+    fn revoke_user_except_keeps_one_session_and_skips_others() {
+        let now = Utc::now();
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut registry = RefreshRegistry::default();
+        let keep = start(&mut registry, user, "a", now);
+        let second = start(&mut registry, user, "b", now);
+        let third = start(&mut registry, user, "c", now);
+        let foreign = start(&mut registry, other, "d", now);
+        registry.revoke_family(third, "logout", now);
+
+        assert_eq!(registry.revoke_user_except(user, Some(keep), "logout_everywhere", now), 1);
+        assert!(registry.is_active(keep));
+        assert!(!registry.is_active(second));
+        assert!(registry.is_active(foreign));
+        // 已吊销的会话不重复计数，原因不被覆盖
+        assert_eq!(registry.session(third).unwrap().revoked_reason.as_deref(), Some("logout"));
+        assert_eq!(registry.revoke_user(user, "password_changed", now), 1);
+        assert_eq!(registry.active_sessions(user, now).len(), 0);
+        assert_eq!(registry.active_sessions(other, now).len(), 1);
+    }
+
+    #[test]
+    fn prune_drops_expired_and_revoked_tokens_but_keeps_sessions() {
+        let now = Utc::now();
+        let user = Uuid::new_v4();
+        let mut registry = RefreshRegistry::default();
+        let live = start(&mut registry, user, "live", now);
+        let revoked = start(&mut registry, user, "revoked", now);
+        let expiring = registry.start_family(user, "expiring".into(), SessionDevice::default(), now, now + Duration::minutes(5));
+        registry.revoke_family(revoked, "logout", now);
+
+        registry.prune(now + Duration::minutes(10));
+        assert_eq!(registry.family_of("live"), Some(live));
+        assert_eq!(registry.family_of("revoked"), None);
+        assert_eq!(registry.family_of("expiring"), None);
+        assert!(registry.session(revoked).is_some());
+        assert!(registry.session(expiring).is_some());
+    }
+
+    #[test]
+    fn one_time_token_is_single_use_and_purpose_bound() {
+        let now = Utc::now();
+        let user = Uuid::new_v4();
+        let mut tokens = OneTimeTokenRegistry::default();
+        tokens.issue(OneTimePurpose::ResetPassword, user, EMAIL, "r1".into(), now, now + Duration::minutes(30));
+
+        assert_eq!(tokens.consume("r1", OneTimePurpose::VerifyEmail, now).unwrap_err(), OneTimeTokenError::Unknown);
+        assert_eq!(tokens.peek_user("r1", OneTimePurpose::ResetPassword), Some(user));
+        let token = tokens.consume("r1", OneTimePurpose::ResetPassword, now).unwrap();
+        assert_eq!((token.user_id, token.email.as_str()), (user, EMAIL));
+        assert_eq!(tokens.consume("r1", OneTimePurpose::ResetPassword, now).unwrap_err(), OneTimeTokenError::Used);
+        assert_eq!(tokens.peek_user("r1", OneTimePurpose::ResetPassword), None);
+    }
+
+    #[test]
+    fn one_time_token_expires_and_is_replaced_on_reissue() {
+        let now = Utc::now();
+        let user = Uuid::new_v4();
+        let mut tokens = OneTimeTokenRegistry::default();
+        let expires_at = now + Duration::minutes(RESET_PASSWORD_TTL_MINUTES);
+        tokens.issue(OneTimePurpose::ResetPassword, user, EMAIL, "r1".into(), now, expires_at);
+        assert_eq!(tokens.consume("r1", OneTimePurpose::ResetPassword, expires_at).unwrap_err(), OneTimeTokenError::Expired);
+
+        // 重新申请：旧令牌作废，新令牌可用
+        tokens.issue(OneTimePurpose::ResetPassword, user, EMAIL, "r2".into(), now, expires_at);
+        tokens.issue(OneTimePurpose::ResetPassword, user, EMAIL, "r3".into(), now, expires_at);
+        assert_eq!(tokens.consume("r2", OneTimePurpose::ResetPassword, now).unwrap_err(), OneTimeTokenError::Used);
+        assert!(tokens.consume("r3", OneTimePurpose::ResetPassword, now).is_ok());
+
+        tokens.issue(OneTimePurpose::VerifyEmail, user, EMAIL, "v1".into(), now, now + Duration::hours(VERIFY_EMAIL_TTL_HOURS));
+        tokens.invalidate(user, OneTimePurpose::VerifyEmail, now);
+        assert_eq!(tokens.consume("v1", OneTimePurpose::VerifyEmail, now).unwrap_err(), OneTimeTokenError::Used);
+        tokens.prune(now);
+        assert_eq!(tokens.consume("v1", OneTimePurpose::VerifyEmail, now).unwrap_err(), OneTimeTokenError::Unknown);
+    }
+}
@@ -0,0 +1,65 @@
+//! 证据存储可用性（08-3「证据存储多区域/多云冗余」、01 §6 证据可用性 P0-3）：按后端记录健康与中断时长
+//!
+//! 主存储连续中断达 EVIDENCE_OUTAGE_PAUSE_DAYS 即暂停新争议受理（经 CapacitySnapshot 进入 Paused 模式），恢复后自动解除。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 08-3：主证据存储中断超过 7 天暂停新仲裁受理
+pub const EVIDENCE_OUTAGE_PAUSE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendHealth {
+    pub backend: String,
+    /// 主存储（副本列表第一个）
+    pub primary: bool,
+    /// 本次中断开始时间；健康时为 None
+    pub down_since: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_ok_at: Option<DateTime<Utc>>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+}
+
+impl BackendHealth {
+    pub fn new(backend: impl Into<String>, primary: bool) -> Self {
+        Self {
+            backend: backend.into(),
+            primary,
+            down_since: None,
+            last_error: None,
+            last_ok_at: None,
+            last_checked_at: None,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.down_since.is_none()
+    }
+
+    /// 读写或探测成功：结束中断
+    pub fn record_ok(&mut self, now: DateTime<Utc>) {
+        self.down_since = None;
+        self.last_error = None;
+        self.last_ok_at = Some(now);
+        self.last_checked_at = Some(now);
+    }
+
+    /// 读写或探测失败：首次失败记为中断开始，持续失败不重置
+    pub fn record_failure(&mut self, error: impl Into<String>, now: DateTime<Utc>) {
+        self.down_since.get_or_insert(now);
+        self.last_error = Some(error.into());
+        self.last_checked_at = Some(now);
+    }
+
+    /// 当前中断时长；健康时为 0
+    pub fn outage(&self, now: DateTime<Utc>) -> Duration {
+        self.down_since.map(|since| now - since).unwrap_or_else(Duration::zero)
+    }
+}
+
+/// 主存储中断是否已达暂停阈值
+pub fn primary_outage_exceeded(backends: &[BackendHealth], now: DateTime<Utc>) -> bool {
+    backends
+        .iter()
+        .any(|b| b.primary && b.outage(now) >= Duration::days(EVIDENCE_OUTAGE_PAUSE_DAYS))
+}
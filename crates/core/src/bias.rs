@@ -0,0 +1,458 @@
+//! 裁决偏差监控（08-3「默认裁决连续偏向」、Runbook §8「仲裁员集体偏差」/§11 导游质押挤兑缓解）
+//!
+//! 输入为已裁决的 `Dispute`（按 resolved_at 排序），输出按仲裁员 / 类别（服务类型、城市、国家）/ 全局的偏差指数，
+//! 以及越过阈值时应自动执行的风控动作：强制复核、暂停类别新单、冻结仲裁员。
+//! 运营解除某仲裁员或类别的偏向冻结后，该维度只统计解除之后裁决的记录（BiasBaselines），否则下一单即按历史偏向再次冻结。
+
+use crate::{Dispute, DisputeStatus, FreezeReason, FreezeRegistry, RiskScope};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 裁决方向：refund_ratio > 0.5 偏退款，< 0.5 偏放款，= 0.5 为对半（不计入连续同向）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulingDirection {
+    Refund,
+    Release,
+    Split,
+}
+
+impl RulingDirection {
+    pub fn from_refund_ratio(ratio: f64) -> Self {
+        if ratio > 0.5 {
+            RulingDirection::Refund
+        } else if ratio < 0.5 {
+            RulingDirection::Release
+        } else {
+            RulingDirection::Split
+        }
+    }
+}
+
+/// 单条已裁决记录（Dispute 本身不含类别，由调用方按订单/导游补齐 scopes）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulingRecord {
+    pub dispute_id: Uuid,
+    pub arbitrator_id: Uuid,
+    pub scopes: Vec<RiskScope>,
+    pub direction: RulingDirection,
+    pub resolved_at: DateTime<Utc>,
+}
+
+impl RulingRecord {
+    /// 仅 Resolved 且仲裁员、裁决、裁决时间齐全的争议可计入；否则返回 None
+    pub fn from_dispute(dispute: &Dispute, scopes: Vec<RiskScope>) -> Option<Self> {
+        if dispute.status != DisputeStatus::Resolved {
+            return None;
+        }
+        Some(Self {
+            dispute_id: dispute.id,
+            arbitrator_id: dispute.arbitrator_id?,
+            scopes,
+            direction: RulingDirection::from_refund_ratio(dispute.resolution.as_ref()?.refund_ratio),
+            resolved_at: dispute.resolved_at?,
+        })
+    }
+}
+
+/// 偏差阈值（08-3 扩展 key「裁决偏差指数阈值」；value 由风控定稿写死 Runbook §11）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasThresholds {
+    /// 全平台连续同向裁决单数（08-3 示例 100 单）→ 强制复核
+    pub consecutive_rulings: usize,
+    /// 单仲裁员连续同向裁决单数 → 冻结该仲裁员
+    pub arbitrator_consecutive_rulings: usize,
+    /// 同向占比上限（Runbook §8：80%）→ 类别暂停 / 仲裁员冻结
+    pub lean_ratio: f64,
+    /// 计算占比的最小样本数，样本不足不触发
+    pub min_sample: usize,
+}
+
+impl Default for BiasThresholds {
+    fn default() -> Self {
+        Self {
+            consecutive_rulings: 100,
+            arbitrator_consecutive_rulings: 20,
+            lean_ratio: 0.8,
+            min_sample: 30,
+        }
+    }
+}
+
+/// 偏差统计维度
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum BiasScope {
+    Global,
+    Arbitrator(Uuid),
+    Category(RiskScope),
+}
+
+/// 某维度的偏差指数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasIndex {
+    pub scope: BiasScope,
+    pub total: usize,
+    pub refunds: usize,
+    pub releases: usize,
+    pub splits: usize,
+    /// (refunds - releases) / total，-1.0 全放款 ~ 1.0 全退款
+    pub index: f64,
+    /// max(refunds, releases) / total
+    pub lean: f64,
+    /// 截至最新一单的连续同向单数
+    pub streak: usize,
+    pub streak_direction: Option<RulingDirection>,
+}
+
+/// 越过阈值后的自动风控动作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+pub enum BiasAction {
+    /// 强制二次复核（连续同向的争议列表）
+    ForceSecondReview { dispute_ids: Vec<Uuid> },
+    /// 暂停该类别新单/新争议
+    PauseCategory { scope: RiskScope, lean: f64 },
+    /// 冻结仲裁员（不再分配新案件）
+    FreezeArbitrator { arbitrator_id: Uuid, lean: f64, streak: usize },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasReport {
+    pub indices: Vec<BiasIndex>,
+    pub actions: Vec<BiasAction>,
+}
+
+#[derive(Default)]
+struct Tally {
+    refunds: usize,
+    releases: usize,
+    splits: usize,
+    streak: Vec<Uuid>,
+    streak_direction: Option<RulingDirection>,
+}
+
+impl Tally {
+    fn push(&mut self, record: &RulingRecord) {
+        match record.direction {
+            RulingDirection::Refund => self.refunds += 1,
+            RulingDirection::Release => self.releases += 1,
+            RulingDirection::Split => self.splits += 1,
+        }
+        if record.direction == RulingDirection::Split {
+            self.streak.clear();
+            self.streak_direction = None;
+        } else if self.streak_direction == Some(record.direction) {
+            self.streak.push(record.dispute_id);
+        } else {
+            self.streak = vec![record.dispute_id];
+            self.streak_direction = Some(record.direction);
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.refunds + self.releases + self.splits
+    }
+
+    fn lean(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            n => self.refunds.max(self.releases) as f64 / n as f64,
+        }
+    }
+
+    fn index(&self, scope: BiasScope) -> BiasIndex {
+        let total = self.total();
+        BiasIndex {
+            scope,
+            total,
+            refunds: self.refunds,
+            releases: self.releases,
+            splits: self.splits,
+            index: match total {
+                0 => 0.0,
+                n => (self.refunds as f64 - self.releases as f64) / n as f64,
+            },
+            lean: self.lean(),
+            streak: self.streak.len(),
+            streak_direction: self.streak_direction,
+        }
+    }
+}
+
+/// 强制复核登记：同一争议只登记一次，复核结果由运营线下跟进
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecondReview {
+    pub dispute_id: Uuid,
+    pub flagged_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecondReviewQueue {
+    reviews: Vec<SecondReview>,
+}
+
+impl SecondReviewQueue {
+    /// 登记待复核争议，返回新登记的条数（已在队列中的跳过）
+    pub fn flag(&mut self, dispute_ids: &[Uuid], now: DateTime<Utc>) -> usize {
+        let mut added = 0;
+        for id in dispute_ids {
+            if !self.contains(*id) {
+                self.reviews.push(SecondReview {
+                    dispute_id: *id,
+                    flagged_at: now,
+                });
+                added += 1;
+            }
+        }
+        added
+    }
+
+    pub fn contains(&self, dispute_id: Uuid) -> bool {
+        self.reviews.iter().any(|r| r.dispute_id == dispute_id)
+    }
+
+    pub fn all(&self) -> &[SecondReview] {
+        &self.reviews
+    }
+}
+
+/// 各维度的计数起点：仅统计 resolved_at 晚于起点的记录；无起点的维度统计全量
+#[derive(Debug, Clone, Default)]
+pub struct BiasBaselines {
+    pub arbitrators: HashMap<Uuid, DateTime<Utc>>,
+    pub categories: HashMap<RiskScope, DateTime<Utc>>,
+}
+
+impl BiasBaselines {
+    /// 取各仲裁员、各类别最近一次偏向冻结的解除时间；争议率等其他原因的类别冻结与裁决偏向无关，不影响起点
+    pub fn from_freezes(freezes: &FreezeRegistry) -> Self {
+        let mut baselines = Self::default();
+        for freeze in freezes.arbitrator_freezes() {
+            if let Some(lifted_at) = freeze.lifted_at {
+                let at = baselines.arbitrators.entry(freeze.arbitrator_id).or_insert(lifted_at);
+                *at = (*at).max(lifted_at);
+            }
+        }
+        for freeze in freezes.all() {
+            if let (FreezeReason::RulingBias { .. }, Some(lifted_at)) = (&freeze.reason, freeze.lifted_at) {
+                let at = baselines.categories.entry(freeze.scope.clone()).or_insert(lifted_at);
+                *at = (*at).max(lifted_at);
+            }
+        }
+        baselines
+    }
+
+    fn counts(since: Option<&DateTime<Utc>>, record: &RulingRecord) -> bool {
+        since.is_none_or(|since| record.resolved_at > *since)
+    }
+}
+
+/// 偏差监控：无状态，每次对全量（或滚动窗口内）已裁决记录重新计算
+#[derive(Debug, Clone, Default)]
+pub struct BiasMonitor {
+    pub thresholds: BiasThresholds,
+}
+
+impl BiasMonitor {
+    pub fn new(thresholds: BiasThresholds) -> Self {
+        Self { thresholds }
+    }
+
+    /// records 须按 resolved_at 升序（连续同向按此顺序计）；函数内部会再排序一次以防调用方遗漏。
+    /// 仲裁员与类别维度只计 baselines 起点之后的记录，全局维度（强制复核）始终计全量
+    pub fn evaluate(&self, records: &[RulingRecord], baselines: &BiasBaselines) -> BiasReport {
+        let mut sorted: Vec<&RulingRecord> = records.iter().collect();
+        sorted.sort_by_key(|r| r.resolved_at);
+
+        let mut global = Tally::default();
+        let mut by_arbitrator: HashMap<Uuid, Tally> = HashMap::new();
+        let mut by_category: HashMap<RiskScope, Tally> = HashMap::new();
+        for record in sorted {
+            global.push(record);
+            if BiasBaselines::counts(baselines.arbitrators.get(&record.arbitrator_id), record) {
+                by_arbitrator.entry(record.arbitrator_id).or_default().push(record);
+            }
+            for scope in &record.scopes {
+                if BiasBaselines::counts(baselines.categories.get(scope), record) {
+                    by_category.entry(scope.clone()).or_default().push(record);
+                }
+            }
+        }
+
+        let t = &self.thresholds;
+        let mut actions = Vec::new();
+        if global.streak.len() >= t.consecutive_rulings {
+            actions.push(BiasAction::ForceSecondReview {
+                dispute_ids: global.streak.clone(),
+            });
+        }
+        let mut arbitrators: Vec<(&Uuid, &Tally)> = by_arbitrator.iter().collect();
+        arbitrators.sort_by_key(|(id, _)| **id);
+        for (id, tally) in &arbitrators {
+            let leaning = tally.total() >= t.min_sample && tally.lean() >= t.lean_ratio;
+            if leaning || tally.streak.len() >= t.arbitrator_consecutive_rulings {
+                actions.push(BiasAction::FreezeArbitrator {
+                    arbitrator_id: **id,
+                    lean: tally.lean(),
+                    streak: tally.streak.len(),
+                });
+            }
+        }
+        let mut categories: Vec<(&RiskScope, &Tally)> = by_category.iter().collect();
+        categories.sort_by_key(|(scope, _)| *scope);
+        for (scope, tally) in &categories {
+            if tally.total() >= t.min_sample && tally.lean() >= t.lean_ratio {
+                actions.push(BiasAction::PauseCategory {
+                    scope: (*scope).clone(),
+                    lean: tally.lean(),
+                });
+            }
+        }
+
+        let mut indices = vec![global.index(BiasScope::Global)];
+        indices.extend(arbitrators.iter().map(|(id, tally)| tally.index(BiasScope::Arbitrator(**id))));
+        indices.extend(categories.iter().map(|(scope, tally)| tally.index(BiasScope::Category((*scope).clone()))));
+        BiasReport { indices, actions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ServiceType;
+    use chrono::Duration;
+
+    fn rulings(
+        arbitrator_id: Uuid,
+        scope: &RiskScope,
+        direction: RulingDirection,
+        count: usize,
+        start: DateTime<Utc>,
+    ) -> Vec<RulingRecord> {
+        (0..count)
+            .map(|i| RulingRecord {
+                dispute_id: Uuid::new_v4(),
+                arbitrator_id,
+                scopes: vec![scope.clone()],
+                direction,
+                resolved_at: start + Duration::minutes(i as i64),
+            })
+            .collect()
+    }
+
+    fn froze_arbitrator(report: &BiasReport, id: Uuid) -> bool {
+        report
+            .actions
+            .iter()
+            .any(|a| matches!(a, BiasAction::FreezeArbitrator { arbitrator_id, .. } if *arbitrator_id == id))
+    }
+
+    fn paused(report: &BiasReport, target: &RiskScope) -> bool {
+        report
+            .actions
+            .iter()
+            .any(|a| matches!(a, BiasAction::PauseCategory { scope, .. } if scope == target))
+    }
+
+    #[test]
+    fn arbitrator_streak_triggers_freeze() {
+        let monitor = BiasMonitor::default();
+        let arbitrator = Uuid::new_v4();
+        let scope = RiskScope::ServiceType(ServiceType::Food);
+        let start = Utc::now();
+        let mut records = rulings(arbitrator, &scope, RulingDirection::Refund, 19, start);
+        let report = monitor.evaluate(&records, &BiasBaselines::default());
+        assert!(!froze_arbitrator(&report, arbitrator));
+
+        records.extend(rulings(arbitrator, &scope, RulingDirection::Refund, 1, start + Duration::hours(1)));
+        let report = monitor.evaluate(&records, &BiasBaselines::default());
+        assert!(froze_arbitrator(&report, arbitrator));
+        // 对半裁决打断连续同向
+        records.extend(rulings(arbitrator, &scope, RulingDirection::Split, 1, start + Duration::hours(2)));
+        let report = monitor.evaluate(&records, &BiasBaselines::default());
+        assert!(!froze_arbitrator(&report, arbitrator));
+    }
+
+    #[test]
+    fn lean_over_min_sample_triggers_category_pause_and_arbitrator_freeze() {
+        let monitor = BiasMonitor::default();
+        let scope = RiskScope::ServiceType(ServiceType::CarTour);
+        let arbitrator = Uuid::new_v4();
+        let start = Utc::now();
+        // 交替穿插打断连续同向，只测占比：24 退款 + 6 放款 = 80%
+        let mut records = Vec::new();
+        for i in 0..30 {
+            let direction = if i % 5 == 4 { RulingDirection::Release } else { RulingDirection::Refund };
+            records.extend(rulings(arbitrator, &scope, direction, 1, start + Duration::minutes(i)));
+        }
+        let report = monitor.evaluate(&records, &BiasBaselines::default());
+        assert!(paused(&report, &scope));
+        assert!(froze_arbitrator(&report, arbitrator));
+
+        // 样本不足 min_sample 不触发
+        let report = monitor.evaluate(&records[..29], &BiasBaselines::default());
+        assert!(!paused(&report, &scope));
+    }
+
+    #[test]
+    fn global_streak_forces_second_review() {
+        let monitor = BiasMonitor::new(BiasThresholds {
+            consecutive_rulings: 5,
+            ..BiasThresholds::default()
+        });
+        let scope = RiskScope::Country("JP".into());
+        let start = Utc::now();
+        let mut records = Vec::new();
+        for i in 0..5 {
+            records.extend(rulings(Uuid::new_v4(), &scope, RulingDirection::Release, 1, start + Duration::minutes(i)));
+        }
+        let report = monitor.evaluate(&records, &BiasBaselines::default());
+        let expected: Vec<Uuid> = records.iter().map(|r| r.dispute_id).collect();
+        assert!(report.actions.contains(&BiasAction::ForceSecondReview { dispute_ids: expected }));
+    }
+
+    #[test]
+    fn lifted_freeze_is_not_reapplied_by_next_ruling() {
+        let monitor = BiasMonitor::default();
+        let arbitrator = Uuid::new_v4();
+        let scope = RiskScope::ServiceType(ServiceType::MultiDay);
+        let start = Utc::now();
+        let mut records = rulings(arbitrator, &scope, RulingDirection::Refund, 30, start);
+        let mut freezes = FreezeRegistry::default();
+        let report = monitor.evaluate(&records, &BiasBaselines::from_freezes(&freezes));
+        assert!(froze_arbitrator(&report, arbitrator));
+        assert!(paused(&report, &scope));
+        let frozen_at = start + Duration::hours(1);
+        let arbitrator_freeze = freezes.freeze_arbitrator(arbitrator, 1.0, 30, frozen_at).id;
+        let category_freeze = freezes.freeze(scope.clone(), FreezeReason::RulingBias { lean: 1.0 }, frozen_at).id;
+
+        // 运营解除后再裁一单同向，不应再次冻结
+        let lifted_at = start + Duration::hours(2);
+        freezes.lift_arbitrator(arbitrator_freeze, "ops", None, lifted_at).unwrap();
+        freezes.lift(category_freeze, "ops", None, lifted_at).unwrap();
+        records.extend(rulings(arbitrator, &scope, RulingDirection::Refund, 1, lifted_at + Duration::minutes(1)));
+        let report = monitor.evaluate(&records, &BiasBaselines::from_freezes(&freezes));
+        assert!(!froze_arbitrator(&report, arbitrator));
+        assert!(!paused(&report, &scope));
+
+        // 解除之后重新累积到阈值仍会冻结
+        records.extend(rulings(arbitrator, &scope, RulingDirection::Refund, 19, lifted_at + Duration::minutes(2)));
+        let report = monitor.evaluate(&records, &BiasBaselines::from_freezes(&freezes));
+        assert!(froze_arbitrator(&report, arbitrator));
+    }
+
+    #[test]
+    fn dispute_rate_lift_does_not_reset_bias_baseline() {
+        let scope = RiskScope::ServiceType(ServiceType::Food);
+        let now = Utc::now();
+        let mut freezes = FreezeRegistry::default();
+        let id = freezes
+            .freeze(scope.clone(), FreezeReason::DisputeRate { rate: 0.3, peer_mean: 0.02, score: 5.0 }, now)
+            .id;
+        freezes.lift(id, "ops", None, now).unwrap();
+        assert!(BiasBaselines::from_freezes(&freezes).categories.is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+//! 争议容量控制（08-3「争议量 10× 触发阈值」、Runbook §11 W-D3-CAPACITY、Runbook §8 minArbitratorCount）
+//!
+//! 以待裁件数对比仲裁员处理能力（积压清空天数）、窗口争议率与证据主存储可用性（availability）判定平台争议模式：
+//! Normal → Extended（SLA 延长 + arbFee 上调，不超过 arbFeeCap）→ Paused（暂停新争议受理）。
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// 08-3 arbFeeBase（USDC）
+pub const ARB_FEE_BASE_USDC: u64 = 50;
+/// 08-3 arbFeeCap（USDC）
+pub const ARB_FEE_CAP_USDC: u64 = 500;
+/// 08-3 minArbitratorCount：低于此人数暂停新争议（Runbook §8）
+pub const MIN_ARBITRATOR_COUNT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeMode {
+    Normal,
+    /// SLA 延长、arbFee 上调
+    Extended,
+    /// 暂停新争议受理；存量按延长 SLA 处理
+    Paused,
+}
+
+/// 容量阈值（写死值由风控/运营定稿 Runbook §11；此处为默认）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityThresholds {
+    /// 单个仲裁员每日可处理件数（仲裁员容量数学模型）
+    pub arbitrator_daily_throughput: f64,
+    /// 积压清空天数超过此值 → Extended
+    pub extended_backlog_days: f64,
+    /// 积压清空天数超过此值 → Paused
+    pub paused_backlog_days: f64,
+    /// 窗口争议率超过此值 → Extended
+    pub extended_dispute_rate: f64,
+    /// 窗口争议率超过此值 → Paused（10× 争议量级，如 10,000 单→800 争议即 8%）
+    pub paused_dispute_rate: f64,
+    /// Normal 模式裁决 SLA（小时）
+    pub base_sla_hours: i64,
+    /// Extended 模式 SLA 倍数
+    pub extended_sla_multiplier: i32,
+    /// Extended 模式 arbFee 倍数（结果不超过 arbFeeCap）
+    pub extended_fee_multiplier: f64,
+}
+
+impl Default for CapacityThresholds {
+    fn default() -> Self {
+        Self {
+            arbitrator_daily_throughput: 10.0,
+            extended_backlog_days: 3.0,
+            paused_backlog_days: 10.0,
+            extended_dispute_rate: 0.04,
+            paused_dispute_rate: 0.08,
+            base_sla_hours: 7 * 24,
+            extended_sla_multiplier: 2,
+            extended_fee_multiplier: 2.0,
+        }
+    }
+}
+
+/// 容量快照：调用方从存储汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacitySnapshot {
+    /// 未裁决争议数（Open + Assigned）
+    pub backlog: usize,
+    /// 在岗仲裁员数
+    pub active_arbitrators: usize,
+    /// 窗口内全平台争议率；窗口订单数不足最小样本时为 None，不参与模式判定
+    pub dispute_rate: Option<f64>,
+    /// 证据主存储中断已达 08-3 暂停阈值（availability::primary_outage_exceeded）
+    #[serde(default)]
+    pub evidence_storage_outage: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityAssessment {
+    pub mode: DisputeMode,
+    /// 按当前吞吐清空积压所需天数；无仲裁员时为 None
+    pub backlog_days: Option<f64>,
+    pub sla_hours: i64,
+    pub arb_fee_usdc: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CapacityController {
+    pub thresholds: CapacityThresholds,
+}
+
+impl CapacityController {
+    pub fn new(thresholds: CapacityThresholds) -> Self {
+        Self { thresholds }
+    }
+
+    pub fn assess(&self, snapshot: &CapacitySnapshot) -> CapacityAssessment {
+        let t = &self.thresholds;
+        let backlog_days = if snapshot.active_arbitrators == 0 {
+            None
+        } else {
+            Some(snapshot.backlog as f64 / (snapshot.active_arbitrators as f64 * t.arbitrator_daily_throughput))
+        };
+        let mode = if snapshot.evidence_storage_outage
+            || snapshot.active_arbitrators < MIN_ARBITRATOR_COUNT
+            || backlog_days.is_none_or(|d| d > t.paused_backlog_days)
+            || snapshot.dispute_rate.is_some_and(|r| r > t.paused_dispute_rate)
+        {
+            DisputeMode::Paused
+        } else if backlog_days.is_some_and(|d| d > t.extended_backlog_days)
+            || snapshot.dispute_rate.is_some_and(|r| r > t.extended_dispute_rate)
+        {
+            DisputeMode::Extended
+        } else {
+            DisputeMode::Normal
+        };
+        CapacityAssessment {
+            mode,
+            backlog_days,
+            sla_hours: self.sla(mode).num_hours(),
+            arb_fee_usdc: self.arb_fee(mode),
+        }
+    }
+
+    /// 模式对应的裁决 SLA；Paused 下存量按延长 SLA 处理（Runbook §8）。
+    /// 新争议按受理时的模式取值；模式切换时由调用方以 SlaClock::extend_target 把未裁决争议调整到新模式的目标
+    pub fn sla(&self, mode: DisputeMode) -> Duration {
+        let base = Duration::hours(self.thresholds.base_sla_hours);
+        match mode {
+            DisputeMode::Normal => base,
+            DisputeMode::Extended | DisputeMode::Paused => base * self.thresholds.extended_sla_multiplier,
+        }
+    }
+
+    /// 模式对应的仲裁费（USDC），不超过 arbFeeCap
+    pub fn arb_fee(&self, mode: DisputeMode) -> u64 {
+        let fee = match mode {
+            DisputeMode::Normal => ARB_FEE_BASE_USDC,
+            DisputeMode::Extended | DisputeMode::Paused => {
+                (ARB_FEE_BASE_USDC as f64 * self.thresholds.extended_fee_multiplier).round() as u64
+            }
+        };
+        fee.min(ARB_FEE_CAP_USDC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dispute, DisputeStatus, SlaClock, SlaPolicy};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn snapshot(backlog: usize, active_arbitrators: usize) -> CapacitySnapshot {
+        CapacitySnapshot {
+            backlog,
+            active_arbitrators,
+            dispute_rate: None,
+            evidence_storage_outage: false,
+        }
+    }
+
+    fn open_clock(controller: &CapacityController, mode: DisputeMode) -> SlaClock {
+        let dispute = Dispute {
+            id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            status: DisputeStatus::Open,
+            evidence_hashes: Vec::new(),
+            arbitrator_id: None,
+            resolution: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+            resolution_timestamp: None,
+        };
+        SlaClock::start(&dispute, SlaPolicy::with_target(controller.sla(mode)))
+    }
+
+    #[test]
+    fn mode_follows_backlog_rate_and_staffing() {
+        let controller = CapacityController::default();
+        // 3 人 × 10 件/日：清空天数 ≤ 3 为 Normal，> 3 Extended，> 10 Paused
+        assert_eq!(controller.assess(&snapshot(90, 3)).mode, DisputeMode::Normal);
+        assert_eq!(controller.assess(&snapshot(91, 3)).mode, DisputeMode::Extended);
+        assert_eq!(controller.assess(&snapshot(301, 3)).mode, DisputeMode::Paused);
+        assert_eq!(controller.assess(&snapshot(0, MIN_ARBITRATOR_COUNT - 1)).mode, DisputeMode::Paused);
+        let mut rate = snapshot(0, 3);
+        rate.dispute_rate = Some(0.05);
+        assert_eq!(controller.assess(&rate).mode, DisputeMode::Extended);
+        rate.evidence_storage_outage = true;
+        assert_eq!(controller.assess(&rate).mode, DisputeMode::Paused);
+    }
+
+    #[test]
+    fn extended_and_paused_double_sla_and_fee() {
+        let controller = CapacityController::default();
+        let normal = controller.assess(&snapshot(0, 3));
+        let extended = controller.assess(&snapshot(91, 3));
+        let paused = controller.assess(&snapshot(301, 3));
+        assert_eq!(normal.sla_hours, 7 * 24);
+        assert_eq!(extended.sla_hours, 14 * 24);
+        assert_eq!(paused.sla_hours, 14 * 24);
+        assert_eq!(normal.arb_fee_usdc, ARB_FEE_BASE_USDC);
+        assert_eq!(extended.arb_fee_usdc, 100);
+
+        let capped = CapacityController::new(CapacityThresholds {
+            extended_fee_multiplier: 20.0,
+            ..CapacityThresholds::default()
+        });
+        assert_eq!(capped.arb_fee(DisputeMode::Extended), ARB_FEE_CAP_USDC);
+    }
+
+    #[test]
+    fn mode_switch_extends_open_clocks_only() {
+        let controller = CapacityController::default();
+        let mut open = open_clock(&controller, DisputeMode::Normal);
+        let mut resolved = open_clock(&controller, DisputeMode::Normal);
+        let start = open.started_at;
+        resolved.enter_stage(DisputeStatus::Resolved, start + Duration::days(1));
+
+        // 切到 Paused：存量改按延长 SLA，超过原 7 天目标不再违约
+        let paused = controller.assess(&snapshot(301, 3)).mode;
+        assert!(open.extend_target(controller.sla(paused)));
+        assert!(!resolved.extend_target(controller.sla(paused)));
+        open.advance(start + Duration::days(8));
+        assert!(!open.is_breached());
+        assert_eq!(open.due_at(), start + Duration::days(14));
+
+        // 回到 Normal 不缩短
+        assert!(!open.extend_target(controller.sla(DisputeMode::Normal)));
+        assert_eq!(open.policy.target(), Duration::days(14));
+        open.advance(start + Duration::days(15));
+        assert!(open.is_breached());
+    }
+}
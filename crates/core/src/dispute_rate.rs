@@ -0,0 +1,259 @@
+//! 滚动争议率（08-3「异常争议率与关联账户防护」、Runbook §11）：按服务类型 / 导游城市 / 导游国家统计窗口内争议率，
+//! 与同维度其他类别的均值比较（z-score 或倍数），越过阈值的类别由调用方登记冻结（见 freeze 模块）。
+
+use crate::RiskScope;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 偏离判定规则
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "rule", content = "value")]
+pub enum RateRule {
+    /// (rate - 同维度其他类别均值) / 其他类别样本标准差 ≥ 值
+    ZScore(f64),
+    /// rate / 同维度其他类别均值 ≥ 值
+    Ratio(f64),
+}
+
+/// 争议率阈值（08-3 扩展 key「某类服务争议率异常阈值」；value 由风控定稿写死 Runbook §11）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeRateThresholds {
+    pub window_days: i64,
+    /// 窗口内订单数不足时不判定，避免小样本误冻结
+    pub min_orders: usize,
+    pub rule: RateRule,
+}
+
+impl Default for DisputeRateThresholds {
+    fn default() -> Self {
+        Self {
+            window_days: 30,
+            min_orders: 50,
+            rule: RateRule::ZScore(3.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopeRate {
+    pub scope: RiskScope,
+    pub orders: usize,
+    pub disputes: usize,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateAnomaly {
+    pub scope: RiskScope,
+    pub rate: f64,
+    pub peer_mean: f64,
+    /// z-score 或倍数，取决于 RateRule
+    pub score: f64,
+}
+
+/// 滚动窗口争议率计算器：记录订单与争议事件（各自带所属类别），窗口外的事件在记录时淘汰
+#[derive(Debug, Clone)]
+pub struct DisputeRateTracker {
+    window: Duration,
+    orders: VecDeque<(DateTime<Utc>, Vec<RiskScope>)>,
+    disputes: VecDeque<(DateTime<Utc>, Vec<RiskScope>)>,
+}
+
+impl DisputeRateTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            orders: VecDeque::new(),
+            disputes: VecDeque::new(),
+        }
+    }
+
+    pub fn record_order(&mut self, at: DateTime<Utc>, scopes: Vec<RiskScope>) {
+        self.orders.push_back((at, scopes));
+        self.prune(at);
+    }
+
+    pub fn record_dispute(&mut self, at: DateTime<Utc>, scopes: Vec<RiskScope>) {
+        self.disputes.push_back((at, scopes));
+        self.prune(at);
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - self.window;
+        while self.orders.front().is_some_and(|(at, _)| *at < cutoff) {
+            self.orders.pop_front();
+        }
+        while self.disputes.front().is_some_and(|(at, _)| *at < cutoff) {
+            self.disputes.pop_front();
+        }
+    }
+
+    /// 窗口 [now - window, now] 内各类别的争议率
+    pub fn rates(&self, now: DateTime<Utc>) -> Vec<ScopeRate> {
+        let cutoff = now - self.window;
+        let mut counts: HashMap<&RiskScope, (usize, usize)> = HashMap::new();
+        for (_, scopes) in self.orders.iter().filter(|(at, _)| *at >= cutoff && *at <= now) {
+            for scope in scopes {
+                counts.entry(scope).or_default().0 += 1;
+            }
+        }
+        for (_, scopes) in self.disputes.iter().filter(|(at, _)| *at >= cutoff && *at <= now) {
+            for scope in scopes {
+                counts.entry(scope).or_default().1 += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(scope, (orders, disputes))| ScopeRate {
+                scope: scope.clone(),
+                orders,
+                disputes,
+                rate: if orders == 0 { 0.0 } else { disputes as f64 / orders as f64 },
+            })
+            .collect()
+    }
+
+    /// 窗口内全平台争议率（容量控制用，见 capacity 模块）；订单数不足 min_orders 时返回 None，避免小样本误切模式
+    pub fn global_rate(&self, now: DateTime<Utc>, min_orders: usize) -> Option<f64> {
+        let cutoff = now - self.window;
+        let in_window = |at: &DateTime<Utc>| *at >= cutoff && *at <= now;
+        let orders = self.orders.iter().filter(|(at, _)| in_window(at)).count();
+        let disputes = self.disputes.iter().filter(|(at, _)| in_window(at)).count();
+        if orders == 0 || orders < min_orders {
+            None
+        } else {
+            Some(disputes as f64 / orders as f64)
+        }
+    }
+
+    /// 越过阈值的类别；只与同维度（服务类型之间、城市之间、国家之间）比较
+    pub fn evaluate(&self, now: DateTime<Utc>, thresholds: &DisputeRateThresholds) -> Vec<RateAnomaly> {
+        let rates: Vec<ScopeRate> = self
+            .rates(now)
+            .into_iter()
+            .filter(|r| r.orders >= thresholds.min_orders)
+            .collect();
+        let mut anomalies = Vec::new();
+        for candidate in &rates {
+            // 留一法：候选类别不计入自身的对照组，否则 z-score 上限为 sqrt(n-1)，类别少的维度（如服务类型）永远无法触发
+            let peers: Vec<f64> = rates
+                .iter()
+                .filter(|r| r.scope != candidate.scope && same_dimension(&r.scope, &candidate.scope))
+                .map(|r| r.rate)
+                .collect();
+            if peers.len() < 2 {
+                continue;
+            }
+            let mean = peers.iter().sum::<f64>() / peers.len() as f64;
+            let score = match thresholds.rule {
+                RateRule::ZScore(_) => {
+                    let variance = peers.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (peers.len() - 1) as f64;
+                    let std_dev = variance.sqrt();
+                    if std_dev == 0.0 {
+                        continue;
+                    }
+                    (candidate.rate - mean) / std_dev
+                }
+                RateRule::Ratio(_) => {
+                    if mean == 0.0 {
+                        continue;
+                    }
+                    candidate.rate / mean
+                }
+            };
+            let limit = match thresholds.rule {
+                RateRule::ZScore(v) | RateRule::Ratio(v) => v,
+            };
+            if score >= limit {
+                anomalies.push(RateAnomaly {
+                    scope: candidate.scope.clone(),
+                    rate: candidate.rate,
+                    peer_mean: mean,
+                    score,
+                });
+            }
+        }
+        anomalies
+    }
+}
+
+fn same_dimension(a: &RiskScope, b: &RiskScope) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FreezeReason, FreezeRegistry, ServiceType};
+
+    fn record(tracker: &mut DisputeRateTracker, at: DateTime<Utc>, service: &ServiceType, orders: usize, disputes: usize) {
+        let scopes = vec![RiskScope::ServiceType(service.clone())];
+        for _ in 0..orders {
+            tracker.record_order(at, scopes.clone());
+        }
+        for _ in 0..disputes {
+            tracker.record_dispute(at, scopes.clone());
+        }
+    }
+
+    #[test]
+    fn anomalous_service_type_is_frozen_with_default_threshold() {
+        let now = Utc::now();
+        let mut tracker = DisputeRateTracker::new(Duration::days(30));
+        let normal = [
+            (ServiceType::WalkingTour, 2),
+            (ServiceType::CarTour, 3),
+            (ServiceType::MultiDay, 2),
+            (ServiceType::Cultural, 4),
+        ];
+        for (service, disputes) in &normal {
+            record(&mut tracker, now, service, 100, *disputes);
+        }
+        record(&mut tracker, now, &ServiceType::Food, 100, 25);
+
+        let thresholds = DisputeRateThresholds::default();
+        let anomalies = tracker.evaluate(now, &thresholds);
+        assert_eq!(anomalies.len(), 1);
+        let anomaly = &anomalies[0];
+        let food = RiskScope::ServiceType(ServiceType::Food);
+        assert_eq!(anomaly.scope, food);
+        assert!((anomaly.peer_mean - 0.0275).abs() < 1e-9);
+        assert!(anomaly.score >= 3.0);
+
+        let mut freezes = FreezeRegistry::default();
+        freezes.freeze(
+            anomaly.scope.clone(),
+            FreezeReason::DisputeRate {
+                rate: anomaly.rate,
+                peer_mean: anomaly.peer_mean,
+                score: anomaly.score,
+            },
+            now,
+        );
+        assert!(freezes.is_frozen(&food));
+        assert!(!freezes.is_frozen(&RiskScope::ServiceType(ServiceType::CarTour)));
+    }
+
+    #[test]
+    fn small_categories_and_uniform_rates_do_not_trip() {
+        let now = Utc::now();
+        let mut tracker = DisputeRateTracker::new(Duration::days(30));
+        for service in [ServiceType::WalkingTour, ServiceType::CarTour, ServiceType::MultiDay] {
+            record(&mut tracker, now, &service, 100, 3);
+        }
+        // 订单数不足 min_orders 的类别不参与判定
+        record(&mut tracker, now, &ServiceType::Food, 10, 5);
+        assert!(tracker.evaluate(now, &DisputeRateThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn global_rate_needs_min_orders() {
+        let now = Utc::now();
+        let mut tracker = DisputeRateTracker::new(Duration::days(30));
+        record(&mut tracker, now, &ServiceType::Food, 10, 1);
+        assert_eq!(tracker.global_rate(now, 50), None);
+        record(&mut tracker, now, &ServiceType::Food, 40, 0);
+        assert_eq!(tracker.global_rate(now, 50), Some(0.02));
+    }
+}
@@ -0,0 +1,536 @@
+//! 订单 EIP-712 结构化签名（03 §1.1「Created/Accepted 须 EIP-712 签名留痕」、01 §5 十一 双签与防护、01 §7 Domain Separator 写死）
+//!
+//! 域（EIP712Domain）：name=TravelTrust、version=1、chainId、verifyingContract（EscrowFactory），部署后不变。
+//! 下单签 OrderIntent（游客钱包），接单签 OrderAcceptance（导游钱包，经 intentHash 绑定游客所签条款）；动作类型由 primaryType 区分。
+//! 两者均含 orderId、nonce、expiry、termsVersionHash：nonce 按签名地址单调递增（跨动作共用），过期或 nonce 不增即拒绝，防重放。
+//! orderId 为 bytes32：订单 UUID 的 16 字节右对齐（高 16 字节为 0），与链上 EscrowInstance 的 orderId 一致。
+//! 签名恢复复用 wallet 模块（secp256k1、low-s、v 取 27/28 或 0/1）；签名人须为账号当前绑定钱包（P0-1），由调用方比对。
+
+use crate::types::ServiceType;
+use crate::wallet::{self, WalletError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const DOMAIN_NAME: &str = "TravelTrust";
+pub const DOMAIN_VERSION: &str = "1";
+/// 当前条款版本；条款变更时同步修改，旧版本签名即失效
+pub const TERMS_VERSION: &str = "traveltrust-terms-v1";
+
+pub const DOMAIN_FIELDS: &[(&str, &str)] = &[
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Eip712Error {
+    #[error("字段 {0} 格式无效")]
+    InvalidField(&'static str),
+    #[error("签名已过期")]
+    Expired,
+    #[error("nonce 须大于该钱包上次使用的 {last}")]
+    NonceNotIncreasing { last: u64 },
+    #[error("条款版本不一致，请按当前条款重新签名")]
+    TermsVersionMismatch,
+    #[error(transparent)]
+    Signature(#[from] WalletError),
+}
+
+/// 类型字符串：Name(type1 name1,type2 name2,...)
+fn encode_type(name: &str, fields: &[(&str, &str)]) -> String {
+    let members: Vec<String> = fields.iter().map(|(n, t)| format!("{} {}", t, n)).collect();
+    format!("{}({})", name, members.join(","))
+}
+
+fn word_u64(v: u64) -> [u8; 32] {
+    let mut w = [0u8; 32];
+    w[24..].copy_from_slice(&v.to_be_bytes());
+    w
+}
+
+fn word_address(address: &str, field: &'static str) -> Result<[u8; 32], Eip712Error> {
+    let lower = wallet::normalize_address(address).map_err(|_| Eip712Error::InvalidField(field))?;
+    let mut w = [0u8; 32];
+    hex::decode_to_slice(&lower[2..], &mut w[12..]).map_err(|_| Eip712Error::InvalidField(field))?;
+    Ok(w)
+}
+
+fn word_bytes32(value: &str, field: &'static str) -> Result<[u8; 32], Eip712Error> {
+    let mut w = [0u8; 32];
+    hex::decode_to_slice(value.trim_start_matches("0x"), &mut w).map_err(|_| Eip712Error::InvalidField(field))?;
+    Ok(w)
+}
+
+fn word_string(value: &str) -> [u8; 32] {
+    wallet::keccak256(value.as_bytes())
+}
+
+/// UUID → bytes32（右对齐）
+pub fn uuid_word(id: Uuid) -> [u8; 32] {
+    let mut w = [0u8; 32];
+    w[16..].copy_from_slice(id.as_bytes());
+    w
+}
+
+pub fn hex32(w: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(w))
+}
+
+/// keccak256(TERMS_VERSION)
+pub fn terms_version_hash() -> String {
+    hex32(&wallet::keccak256(TERMS_VERSION.as_bytes()))
+}
+
+/// 服务类型在签名结构中的字符串：与 API 的 snake_case 一致，自定义类型为 other:<名称>
+pub fn service_type_code(service_type: &ServiceType) -> String {
+    match service_type {
+        ServiceType::WalkingTour => "walking_tour".into(),
+        ServiceType::CarTour => "car_tour".into(),
+        ServiceType::MultiDay => "multi_day".into(),
+        ServiceType::Cultural => "cultural".into(),
+        ServiceType::Food => "food".into(),
+        ServiceType::Other(name) => format!("other:{}", name),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    /// EscrowFactory 地址（小写 0x）
+    pub verifying_contract: String,
+}
+
+impl Eip712Domain {
+    pub fn new(chain_id: u64, verifying_contract: &str) -> Result<Self, Eip712Error> {
+        Ok(Self {
+            name: DOMAIN_NAME.to_string(),
+            version: DOMAIN_VERSION.to_string(),
+            chain_id,
+            verifying_contract: wallet::normalize_address(verifying_contract)
+                .map_err(|_| Eip712Error::InvalidField("verifyingContract"))?,
+        })
+    }
+
+    pub fn separator(&self) -> Result<[u8; 32], Eip712Error> {
+        let mut data = wallet::keccak256(encode_type("EIP712Domain", DOMAIN_FIELDS).as_bytes()).to_vec();
+        data.extend(word_string(&self.name));
+        data.extend(word_string(&self.version));
+        data.extend(word_u64(self.chain_id));
+        data.extend(word_address(&self.verifying_contract, "verifyingContract")?);
+        Ok(wallet::keccak256(&data))
+    }
+}
+
+/// 可做 EIP-712 hashStruct 的订单结构
+pub trait TypedStruct {
+    const TYPE_NAME: &'static str;
+    const FIELDS: &'static [(&'static str, &'static str)];
+
+    /// 按 FIELDS 顺序的 32 字节编码
+    fn encode_data(&self) -> Result<Vec<[u8; 32]>, Eip712Error>;
+
+    fn nonce(&self) -> u64;
+    fn expiry(&self) -> i64;
+    fn terms_version_hash(&self) -> &str;
+
+    fn struct_hash(&self) -> Result<[u8; 32], Eip712Error> {
+        let mut data = wallet::keccak256(encode_type(Self::TYPE_NAME, Self::FIELDS).as_bytes()).to_vec();
+        for word in self.encode_data()? {
+            data.extend(word);
+        }
+        Ok(wallet::keccak256(&data))
+    }
+
+    /// 待签摘要：keccak256(0x1901 ‖ domainSeparator ‖ hashStruct)
+    fn signing_hash(&self, domain: &Eip712Domain) -> Result<[u8; 32], Eip712Error> {
+        let mut data = vec![0x19, 0x01];
+        data.extend(domain.separator()?);
+        data.extend(self.struct_hash()?);
+        Ok(wallet::keccak256(&data))
+    }
+}
+
+/// 游客下单意向（Created）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderIntent {
+    pub order_id: Uuid,
+    /// 游客钱包
+    pub tourist: String,
+    pub guide_id: Uuid,
+    pub service_type: String,
+    /// 十进制金额字符串，与订单 amount 原样一致
+    pub amount: String,
+    pub currency: String,
+    pub nonce: u64,
+    /// Unix 秒
+    pub expiry: i64,
+    pub terms_version_hash: String,
+}
+
+impl TypedStruct for OrderIntent {
+    const TYPE_NAME: &'static str = "OrderIntent";
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("orderId", "bytes32"),
+        ("tourist", "address"),
+        ("guideId", "bytes32"),
+        ("serviceType", "string"),
+        ("amount", "string"),
+        ("currency", "string"),
+        ("nonce", "uint256"),
+        ("expiry", "uint256"),
+        ("termsVersionHash", "bytes32"),
+    ];
+
+    fn encode_data(&self) -> Result<Vec<[u8; 32]>, Eip712Error> {
+        Ok(vec![
+            uuid_word(self.order_id),
+            word_address(&self.tourist, "tourist")?,
+            uuid_word(self.guide_id),
+            word_string(&self.service_type),
+            word_string(&self.amount),
+            word_string(&self.currency),
+            word_u64(self.nonce),
+            word_u64(u64::try_from(self.expiry).map_err(|_| Eip712Error::InvalidField("expiry"))?),
+            word_bytes32(&self.terms_version_hash, "termsVersionHash")?,
+        ])
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+    fn expiry(&self) -> i64 {
+        self.expiry
+    }
+    fn terms_version_hash(&self) -> &str {
+        &self.terms_version_hash
+    }
+}
+
+/// 导游接单确认（Accepted）；intentHash 为游客 OrderIntent 的 hashStruct
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderAcceptance {
+    pub order_id: Uuid,
+    /// 导游钱包
+    pub guide: String,
+    pub intent_hash: String,
+    pub nonce: u64,
+    pub expiry: i64,
+    pub terms_version_hash: String,
+}
+
+impl TypedStruct for OrderAcceptance {
+    const TYPE_NAME: &'static str = "OrderAcceptance";
+    const FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("orderId", "bytes32"),
+        ("guide", "address"),
+        ("intentHash", "bytes32"),
+        ("nonce", "uint256"),
+        ("expiry", "uint256"),
+        ("termsVersionHash", "bytes32"),
+    ];
+
+    fn encode_data(&self) -> Result<Vec<[u8; 32]>, Eip712Error> {
+        Ok(vec![
+            uuid_word(self.order_id),
+            word_address(&self.guide, "guide")?,
+            word_bytes32(&self.intent_hash, "intentHash")?,
+            word_u64(self.nonce),
+            word_u64(u64::try_from(self.expiry).map_err(|_| Eip712Error::InvalidField("expiry"))?),
+            word_bytes32(&self.terms_version_hash, "termsVersionHash")?,
+        ])
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+    fn expiry(&self) -> i64 {
+        self.expiry
+    }
+    fn terms_version_hash(&self) -> &str {
+        &self.terms_version_hash
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedAction {
+    Intent,
+    Acceptance,
+}
+
+/// 订单签名留痕（Order 上只写一次，不随换绑变更）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderSignature {
+    pub action: SignedAction,
+    /// 恢复出的签名钱包（小写 0x）
+    pub signer: String,
+    pub signature: String,
+    /// hashStruct（接单签名的 intentHash 即引用下单签名的此值）
+    pub struct_hash: String,
+    /// 0x1901 摘要
+    pub digest: String,
+    pub nonce: u64,
+    pub expiry: i64,
+    pub terms_version_hash: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// 校验过期、条款版本并恢复签名人；nonce 由 SignerNonces 另行校验
+pub fn recover_signer<T: TypedStruct>(
+    domain: &Eip712Domain,
+    value: &T,
+    action: SignedAction,
+    signature: &str,
+    now: DateTime<Utc>,
+) -> Result<OrderSignature, Eip712Error> {
+    if value.expiry() <= now.timestamp() {
+        return Err(Eip712Error::Expired);
+    }
+    if !value.terms_version_hash().eq_ignore_ascii_case(&terms_version_hash()) {
+        return Err(Eip712Error::TermsVersionMismatch);
+    }
+    let digest = value.signing_hash(domain)?;
+    let signer = wallet::recover_address(&digest, signature)?;
+    Ok(OrderSignature {
+        action,
+        signer,
+        signature: signature.trim().to_string(),
+        struct_hash: hex32(&value.struct_hash()?),
+        digest: hex32(&digest),
+        nonce: value.nonce(),
+        expiry: value.expiry(),
+        terms_version_hash: terms_version_hash(),
+        signed_at: now,
+    })
+}
+
+/// 各签名钱包已使用的最大 nonce（单调递增防重放）
+#[derive(Debug, Default)]
+pub struct SignerNonces {
+    last: HashMap<String, u64>,
+}
+
+impl SignerNonces {
+    pub fn check(&self, signer: &str, nonce: u64) -> Result<(), Eip712Error> {
+        match self.last.get(signer) {
+            Some(&last) if nonce <= last => Err(Eip712Error::NonceNotIncreasing { last }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn commit(&mut self, signer: &str, nonce: u64) {
+        self.last.insert(signer.to_string(), nonce);
+    }
+
+    pub fn last(&self, signer: &str) -> Option<u64> {
+        self.last.get(signer).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use k256::ecdsa::SigningKey;
+
+    /// EIP-712 规范示例（Ether Mail）的 Person 结构
+    struct Person {
+        name: &'static str,
+        wallet: &'static str,
+    }
+
+    impl TypedStruct for Person {
+        const TYPE_NAME: &'static str = "Person";
+        const FIELDS: &'static [(&'static str, &'static str)] = &[("name", "string"), ("wallet", "address")];
+
+        fn encode_data(&self) -> Result<Vec<[u8; 32]>, Eip712Error> {
+            Ok(vec![word_string(self.name), word_address(self.wallet, "wallet")?])
+        }
+
+        fn nonce(&self) -> u64 {
+            0
+        }
+        fn expiry(&self) -> i64 {
+            0
+        }
+        fn terms_version_hash(&self) -> &str {
+            ""
+        }
+    }
+
+    /// Mail 引用 Person，类型串须拼接被引用类型；订单结构无嵌套，故此处覆写 struct_hash
+    struct Mail {
+        from: Person,
+        to: Person,
+        contents: &'static str,
+    }
+
+    impl TypedStruct for Mail {
+        const TYPE_NAME: &'static str = "Mail";
+        const FIELDS: &'static [(&'static str, &'static str)] =
+            &[("from", "Person"), ("to", "Person"), ("contents", "string")];
+
+        fn encode_data(&self) -> Result<Vec<[u8; 32]>, Eip712Error> {
+            Ok(vec![self.from.struct_hash()?, self.to.struct_hash()?, word_string(self.contents)])
+        }
+
+        fn struct_hash(&self) -> Result<[u8; 32], Eip712Error> {
+            let type_string = format!(
+                "{}{}",
+                encode_type(Self::TYPE_NAME, Self::FIELDS),
+                encode_type(Person::TYPE_NAME, Person::FIELDS)
+            );
+            let mut data = wallet::keccak256(type_string.as_bytes()).to_vec();
+            for word in self.encode_data()? {
+                data.extend(word);
+            }
+            Ok(wallet::keccak256(&data))
+        }
+
+        fn nonce(&self) -> u64 {
+            0
+        }
+        fn expiry(&self) -> i64 {
+            0
+        }
+        fn terms_version_hash(&self) -> &str {
+            ""
+        }
+    }
+
+    fn mail_domain() -> Eip712Domain {
+        Eip712Domain {
+            name: "Ether Mail".into(),
+            version: "1".into(),
+            chain_id: 1,
+            verifying_contract: "0xcccccccccccccccccccccccccccccccccccccccc".into(),
+        }
+    }
+
+    fn mail() -> Mail {
+        Mail {
+            from: Person {
+                name: "Cow",
+                wallet: "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+            },
+            to: Person {
+                name: "Bob",
+                wallet: "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+            },
+            contents: "Hello, Bob!",
+        }
+    }
+
+    /// 规范示例签名（私钥 keccak256("cow")）：r、s 与 v=28
+    const MAIL_R: &str = "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d";
+    const MAIL_S: &str = "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562";
+    /// secp256k1 阶 n - MAIL_S（同一签名的 high-s 形式）
+    const MAIL_HIGH_S: &str = "f8d666c92cfb3eac09bbc205fa0bf00eb2d7b3d4f8517d33c63c3b76ca7d2bdf";
+    const COW_ADDRESS: &str = "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826";
+
+    fn mail_signature(s: &str, v: u8) -> String {
+        format!("0x{}{}{:02x}", MAIL_R, s, v)
+    }
+
+    #[test]
+    fn mail_example_matches_spec_vectors() {
+        let domain = mail_domain();
+        assert_eq!(
+            hex32(&domain.separator().unwrap()),
+            "0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        let mail = mail();
+        assert_eq!(
+            hex32(&mail.struct_hash().unwrap()),
+            "0xc52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex32(&mail.signing_hash(&domain).unwrap()),
+            "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn mail_example_signature_recovers_with_either_v_form() {
+        let digest = mail().signing_hash(&mail_domain()).unwrap();
+        assert_eq!(wallet::recover_address(&digest, &mail_signature(MAIL_S, 28)).unwrap(), COW_ADDRESS);
+        assert_eq!(wallet::recover_address(&digest, &mail_signature(MAIL_S, 1)).unwrap(), COW_ADDRESS);
+        // 错误的恢复位得到其他地址，由调用方比对绑定钱包时拒绝
+        assert_ne!(wallet::recover_address(&digest, &mail_signature(MAIL_S, 27)).unwrap(), COW_ADDRESS);
+        assert_ne!(wallet::recover_address(&digest, &mail_signature(MAIL_S, 0)).unwrap(), COW_ADDRESS);
+        assert_eq!(
+            wallet::recover_address(&digest, &mail_signature(MAIL_S, 2)),
+            Err(WalletError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn high_s_signature_is_rejected() {
+        let digest = mail().signing_hash(&mail_domain()).unwrap();
+        // (r, n - s) 配合翻转的 v 在 secp256k1 上同样有效，EIP-2 要求拒绝以防签名延展
+        assert_eq!(
+            wallet::recover_address(&digest, &mail_signature(MAIL_HIGH_S, 27)),
+            Err(WalletError::HighS)
+        );
+    }
+
+    fn sign(key: &SigningKey, digest: &[u8; 32]) -> String {
+        let (sig, recovery_id) = key.sign_prehash_recoverable(digest).unwrap();
+        format!("0x{}{:02x}", hex::encode(sig.to_bytes()), 27 + recovery_id.to_byte())
+    }
+
+    #[test]
+    fn order_intent_sign_and_recover_round_trip() {
+        let key = SigningKey::from_slice(&wallet::keccak256(b"cow")).unwrap();
+        let domain = Eip712Domain::new(31337, "0x5FbDB2315678afecb367f032d93F642f64180aa3").unwrap();
+        let now = Utc::now();
+        let intent = OrderIntent {
+            order_id: Uuid::new_v4(),
+            tourist: COW_ADDRESS.into(),
+            guide_id: Uuid::new_v4(),
+            service_type: service_type_code(&ServiceType::Food),
+            amount: "120.50".into(),
+            currency: "USDC".into(),
+            nonce: 1,
+            expiry: (now + Duration::minutes(10)).timestamp(),
+            terms_version_hash: terms_version_hash(),
+        };
+        let signature = sign(&key, &intent.signing_hash(&domain).unwrap());
+
+        let signed = recover_signer(&domain, &intent, SignedAction::Intent, &signature, now).unwrap();
+        assert_eq!(signed.signer, COW_ADDRESS);
+        assert_eq!(signed.struct_hash, hex32(&intent.struct_hash().unwrap()));
+
+        // 篡改任一字段即恢复出其他地址；换链（domain 不同）同理
+        let mut tampered = intent.clone();
+        tampered.amount = "1.00".into();
+        let other = recover_signer(&domain, &tampered, SignedAction::Intent, &signature, now).unwrap();
+        assert_ne!(other.signer, COW_ADDRESS);
+        let other_chain = Eip712Domain::new(1, "0x5FbDB2315678afecb367f032d93F642f64180aa3").unwrap();
+        let other = recover_signer(&other_chain, &intent, SignedAction::Intent, &signature, now).unwrap();
+        assert_ne!(other.signer, COW_ADDRESS);
+
+        assert_eq!(
+            recover_signer(&domain, &intent, SignedAction::Intent, &signature, now + Duration::minutes(11)).unwrap_err(),
+            Eip712Error::Expired
+        );
+    }
+
+    #[test]
+    fn signer_nonces_must_increase() {
+        let mut nonces = SignerNonces::default();
+        assert!(nonces.check(COW_ADDRESS, 1).is_ok());
+        nonces.commit(COW_ADDRESS, 1);
+        assert_eq!(nonces.check(COW_ADDRESS, 1), Err(Eip712Error::NonceNotIncreasing { last: 1 }));
+        assert!(nonces.check(COW_ADDRESS, 2).is_ok());
+    }
+}
@@ -1,6 +1,10 @@
 //! 订单托管状态机（与 01 §1 一致）：created → accepted → escrowed → completed | disputed → refunded/partially_refunded/slashed
 
+use crate::amount::TokenAmount;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -32,6 +36,50 @@ impl OrderState {
     }
 }
 
+/// 订单资金结算：链上放款/退款事件对账后写入（01 §5 对账三段式；DB 资金状态仅由链上事件驱动）。
+/// 完成放款 guide_payout 为全额；争议裁决按 refund_ratio 拆分，金额以链上事件为准
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlement {
+    pub order_id: Uuid,
+    pub currency: String,
+    /// 放给导游的金额
+    pub guide_payout: TokenAmount,
+    /// 退给游客的金额
+    pub tourist_refund: TokenAmount,
+    /// 放款/退款交易哈希
+    pub tx_hash: Option<String>,
+    pub settled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SettlementError {
+    #[error("订单状态 {0:?} 不可结算，仅 escrowed 或已裁决的 disputed 订单")]
+    InvalidState(OrderState),
+    #[error("放款与退款之和须等于订单金额")]
+    AmountMismatch,
+}
+
+impl Settlement {
+    /// 结算后的资金终态：争议裁决扣罚导游为 slashed；否则无退款为 completed、无放款为 refunded，其余 partially_refunded
+    pub fn final_state(&self, state: OrderState, order_amount: TokenAmount, slash_guide: bool) -> Result<OrderState, SettlementError> {
+        if !matches!(state, OrderState::Escrowed | OrderState::Disputed) {
+            return Err(SettlementError::InvalidState(state));
+        }
+        if self.guide_payout.saturating_add(self.tourist_refund) != order_amount {
+            return Err(SettlementError::AmountMismatch);
+        }
+        Ok(if state == OrderState::Disputed && slash_guide {
+            OrderState::Slashed
+        } else if self.tourist_refund.is_zero() {
+            OrderState::Completed
+        } else if self.guide_payout.is_zero() {
+            OrderState::Refunded
+        } else {
+            OrderState::PartiallyRefunded
+        })
+    }
+}
+
 /// 托管层抽象（链下或链上）
 pub trait EscrowState: Send + Sync {
     /// 是否允许对该订单进行评价（仅资金终态：completed / refunded / partially_refunded / slashed）
@@ -47,3 +95,43 @@ pub trait EscrowState: Send + Sync {
 
 pub struct DefaultEscrow;
 impl EscrowState for DefaultEscrow {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settlement(guide_payout: &str, tourist_refund: &str) -> Settlement {
+        Settlement {
+            order_id: Uuid::new_v4(),
+            currency: "USDC".into(),
+            guide_payout: guide_payout.parse().unwrap(),
+            tourist_refund: tourist_refund.parse().unwrap(),
+            tx_hash: None,
+            settled_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn final_state_follows_split_and_slash() {
+        let amount: TokenAmount = "100".parse().unwrap();
+        let full = settlement("100", "0");
+        let refund = settlement("0", "100");
+        let split = settlement("60.5", "39.5");
+        assert_eq!(full.final_state(OrderState::Escrowed, amount, false), Ok(OrderState::Completed));
+        assert_eq!(refund.final_state(OrderState::Disputed, amount, false), Ok(OrderState::Refunded));
+        assert_eq!(split.final_state(OrderState::Disputed, amount, false), Ok(OrderState::PartiallyRefunded));
+        assert_eq!(refund.final_state(OrderState::Disputed, amount, true), Ok(OrderState::Slashed));
+        // 扣罚仅来自争议裁决
+        assert_eq!(full.final_state(OrderState::Escrowed, amount, true), Ok(OrderState::Completed));
+    }
+
+    #[test]
+    fn final_state_rejects_wrong_state_or_amount() {
+        let amount: TokenAmount = "100".parse().unwrap();
+        for state in [OrderState::Created, OrderState::Accepted, OrderState::Completed, OrderState::Cancelled] {
+            assert_eq!(settlement("100", "0").final_state(state, amount, false), Err(SettlementError::InvalidState(state)));
+        }
+        assert_eq!(settlement("60", "30").final_state(OrderState::Escrowed, amount, false), Err(SettlementError::AmountMismatch));
+        assert_eq!(settlement("100", "0.000001").final_state(OrderState::Escrowed, amount, false), Err(SettlementError::AmountMismatch));
+    }
+}
@@ -0,0 +1,293 @@
+//! 证据领域模型（01 §6、04 §一「独立 evidence 表」）：元数据、大小/类型校验与上传回执（Runbook §5）
+//!
+//! 08-3：evidenceMaxSize=50MB、evidenceTypeAllowlist=image/*,application/pdf。声明类型与魔数嗅探类型须同时在白名单内且一致，
+//! 防止以 pdf/图片名义上传可执行内容（证据 DoS P0）。Dispute.evidence_hashes 存本结构的 sha256。
+//! 回执由服务器 ed25519 签名（key_id 标识轮换中的密钥）；`verify_receipt` 供游客、导游、法院离线验签，无需信任数据库。
+//! 原文件 hash 与回执另有 TSA 时间戳（timestamp），证明其不晚于盖章时间已存在。
+
+use crate::privacy::{DerivedEvidence, DerivedKind};
+use crate::timestamp::TimeStampToken;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 08-3 evidenceMaxSize：50 MB
+pub const EVIDENCE_MAX_SIZE_BYTES: u64 = 50 * 1024 * 1024;
+/// 08-3 evidenceTypeAllowlist
+pub const EVIDENCE_TYPE_ALLOWLIST: &[&str] = &["image/*", "application/pdf"];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EvidenceError {
+    #[error("证据为空")]
+    Empty,
+    #[error("证据大小 {size} 字节超过上限 {max} 字节（08-3 evidenceMaxSize）")]
+    TooLarge { size: u64, max: u64 },
+    #[error("证据类型 {0} 不在白名单内（08-3 evidenceTypeAllowlist）")]
+    TypeNotAllowed(String),
+    #[error("无法识别文件类型")]
+    UnknownType,
+    #[error("声明类型 {declared} 与文件内容 {sniffed} 不一致")]
+    TypeMismatch { declared: String, sniffed: String },
+    #[error("sha256 须为 64 位十六进制")]
+    InvalidHash,
+}
+
+/// 存储位置：后端名 + 对象键（对象存储 bucket/key 或本地路径）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLocation {
+    pub backend: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub id: Uuid,
+    /// 原文件 sha256（小写十六进制）
+    pub sha256: String,
+    pub uploader_id: Uuid,
+    pub order_id: Uuid,
+    pub dispute_id: Option<Uuid>,
+    /// 上传方声明的 Content-Type
+    pub declared_mime: String,
+    /// 按文件头魔数识别的类型
+    pub sniffed_mime: String,
+    pub size_bytes: u64,
+    pub uploaded_at: DateTime<Utc>,
+    pub location: StorageLocation,
+    /// 原文件因保留期满被删除的时间（元数据与 hash 保留，见 retention）
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// 派生件（去元数据副本、缩略图，见 privacy）
+    #[serde(default)]
+    pub derived: Vec<DerivedEvidence>,
+    /// 原文件 sha256 的可信时间戳（见 timestamp）；TSA 检测到时间回滚时为空
+    #[serde(default)]
+    pub timestamp: Option<TimeStampToken>,
+}
+
+/// 上传回执（Runbook §5：文件哈希、上传者、时间、orderId）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvidenceReceipt {
+    pub evidence_id: Uuid,
+    pub sha256: String,
+    pub uploader_id: Uuid,
+    pub order_id: Uuid,
+    pub dispute_id: Option<Uuid>,
+    pub mime: String,
+    pub size_bytes: u64,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+/// 回执签名域分隔前缀，防止签名被挪用到其他消息
+const RECEIPT_SIGNING_DOMAIN: &str = "traveltrust-evidence-receipt-v1";
+
+impl EvidenceReceipt {
+    /// 签名原文：域前缀 + key_id + 逐行 `字段=值`（时间固定为 RFC 3339 纳秒 UTC），不依赖 JSON 序列化细节
+    pub fn signing_bytes(&self, key_id: &str) -> Vec<u8> {
+        let dispute_id = self.dispute_id.map(|id| id.to_string()).unwrap_or_default();
+        format!(
+            "{}\nkey_id={}\nevidence_id={}\nsha256={}\nuploader_id={}\norder_id={}\ndispute_id={}\nmime={}\nsize_bytes={}\nuploaded_at={}",
+            RECEIPT_SIGNING_DOMAIN,
+            key_id,
+            self.evidence_id,
+            self.sha256,
+            self.uploader_id,
+            self.order_id,
+            dispute_id,
+            self.mime,
+            self.size_bytes,
+            self.uploaded_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        )
+        .into_bytes()
+    }
+}
+
+/// 已签名回执：交给上传方保存，可脱离平台数据库验证
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedEvidenceReceipt {
+    pub receipt: EvidenceReceipt,
+    /// 签名密钥标识（见 GET /api/v1/evidence/receipt-keys）
+    pub key_id: String,
+    pub algorithm: String,
+    /// ed25519 签名，十六进制
+    pub signature: String,
+    /// 回执签发时对 digest() 的可信时间戳（见 timestamp）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<TimeStampToken>,
+}
+
+impl SignedEvidenceReceipt {
+    /// 回执摘要：签名原文 + 签名的 sha256，作为时间戳的 message imprint
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.receipt.signing_bytes(&self.key_id));
+        hasher.update(b"\nsignature=");
+        hasher.update(self.signature.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// 公布的回执验签公钥（TSA 公钥同结构）（轮换后旧公钥继续公布）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptPublicKey {
+    pub key_id: String,
+    pub algorithm: String,
+    /// ed25519 公钥，十六进制
+    pub public_key: String,
+    /// 当前用于签发新回执
+    pub active: bool,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReceiptError {
+    #[error("不支持的签名算法 {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("未找到密钥 {0}")]
+    UnknownKey(String),
+    #[error("公钥格式错误")]
+    InvalidPublicKey,
+    #[error("签名格式错误")]
+    InvalidSignature,
+    #[error("签名校验失败")]
+    VerificationFailed,
+}
+
+pub const RECEIPT_ALGORITHM: &str = "ed25519";
+
+/// 用指定公钥验证回执签名
+pub fn verify_receipt(signed: &SignedEvidenceReceipt, public_key_hex: &str) -> Result<(), ReceiptError> {
+    if signed.algorithm != RECEIPT_ALGORITHM {
+        return Err(ReceiptError::UnsupportedAlgorithm(signed.algorithm.clone()));
+    }
+    verify_ed25519(&signed.receipt.signing_bytes(&signed.key_id), &signed.signature, public_key_hex)
+}
+
+/// 验证派生件（去元数据副本、缩略图）与来源原文件的绑定签名
+pub fn verify_derivation(derived: &DerivedEvidence, public_key_hex: &str) -> Result<(), ReceiptError> {
+    verify_ed25519(&derived.signing_bytes(&derived.key_id), &derived.signature, public_key_hex)
+}
+
+pub(crate) fn verify_ed25519(message: &[u8], signature_hex: &str, public_key_hex: &str) -> Result<(), ReceiptError> {
+    let key_bytes: [u8; 32] = hex::decode(public_key_hex)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or(ReceiptError::InvalidPublicKey)?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|_| ReceiptError::InvalidPublicKey)?;
+    let sig_bytes: [u8; 64] = hex::decode(signature_hex)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or(ReceiptError::InvalidSignature)?;
+    key.verify(message, &Signature::from_bytes(&sig_bytes))
+        .map_err(|_| ReceiptError::VerificationFailed)
+}
+
+/// 按回执中的 key_id 从公布的公钥列表中取钥验证（含已轮换的旧钥）
+pub fn verify_receipt_with_keys(signed: &SignedEvidenceReceipt, keys: &[ReceiptPublicKey]) -> Result<(), ReceiptError> {
+    let key = keys
+        .iter()
+        .find(|k| k.key_id == signed.key_id && k.algorithm == signed.algorithm)
+        .ok_or_else(|| ReceiptError::UnknownKey(signed.key_id.clone()))?;
+    verify_receipt(signed, &key.public_key)
+}
+
+impl Evidence {
+    /// 校验元数据（大小、类型白名单、声明与嗅探一致、hash 格式）
+    pub fn validate(&self) -> Result<(), EvidenceError> {
+        validate_size(self.size_bytes)?;
+        validate_type(&self.declared_mime, Some(&self.sniffed_mime))?;
+        validate_sha256(&self.sha256)
+    }
+
+    pub fn derived(&self, kind: DerivedKind) -> Option<&DerivedEvidence> {
+        self.derived.iter().find(|d| d.kind == kind)
+    }
+
+    pub fn receipt(&self) -> EvidenceReceipt {
+        EvidenceReceipt {
+            evidence_id: self.id,
+            sha256: self.sha256.clone(),
+            uploader_id: self.uploader_id,
+            order_id: self.order_id,
+            dispute_id: self.dispute_id,
+            mime: self.sniffed_mime.clone(),
+            size_bytes: self.size_bytes,
+            uploaded_at: self.uploaded_at,
+        }
+    }
+}
+
+pub fn validate_size(size: u64) -> Result<(), EvidenceError> {
+    match size {
+        0 => Err(EvidenceError::Empty),
+        s if s > EVIDENCE_MAX_SIZE_BYTES => Err(EvidenceError::TooLarge {
+            size: s,
+            max: EVIDENCE_MAX_SIZE_BYTES,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// MIME 是否命中白名单（支持 `type/*` 通配）
+pub fn is_allowed_mime(mime: &str) -> bool {
+    let mime = essence(mime);
+    EVIDENCE_TYPE_ALLOWLIST.iter().any(|allowed| match allowed.strip_suffix("/*") {
+        Some(prefix) => mime.split_once('/').is_some_and(|(t, sub)| t == prefix && !sub.is_empty()),
+        None => mime == *allowed,
+    })
+}
+
+/// 声明类型须在白名单；给出嗅探类型时须可识别、在白名单且与声明一致
+pub fn validate_type(declared: &str, sniffed: Option<&str>) -> Result<(), EvidenceError> {
+    let declared = essence(declared);
+    if !is_allowed_mime(&declared) {
+        return Err(EvidenceError::TypeNotAllowed(declared));
+    }
+    let sniffed = sniffed.ok_or(EvidenceError::UnknownType)?;
+    if !is_allowed_mime(sniffed) {
+        return Err(EvidenceError::TypeNotAllowed(sniffed.to_string()));
+    }
+    // image/jpg 等常见别名按嗅探结果归一
+    let same = declared == sniffed || (declared == "image/jpg" && sniffed == "image/jpeg");
+    if !same {
+        return Err(EvidenceError::TypeMismatch {
+            declared,
+            sniffed: sniffed.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn validate_sha256(hash: &str) -> Result<(), EvidenceError> {
+    if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+        Ok(())
+    } else {
+        Err(EvidenceError::InvalidHash)
+    }
+}
+
+/// 按文件头魔数识别白名单内的类型；无法识别返回 None
+pub fn sniff_mime(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if head.len() >= 12 && &head[0..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if head.len() >= 12 && &head[4..8] == b"ftyp" && matches!(&head[8..12], b"heic" | b"heix" | b"mif1") {
+        Some("image/heic")
+    } else {
+        None
+    }
+}
+
+/// 去掉参数并小写：`Image/PNG; charset=x` → `image/png`
+fn essence(mime: &str) -> String {
+    mime.split(';').next().unwrap_or("").trim().to_ascii_lowercase()
+}
@@ -0,0 +1,141 @@
+//! 类别冻结（08-3「异常争议率…自动冻结该类别新单」、Runbook §11）：冻结记录与登记表
+//!
+//! 冻结只能由运营人工解除（lift），解除后记录保留用于审计。
+//! 仲裁员冻结（bias 模块 FreezeArbitrator）同表登记：冻结期间该仲裁员不得再裁决。
+
+use crate::RiskScope;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 冻结原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum FreezeReason {
+    /// 争议率显著偏离同维度均值（score 为 z-score 或倍数，取决于阈值规则）
+    DisputeRate { rate: f64, peer_mean: f64, score: f64 },
+    /// 裁决集体偏向（bias 模块 PauseCategory）
+    RulingBias { lean: f64 },
+    /// 运营手动冻结
+    Manual { note: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryFreeze {
+    pub id: Uuid,
+    pub scope: RiskScope,
+    pub reason: FreezeReason,
+    pub frozen_at: DateTime<Utc>,
+    pub lifted_at: Option<DateTime<Utc>>,
+    pub lifted_by: Option<String>,
+    pub lift_note: Option<String>,
+}
+
+impl CategoryFreeze {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+}
+
+/// 仲裁员冻结（裁决偏向：占比越线或连续同向）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitratorFreeze {
+    pub id: Uuid,
+    pub arbitrator_id: Uuid,
+    pub lean: f64,
+    pub streak: usize,
+    pub frozen_at: DateTime<Utc>,
+    pub lifted_at: Option<DateTime<Utc>>,
+    pub lifted_by: Option<String>,
+    pub lift_note: Option<String>,
+}
+
+impl ArbitratorFreeze {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+}
+
+/// 冻结登记表：同一类别、同一仲裁员同时只保留一条生效冻结
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FreezeRegistry {
+    freezes: Vec<CategoryFreeze>,
+    #[serde(default)]
+    arbitrators: Vec<ArbitratorFreeze>,
+}
+
+impl FreezeRegistry {
+    /// 冻结类别；已有生效冻结时不重复登记，返回已有记录
+    pub fn freeze(&mut self, scope: RiskScope, reason: FreezeReason, now: DateTime<Utc>) -> &CategoryFreeze {
+        if let Some(idx) = self.freezes.iter().position(|f| f.is_active() && f.scope == scope) {
+            return &self.freezes[idx];
+        }
+        self.freezes.push(CategoryFreeze {
+            id: Uuid::new_v4(),
+            scope,
+            reason,
+            frozen_at: now,
+            lifted_at: None,
+            lifted_by: None,
+            lift_note: None,
+        });
+        &self.freezes[self.freezes.len() - 1]
+    }
+
+    /// 运营解除冻结；id 不存在或已解除返回 None
+    pub fn lift(&mut self, id: Uuid, by: &str, note: Option<String>, now: DateTime<Utc>) -> Option<&CategoryFreeze> {
+        let freeze = self.freezes.iter_mut().find(|f| f.id == id && f.is_active())?;
+        freeze.lifted_at = Some(now);
+        freeze.lifted_by = Some(by.to_string());
+        freeze.lift_note = note;
+        Some(freeze)
+    }
+
+    /// 给定类别中任一处于生效冻结时返回该冻结
+    pub fn active_for(&self, scopes: &[RiskScope]) -> Option<&CategoryFreeze> {
+        self.freezes.iter().find(|f| f.is_active() && scopes.contains(&f.scope))
+    }
+
+    pub fn is_frozen(&self, scope: &RiskScope) -> bool {
+        self.freezes.iter().any(|f| f.is_active() && &f.scope == scope)
+    }
+
+    pub fn all(&self) -> &[CategoryFreeze] {
+        &self.freezes
+    }
+
+    /// 冻结仲裁员；已有生效冻结时不重复登记，返回已有记录
+    pub fn freeze_arbitrator(&mut self, arbitrator_id: Uuid, lean: f64, streak: usize, now: DateTime<Utc>) -> &ArbitratorFreeze {
+        if let Some(idx) = self.arbitrators.iter().position(|f| f.is_active() && f.arbitrator_id == arbitrator_id) {
+            return &self.arbitrators[idx];
+        }
+        self.arbitrators.push(ArbitratorFreeze {
+            id: Uuid::new_v4(),
+            arbitrator_id,
+            lean,
+            streak,
+            frozen_at: now,
+            lifted_at: None,
+            lifted_by: None,
+            lift_note: None,
+        });
+        &self.arbitrators[self.arbitrators.len() - 1]
+    }
+
+    /// 运营解除仲裁员冻结；id 不存在或已解除返回 None
+    pub fn lift_arbitrator(&mut self, id: Uuid, by: &str, note: Option<String>, now: DateTime<Utc>) -> Option<&ArbitratorFreeze> {
+        let freeze = self.arbitrators.iter_mut().find(|f| f.id == id && f.is_active())?;
+        freeze.lifted_at = Some(now);
+        freeze.lifted_by = Some(by.to_string());
+        freeze.lift_note = note;
+        Some(freeze)
+    }
+
+    pub fn arbitrator_frozen(&self, arbitrator_id: Uuid) -> Option<&ArbitratorFreeze> {
+        self.arbitrators.iter().find(|f| f.is_active() && f.arbitrator_id == arbitrator_id)
+    }
+
+    pub fn arbitrator_freezes(&self) -> &[ArbitratorFreeze] {
+        &self.arbitrators
+    }
+}
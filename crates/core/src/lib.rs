@@ -1,13 +1,59 @@
-//! TravelTrust 领域类型与抽象：Registry / Escrow / Staking / Reputation / Dispute
+//! TravelTrust 领域类型与抽象：Registry / Escrow / Staking / Reputation / Dispute / 风控监控
 //!
 //! 先链下实现，接口设计兼容后续上链。
 
+pub mod amount;
+pub mod anchor;
+pub mod audit;
+pub mod auth;
+pub mod availability;
+pub mod bias;
+pub mod capacity;
+pub mod dispute_rate;
+pub mod eip712;
 pub mod escrow;
+pub mod evidence;
+pub mod freeze;
+pub mod privacy;
+pub mod profile;
+pub mod rbac;
 pub mod reputation;
+pub mod retention;
+pub mod siwe;
+pub mod sla;
 pub mod staking;
+pub mod stats;
+pub mod sybil;
+pub mod timestamp;
+pub mod totp;
 pub mod types;
+pub mod wallet;
 
-pub use escrow::{EscrowState, OrderState};
+pub use amount::{CurrencyTotals, TokenAmount};
+pub use anchor::{AnchorBatch, AnchorItem, AnchorKind, AnchorLedger, AnchorTx, InclusionProof};
+pub use audit::{AccessAction, AccessLog, AccessLogEntry, SecurityEvent, SecurityLog, SecurityLogEntry};
+pub use auth::{OneTimePurpose, OneTimeTokenRegistry, RefreshRegistry, SessionDevice, SessionFamily};
+pub use availability::BackendHealth;
+pub use bias::{BiasAction, BiasBaselines, BiasMonitor, BiasThresholds, RulingRecord, SecondReview, SecondReviewQueue};
+pub use capacity::{CapacityController, CapacitySnapshot, DisputeMode};
+pub use dispute_rate::{DisputeRateThresholds, DisputeRateTracker, RateRule};
+pub use eip712::{Eip712Domain, OrderAcceptance, OrderIntent, OrderSignature, SignedAction, SignerNonces};
+pub use escrow::{EscrowState, OrderState, Settlement, SettlementError};
+pub use evidence::{
+    Evidence, EvidenceError, EvidenceReceipt, ReceiptPublicKey, SignedEvidenceReceipt, StorageLocation,
+};
+pub use freeze::{ArbitratorFreeze, CategoryFreeze, FreezeReason, FreezeRegistry};
+pub use privacy::{DerivedEvidence, DerivedKind};
+pub use profile::ProfileError;
+pub use rbac::{Audience, Ownership, RoutePolicy};
 pub use reputation::ReviewWeight;
+pub use retention::{DeletionRecord, HoldReason, LegalHold, LegalHoldRegistry, RetentionPolicy};
+pub use siwe::{SiweMessage, SiweNonces};
+pub use sla::{SlaAlert, SlaClock, SlaPolicy};
 pub use staking::StakeTier;
+pub use stats::{StatsCache, UserStats};
+pub use sybil::{SharedSignal, SignalKind, SybilSignals};
+pub use timestamp::{MessageImprint, TimeStampToken};
+pub use totp::{SecondFactor, TotpRegistry};
 pub use types::*;
+pub use wallet::{RebindBlocker, WalletBinding, WalletChallenge, WalletRegistry};
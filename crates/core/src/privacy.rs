@@ -0,0 +1,463 @@
+//! 证据隐私处理（P0-4 隐私、01 §6）：图片去元数据（EXIF/GPS、XMP、IPTC、注释）与派生件
+//!
+//! 原文件字节不变（hash 与回执完整性），进入受限访问；对外（订单双方）提供去元数据副本，仲裁查看器使用缩略图。
+//! 去元数据为无损的容器级剔除（不重新编码像素），支持 JPEG / PNG / WebP / GIF；主图之后的附加数据（JPEG 副图、PNG IEND 之后等）一律丢弃；HEIC 等不支持时不产出副本，原文件仅限受限角色访问。
+//! 派生件记录来源原文件 sha256 并由服务器回执密钥签名（signing_bytes），可证明其由哪一份原文件生成。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::evidence::StorageLocation;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PrivacyError {
+    #[error("不支持去元数据的类型 {0}")]
+    Unsupported(String),
+    #[error("文件结构损坏，无法去元数据")]
+    Malformed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedKind {
+    /// 去元数据副本
+    Redacted,
+    /// 缩略图（仲裁查看器）
+    Thumbnail,
+}
+
+impl DerivedKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DerivedKind::Redacted => "redacted",
+            DerivedKind::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+/// 派生件：与原文件 sha256 绑定并签名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivedEvidence {
+    pub kind: DerivedKind,
+    pub sha256: String,
+    /// 来源原文件 sha256
+    pub source_sha256: String,
+    pub mime: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+    pub location: StorageLocation,
+    pub key_id: String,
+    /// ed25519 签名，十六进制（同回执密钥，见 evidence::verify_derivation）
+    pub signature: String,
+}
+
+/// 派生件签名域分隔前缀
+const DERIVATION_SIGNING_DOMAIN: &str = "traveltrust-evidence-derivation-v1";
+
+impl DerivedEvidence {
+    pub fn signing_bytes(&self, key_id: &str) -> Vec<u8> {
+        format!(
+            "{}\nkey_id={}\nkind={}\nsha256={}\nsource_sha256={}\nmime={}\nsize_bytes={}\ncreated_at={}",
+            DERIVATION_SIGNING_DOMAIN,
+            key_id,
+            self.kind.as_str(),
+            self.sha256,
+            self.source_sha256,
+            self.mime,
+            self.size_bytes,
+            self.created_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        )
+        .into_bytes()
+    }
+}
+
+/// 该类型是否须去元数据后才可对订单双方提供（照片类）
+pub fn requires_redaction(mime: &str) -> bool {
+    mime.starts_with("image/")
+}
+
+/// 按嗅探类型去除元数据；返回新字节
+pub fn strip_metadata(mime: &str, bytes: &[u8]) -> Result<Vec<u8>, PrivacyError> {
+    match mime {
+        "image/jpeg" => strip_jpeg(bytes),
+        "image/png" => strip_png(bytes),
+        "image/webp" => strip_webp(bytes),
+        "image/gif" => strip_gif(bytes),
+        other => Err(PrivacyError::Unsupported(other.to_string())),
+    }
+}
+
+/// JPEG：剔除 APP1（EXIF/XMP）、APP2 中的 MPF 索引、APP3–APP13（含 IPTC）、APP15 与 COM；保留 APP0（JFIF）、APP2（ICC）、APP14（Adobe）。
+/// 逐段解析至主图 EOI 为止（渐进式的多个扫描段之间的表段照常处理），EOI 之后的数据一律丢弃：
+/// 手机照片常在主图之后附带 MPF 副图/深度图，各自带完整 EXIF（含 GPS）
+fn strip_jpeg(b: &[u8]) -> Result<Vec<u8>, PrivacyError> {
+    if !b.starts_with(&[0xFF, 0xD8]) {
+        return Err(PrivacyError::Malformed);
+    }
+    let mut out = Vec::with_capacity(b.len());
+    out.extend_from_slice(&b[..2]);
+    let mut i = 2;
+    loop {
+        if i + 1 >= b.len() || b[i] != 0xFF {
+            return Err(PrivacyError::Malformed);
+        }
+        let marker = b[i + 1];
+        if marker == 0xFF {
+            // 填充字节
+            i += 1;
+            continue;
+        }
+        // 主图结束，丢弃之后的副图等附加数据
+        if marker == 0xD9 {
+            out.extend_from_slice(&b[i..i + 2]);
+            return Ok(out);
+        }
+        // 无长度字段的标记
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            out.extend_from_slice(&b[i..i + 2]);
+            i += 2;
+            continue;
+        }
+        if i + 4 > b.len() {
+            return Err(PrivacyError::Malformed);
+        }
+        let len = u16::from_be_bytes([b[i + 2], b[i + 3]]) as usize;
+        let end = i + 2 + len;
+        if len < 2 || end > b.len() {
+            return Err(PrivacyError::Malformed);
+        }
+        let mpf = marker == 0xE2 && b[i + 4..end].starts_with(b"MPF\0");
+        let drop = mpf || matches!(marker, 0xE1 | 0xE3..=0xED | 0xEF | 0xFE);
+        if !drop {
+            out.extend_from_slice(&b[i..end]);
+        }
+        i = end;
+        // SOS 之后为熵编码数据，原样保留至下一个标记（0xFF00 为填充、RSTn 属于扫描数据）
+        if marker == 0xDA {
+            let mut j = i;
+            loop {
+                if j + 1 >= b.len() {
+                    return Err(PrivacyError::Malformed);
+                }
+                if b[j] == 0xFF && !matches!(b[j + 1], 0x00 | 0xD0..=0xD7 | 0xFF) {
+                    break;
+                }
+                j += 1;
+            }
+            out.extend_from_slice(&b[i..j]);
+            i = j;
+        }
+    }
+}
+
+/// PNG：剔除 eXIf、tEXt、zTXt、iTXt、tIME
+fn strip_png(b: &[u8]) -> Result<Vec<u8>, PrivacyError> {
+    const SIG: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !b.starts_with(SIG) {
+        return Err(PrivacyError::Malformed);
+    }
+    let mut out = Vec::with_capacity(b.len());
+    out.extend_from_slice(SIG);
+    let mut i = SIG.len();
+    while i < b.len() {
+        if i + 8 > b.len() {
+            return Err(PrivacyError::Malformed);
+        }
+        let len = u32::from_be_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) as usize;
+        let kind = &b[i + 4..i + 8];
+        let end = i.checked_add(12 + len).filter(|&e| e <= b.len()).ok_or(PrivacyError::Malformed)?;
+        if !matches!(kind, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            out.extend_from_slice(&b[i..end]);
+        }
+        i = end;
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// WebP：剔除 EXIF、XMP 块并清除 VP8X 中对应标志位，重算 RIFF 长度；RIFF 长度之外的附加数据丢弃
+fn strip_webp(b: &[u8]) -> Result<Vec<u8>, PrivacyError> {
+    if b.len() < 12 || &b[0..4] != b"RIFF" || &b[8..12] != b"WEBP" {
+        return Err(PrivacyError::Malformed);
+    }
+    let riff_end = (u32::from_le_bytes([b[4], b[5], b[6], b[7]]) as usize).saturating_add(8).min(b.len());
+    let mut out = Vec::with_capacity(b.len());
+    out.extend_from_slice(&b[0..12]);
+    let mut i = 12;
+    while i + 8 <= riff_end {
+        let kind = &b[i..i + 4];
+        let len = u32::from_le_bytes([b[i + 4], b[i + 5], b[i + 6], b[i + 7]]) as usize;
+        let padded = len + (len & 1);
+        let end = (i + 8 + padded).min(riff_end);
+        if i + 8 + len > riff_end {
+            return Err(PrivacyError::Malformed);
+        }
+        match kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                let start = out.len();
+                out.extend_from_slice(&b[i..end]);
+                if len > 0 {
+                    // 标志位：0x08 EXIF、0x04 XMP
+                    out[start + 8] &= !(0x08 | 0x04);
+                }
+            }
+            _ => out.extend_from_slice(&b[i..end]),
+        }
+        i = end;
+    }
+    let riff_len = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Ok(out)
+}
+
+/// GIF：剔除注释扩展与 XMP 应用扩展，保留图像与其他扩展（如 NETSCAPE 循环）
+fn strip_gif(b: &[u8]) -> Result<Vec<u8>, PrivacyError> {
+    if b.len() < 13 || !(b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a")) {
+        return Err(PrivacyError::Malformed);
+    }
+    let color_table = |flags: u8| if flags & 0x80 != 0 { 3usize << ((flags & 0x07) + 1) } else { 0 };
+    let mut i = 13 + color_table(b[10]);
+    let mut out = Vec::with_capacity(b.len());
+    out.extend_from_slice(b.get(..i).ok_or(PrivacyError::Malformed)?);
+    // 跳过数据子块序列，返回结束位置
+    let sub_blocks = |mut j: usize| -> Result<usize, PrivacyError> {
+        loop {
+            let n = *b.get(j).ok_or(PrivacyError::Malformed)? as usize;
+            j += 1 + n;
+            if n == 0 {
+                return Ok(j);
+            }
+        }
+    };
+    loop {
+        match b.get(i).ok_or(PrivacyError::Malformed)? {
+            0x3B => {
+                out.push(0x3B);
+                return Ok(out);
+            }
+            0x21 => {
+                let label = *b.get(i + 1).ok_or(PrivacyError::Malformed)?;
+                let end = sub_blocks(i + 2)?;
+                let is_xmp = label == 0xFF && b.get(i + 3..i + 14) == Some(b"XMP DataXMP".as_slice());
+                if label != 0xFE && !is_xmp {
+                    out.extend_from_slice(b.get(i..end).ok_or(PrivacyError::Malformed)?);
+                }
+                i = end;
+            }
+            0x2C => {
+                let flags = *b.get(i + 9).ok_or(PrivacyError::Malformed)?;
+                // 描述符 10 字节 + 局部颜色表 + LZW 最小码长 1 字节 + 数据子块
+                let end = sub_blocks(i + 10 + color_table(flags) + 1)?;
+                out.extend_from_slice(b.get(i..end).ok_or(PrivacyError::Malformed)?);
+                i = end;
+            }
+            _ => return Err(PrivacyError::Malformed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    /// 元数据残留检查：EXIF、GPS、XMP、文本块标记均不得出现
+    fn assert_clean(out: &[u8]) {
+        for needle in [b"Exif".as_slice(), b"GPS", b"xmpmeta", b"secret comment", b"MPF"] {
+            assert!(!contains(out, needle), "残留 {:?}", String::from_utf8_lossy(needle));
+        }
+    }
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut seg = vec![0xFF, marker];
+        seg.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        seg.extend_from_slice(payload);
+        seg
+    }
+
+    fn exif_jpeg(scans: &[&[u8]]) -> (Vec<u8>, Vec<u8>) {
+        let jfif = jpeg_segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        let icc = jpeg_segment(0xE2, b"ICC_PROFILE\0\x01\x01profile");
+        let dqt = jpeg_segment(0xDB, &[0u8; 65]);
+        let dht = jpeg_segment(0xC4, &[0u8; 20]);
+        let sos = jpeg_segment(0xDA, &[1, 1, 0, 0, 63, 0]);
+        let mut file = vec![0xFF, 0xD8];
+        let mut expected = vec![0xFF, 0xD8];
+        file.extend(&jfif);
+        expected.extend(&jfif);
+        file.extend(jpeg_segment(0xE1, b"Exif\0\0MM\0*GPSLatitude 31.2304"));
+        file.extend(jpeg_segment(0xE1, b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>"));
+        file.extend(&icc);
+        expected.extend(&icc);
+        file.extend(jpeg_segment(0xE2, b"MPF\0II*\0index"));
+        file.extend(jpeg_segment(0xED, b"Photoshop 3.0\0IPTC"));
+        file.extend(jpeg_segment(0xFE, b"secret comment"));
+        for part in [&dqt, &dht] {
+            file.extend(part);
+            expected.extend(part);
+        }
+        for (n, scan) in scans.iter().enumerate() {
+            if n > 0 {
+                // 渐进式：扫描之间可再出现表段
+                file.extend(&dht);
+                expected.extend(&dht);
+            }
+            file.extend(&sos);
+            expected.extend(&sos);
+            file.extend(*scan);
+            expected.extend(*scan);
+        }
+        file.extend([0xFF, 0xD9]);
+        expected.extend([0xFF, 0xD9]);
+        (file, expected)
+    }
+
+    #[test]
+    fn jpeg_drops_metadata_segments() {
+        // 熵编码数据含 0xFF00 填充与 RST 标记，须原样保留
+        let (file, expected) = exif_jpeg(&[&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56]]);
+        let out = strip_metadata("image/jpeg", &file).unwrap();
+        assert_eq!(out, expected);
+        assert_clean(&out);
+        assert_eq!(strip_metadata("image/jpeg", &out).unwrap(), out);
+    }
+
+    #[test]
+    fn jpeg_progressive_scans_are_kept() {
+        let (file, expected) = exif_jpeg(&[&[0x01, 0x02], &[0x03, 0xFF, 0x00], &[0x04]]);
+        assert_eq!(strip_metadata("image/jpeg", &file).unwrap(), expected);
+    }
+
+    #[test]
+    fn jpeg_drops_appended_images() {
+        // 主图 EOI 之后附带的 MPF 副图有自己的 EXIF（GPS）
+        let (mut file, expected) = exif_jpeg(&[&[0x12, 0x34]]);
+        let (secondary, _) = exif_jpeg(&[&[0x56]]);
+        file.extend(&secondary);
+        file.extend(b"trailing GPS dump");
+        let out = strip_metadata("image/jpeg", &file).unwrap();
+        assert_eq!(out, expected);
+        assert_clean(&out);
+    }
+
+    #[test]
+    fn jpeg_without_eoi_is_malformed() {
+        let (mut file, _) = exif_jpeg(&[&[0x12, 0x34]]);
+        file.truncate(file.len() - 2);
+        assert_eq!(strip_metadata("image/jpeg", &file), Err(PrivacyError::Malformed));
+        assert_eq!(strip_metadata("image/jpeg", b"not a jpeg"), Err(PrivacyError::Malformed));
+    }
+
+    fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&[0, 0, 0, 0]);
+        chunk
+    }
+
+    #[test]
+    fn png_drops_text_exif_and_time_chunks() {
+        let ihdr = png_chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        let idat = png_chunk(b"IDAT", b"pixels");
+        let iend = png_chunk(b"IEND", b"");
+        let mut file = b"\x89PNG\r\n\x1a\n".to_vec();
+        file.extend(&ihdr);
+        file.extend(png_chunk(b"eXIf", b"MM\0*GPSLatitude"));
+        file.extend(png_chunk(b"tEXt", b"Comment\0secret comment"));
+        file.extend(png_chunk(b"zTXt", b"Raw profile\0\0compressed Exif"));
+        file.extend(png_chunk(b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x:xmpmeta/>"));
+        file.extend(png_chunk(b"tIME", &[7, 234, 1, 1, 0, 0, 0]));
+        file.extend(&idat);
+        file.extend(&iend);
+        file.extend(b"trailing Exif");
+
+        let out = strip_metadata("image/png", &file).unwrap();
+        let mut expected = b"\x89PNG\r\n\x1a\n".to_vec();
+        expected.extend(ihdr);
+        expected.extend(idat);
+        expected.extend(iend);
+        assert_eq!(out, expected);
+        assert_clean(&out);
+        assert_eq!(strip_metadata("image/png", &out).unwrap(), out);
+    }
+
+    fn riff_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = kind.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn riff(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&((body.len() + 4) as u32).to_le_bytes());
+        file.extend_from_slice(b"WEBP");
+        file.extend(body);
+        file
+    }
+
+    #[test]
+    fn webp_drops_exif_xmp_and_clears_flags() {
+        // VP8X 标志：0x10 alpha、0x08 EXIF、0x04 XMP
+        let vp8x = |flags: u8| riff_chunk(b"VP8X", &[flags, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let alph = riff_chunk(b"ALPH", b"alpha");
+        let vp8 = riff_chunk(b"VP8 ", b"odd-length frame");
+        let mut file = riff(&[
+            vp8x(0x10 | 0x08 | 0x04),
+            alph.clone(),
+            vp8.clone(),
+            riff_chunk(b"EXIF", b"MM\0*GPSLatitude"),
+            riff_chunk(b"XMP ", b"<x:xmpmeta/>"),
+        ]);
+        file.extend(b"trailing Exif");
+
+        let out = strip_metadata("image/webp", &file).unwrap();
+        assert_eq!(out, riff(&[vp8x(0x10), alph, vp8]));
+        assert_clean(&out);
+        assert_eq!(strip_metadata("image/webp", &out).unwrap(), out);
+    }
+
+    #[test]
+    fn gif_drops_comment_and_xmp_extensions() {
+        let header = b"GIF89a\x01\x00\x01\x00\x00\x00\x00".to_vec();
+        let netscape = b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00".to_vec();
+        let gce = b"\x21\xF9\x04\x00\x00\x00\x00\x00".to_vec();
+        let image = b"\x2C\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00".to_vec();
+        let mut file = header.clone();
+        file.extend(&netscape);
+        file.extend(b"\x21\xFE\x0Esecret comment\x00");
+        file.extend(b"\x21\xFF\x0BXMP DataXMP\x0C<x:xmpmeta/>\x00");
+        file.extend(&gce);
+        file.extend(&image);
+        file.push(0x3B);
+        file.extend(b"trailing Exif");
+
+        let out = strip_metadata("image/gif", &file).unwrap();
+        let expected = [header, netscape, gce, image, vec![0x3B]].concat();
+        assert_eq!(out, expected);
+        assert_clean(&out);
+        assert_eq!(strip_metadata("image/gif", &out).unwrap(), out);
+    }
+
+    #[test]
+    fn unsupported_types_are_rejected() {
+        assert_eq!(
+            strip_metadata("image/heic", b"...."),
+            Err(PrivacyError::Unsupported("image/heic".into()))
+        );
+        assert!(requires_redaction("image/heic"));
+        assert!(!requires_redaction("application/pdf"));
+    }
+}
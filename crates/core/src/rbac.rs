@@ -0,0 +1,347 @@
+//! 路由权限矩阵（01 §7「权限与角色（P0，必须写死）」、P0-2 RBAC 审计）：每条路由的允许角色与资源归属规则。
+//!
+//! 鉴权中间件按本表做角色校验（不在表内的路由默认需登录）；归属规则需加载资源，由各 handler 校验，拒绝时返回同一规则名。
+//! 权限矩阵文档由本表生成（`traveltrust-api --rbac-matrix`），不手工维护，保证审计看到的矩阵与代码一致。
+//! 高风险操作（裁决、风控参数与角色变更）标记 step_up：须当前会话 5 分钟内做过 TOTP 校验（见 totp）。
+//! 执行器（Executor）可读取争议与裁决，并登记链上托管与结算事件（对账进程，不要求 step-up）；裁决执行审批类路由落地时在本表登记并标记 step_up。
+
+use crate::types::UserRole;
+use Audience::{Authenticated, Public, Roles};
+
+/// 路由受众
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Audience {
+    /// 无需登录
+    Public,
+    /// 任意已登录角色
+    Authenticated,
+    /// 仅列出的角色
+    Roles(&'static [UserRole]),
+}
+
+impl Audience {
+    /// role 为 None 表示未登录
+    pub fn permits(&self, role: Option<&UserRole>) -> bool {
+        match (self, role) {
+            (Audience::Public, _) => true,
+            (_, None) => false,
+            (Audience::Authenticated, Some(_)) => true,
+            (Audience::Roles(roles), Some(role)) => roles.contains(role),
+        }
+    }
+
+    pub fn requires_login(&self) -> bool {
+        !matches!(self, Audience::Public)
+    }
+}
+
+/// 资源归属规则（handler 加载资源后校验）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership {
+    None,
+    /// 仅作用于令牌所属账号本身
+    Account,
+    /// 列表按当前用户过滤
+    ScopedToUser,
+    /// 导游资料的所有者
+    GuideOwner,
+    /// 订单游客或订单指派导游
+    OrderParty,
+    /// 订单游客
+    OrderTourist,
+    /// 订单指派导游
+    AssignedGuide,
+    /// 订单双方，或仲裁员、运营、合规类角色
+    OrderPartyOrStaff,
+    /// 订单双方、该订单争议的被指派仲裁员，或合规类角色（证据工单内可读，04 §四）
+    EvidenceReader,
+    /// 上传会话的发起人
+    UploadOwner,
+    /// 仲裁员仅限指派给本人的争议（其他允许角色不限）
+    AssignedArbitrator,
+    /// 不可作用于本人账号
+    NotSelf,
+}
+
+impl Ownership {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ownership::None => "none",
+            Ownership::Account => "account",
+            Ownership::ScopedToUser => "scoped_to_user",
+            Ownership::GuideOwner => "guide_owner",
+            Ownership::OrderParty => "order_party",
+            Ownership::OrderTourist => "order_tourist",
+            Ownership::AssignedGuide => "assigned_guide",
+            Ownership::OrderPartyOrStaff => "order_party_or_staff",
+            Ownership::EvidenceReader => "evidence_reader",
+            Ownership::UploadOwner => "upload_owner",
+            Ownership::AssignedArbitrator => "assigned_arbitrator",
+            Ownership::NotSelf => "not_self",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Ownership::None => "—",
+            Ownership::Account => "仅本人账号",
+            Ownership::ScopedToUser => "仅返回与本人相关的记录",
+            Ownership::GuideOwner => "导游资料所有者",
+            Ownership::OrderParty => "订单游客或指派导游",
+            Ownership::OrderTourist => "订单游客",
+            Ownership::AssignedGuide => "订单指派导游",
+            Ownership::OrderPartyOrStaff => "订单双方，或仲裁员/运营/合规/法务",
+            Ownership::EvidenceReader => "订单双方、该订单争议的被指派仲裁员，或合规/法务",
+            Ownership::UploadOwner => "上传会话发起人",
+            Ownership::AssignedArbitrator => "仲裁员仅限指派给本人的争议",
+            Ownership::NotSelf => "不可作用于本人账号",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RoutePolicy {
+    pub method: &'static str,
+    /// 路由模式，`:name` 为路径参数（与 axum 路由一致）
+    pub path: &'static str,
+    pub audience: Audience,
+    pub ownership: Ownership,
+    pub note: &'static str,
+    /// 需近期 TOTP 校验（step-up）
+    pub step_up: bool,
+}
+
+impl RoutePolicy {
+    pub fn route(&self) -> String {
+        format!("{} {}", self.method, self.path)
+    }
+
+    const fn with_step_up(mut self) -> Self {
+        self.step_up = true;
+        self
+    }
+}
+
+const TOURIST_OR_GUIDE: &[UserRole] = &[UserRole::Tourist, UserRole::Guide];
+const GUIDE: &[UserRole] = &[UserRole::Guide];
+const TOURIST: &[UserRole] = &[UserRole::Tourist];
+const ARBITRATOR: &[UserRole] = &[UserRole::Arbitrator];
+const OPERATOR: &[UserRole] = &[UserRole::Operator];
+const EXECUTOR: &[UserRole] = &[UserRole::Executor];
+const COMPLIANCE: &[UserRole] = &[UserRole::Compliance, UserRole::Legal];
+const OPERATOR_OR_COMPLIANCE: &[UserRole] = &[UserRole::Operator, UserRole::Compliance, UserRole::Legal];
+const ORDER_READERS: &[UserRole] = &[
+    UserRole::Tourist,
+    UserRole::Guide,
+    UserRole::Arbitrator,
+    UserRole::Operator,
+    UserRole::Compliance,
+    UserRole::Legal,
+];
+const EVIDENCE_READERS: &[UserRole] = &[
+    UserRole::Tourist,
+    UserRole::Guide,
+    UserRole::Arbitrator,
+    UserRole::Compliance,
+    UserRole::Legal,
+];
+const DISPUTE_READERS: &[UserRole] = &[UserRole::Arbitrator, UserRole::Operator, UserRole::Executor];
+
+const fn policy(
+    method: &'static str,
+    path: &'static str,
+    audience: Audience,
+    ownership: Ownership,
+    note: &'static str,
+) -> RoutePolicy {
+    RoutePolicy {
+        method,
+        path,
+        audience,
+        ownership,
+        note,
+        step_up: false,
+    }
+}
+
+/// 全部路由（与 main.rs 路由表一一对应；静态段在参数段之前）
+pub const ROUTE_POLICIES: &[RoutePolicy] = &[
+    policy("GET", "/health", Public, Ownership::None, "健康检查"),
+    policy("POST", "/auth/register", Public, Ownership::None, "邮箱注册，仅可自选游客/导游"),
+    policy("POST", "/auth/login", Public, Ownership::None, "邮箱密码登录"),
+    policy("POST", "/auth/refresh", Public, Ownership::None, "轮换刷新令牌"),
+    policy("POST", "/auth/verify-email", Public, Ownership::None, "邮箱验证令牌"),
+    policy("POST", "/auth/forgot-password", Public, Ownership::None, "申请密码重置"),
+    policy("POST", "/auth/reset-password", Public, Ownership::None, "重置令牌 + 新密码"),
+    policy("POST", "/auth/siwe/nonce", Public, Ownership::None, "SIWE 登录 nonce"),
+    policy("POST", "/auth/siwe/login", Public, Ownership::None, "钱包签名登录，未绑定地址新建游客账号"),
+    policy("POST", "/auth/logout", Authenticated, Ownership::Account, "吊销当前或全部会话"),
+    policy("GET", "/api/v1/me", Authenticated, Ownership::Account, "当前用户资料"),
+    policy("PUT", "/api/v1/me", Authenticated, Ownership::Account, "更新昵称、头像"),
+    policy("GET", "/api/v1/me/stats", Authenticated, Ownership::Account, "按角色统计摘要"),
+    policy("PUT", "/api/v1/me/password", Authenticated, Ownership::Account, "修改密码"),
+    policy("POST", "/api/v1/me/email", Authenticated, Ownership::Account, "钱包账号关联邮箱与密码，吊销全部会话"),
+    policy("PUT", "/api/v1/me/email", Authenticated, Ownership::Account, "更换邮箱（需当前密码），吊销全部会话"),
+    policy("POST", "/api/v1/me/wallet/challenge", Authenticated, Ownership::Account, "钱包绑定挑战（EIP-191 待签消息）"),
+    policy("PUT", "/api/v1/me/wallet", Authenticated, Ownership::Account, "签名证明后绑定/换绑钱包，有未终结事项不可换绑"),
+    policy("DELETE", "/api/v1/me/wallet", Authenticated, Ownership::Account, "解绑钱包，有未终结事项不可解绑"),
+    policy("GET", "/api/v1/me/wallets", Authenticated, Ownership::Account, "钱包绑定历史"),
+    policy("GET", "/api/v1/me/totp", Authenticated, Ownership::Account, "TOTP 启用状态与 step-up 有效期"),
+    policy("POST", "/api/v1/me/totp/enroll", Authenticated, Ownership::Account, "发起 TOTP 启用（密钥与 otpauth URI）"),
+    policy("POST", "/api/v1/me/totp/confirm", Authenticated, Ownership::Account, "验证码确认启用，下发恢复码"),
+    policy("POST", "/api/v1/me/totp/step-up", Authenticated, Ownership::Account, "TOTP 校验，当前会话获得 5 分钟 step-up"),
+    policy("POST", "/api/v1/me/totp/recovery-codes", Authenticated, Ownership::Account, "重新生成恢复码，旧码作废"),
+    policy("DELETE", "/api/v1/me/totp", Authenticated, Ownership::Account, "停用 TOTP，仲裁员/运营不可停用"),
+    policy("GET", "/api/v1/me/sessions", Authenticated, Ownership::Account, "登录会话与设备"),
+    policy("DELETE", "/api/v1/me/sessions", Authenticated, Ownership::Account, "退出所有设备（可保留当前会话）"),
+    policy("DELETE", "/api/v1/me/sessions/:id", Authenticated, Ownership::Account, "吊销本人的一个会话"),
+    policy("GET", "/api/v1/guides", Public, Ownership::None, "导游列表"),
+    policy("POST", "/api/v1/guides", Roles(TOURIST_OR_GUIDE), Ownership::Account, "导游注册，每账号一份资料"),
+    policy("GET", "/api/v1/guides/:id", Public, Ownership::None, "导游详情"),
+    policy("POST", "/api/v1/guides/:id/stake", Roles(GUIDE), Ownership::GuideOwner, "质押"),
+    policy("GET", "/api/v1/orders", Authenticated, Ownership::ScopedToUser, "我的订单"),
+    policy("POST", "/api/v1/orders", Roles(TOURIST_OR_GUIDE), Ownership::Account, "下单，游客为当前账号，须附绑定钱包的 EIP-712 签名"),
+    policy("GET", "/api/v1/orders/:id", Roles(ORDER_READERS), Ownership::OrderPartyOrStaff, "订单详情"),
+    policy("POST", "/api/v1/orders/:id/accept", Roles(GUIDE), Ownership::AssignedGuide, "导游接单，须附绑定钱包的 EIP-712 签名"),
+    policy("GET", "/api/v1/signing/eip712", Public, Ownership::None, "订单签名域与类型定义"),
+    policy("POST", "/api/v1/orders/:id/cancel", Roles(TOURIST_OR_GUIDE), Ownership::OrderParty, "取消订单"),
+    policy("POST", "/api/v1/orders/:id/confirm-completion", Roles(TOURIST), Ownership::OrderTourist, "确认完成"),
+    policy("GET", "/api/v1/orders/:id/reviews", Authenticated, Ownership::None, "订单评价"),
+    policy("POST", "/api/v1/orders/:id/reviews", Roles(TOURIST_OR_GUIDE), Ownership::OrderParty, "提交评价"),
+    policy("GET", "/api/v1/orders/:id/evidence", Roles(EVIDENCE_READERS), Ownership::EvidenceReader, "证据列表，逐条审计"),
+    policy("POST", "/api/v1/orders/:id/evidence", Roles(TOURIST_OR_GUIDE), Ownership::OrderParty, "上传证据"),
+    policy("POST", "/api/v1/orders/:id/evidence/uploads", Roles(TOURIST_OR_GUIDE), Ownership::OrderParty, "创建分片上传"),
+    policy("GET", "/api/v1/orders/:id/evidence/uploads/:upload_id", Roles(TOURIST_OR_GUIDE), Ownership::UploadOwner, "上传进度"),
+    policy(
+        "PUT",
+        "/api/v1/orders/:id/evidence/uploads/:upload_id/chunks/:index",
+        Roles(TOURIST_OR_GUIDE),
+        Ownership::UploadOwner,
+        "上传分片",
+    ),
+    policy(
+        "POST",
+        "/api/v1/orders/:id/evidence/uploads/:upload_id/complete",
+        Roles(TOURIST_OR_GUIDE),
+        Ownership::UploadOwner,
+        "完成分片上传",
+    ),
+    policy("POST", "/api/v1/orders/:id/dispute", Roles(TOURIST_OR_GUIDE), Ownership::OrderParty, "发起争议"),
+    policy("GET", "/api/v1/orders/:id/evidence/:evidence_id/url", Roles(EVIDENCE_READERS), Ownership::EvidenceReader, "签发下载链接"),
+    policy("GET", "/api/v1/evidence/receipt-keys", Public, Ownership::None, "回执验签公钥"),
+    policy("GET", "/api/v1/evidence/:evidence_id/download", Roles(EVIDENCE_READERS), Ownership::EvidenceReader, "签名链接下载"),
+    policy("GET", "/api/v1/disputes", Roles(DISPUTE_READERS), Ownership::AssignedArbitrator, "争议列表，仲裁员按指派过滤"),
+    policy("GET", "/api/v1/disputes/:id", Roles(DISPUTE_READERS), Ownership::AssignedArbitrator, "争议详情"),
+    policy("POST", "/api/v1/disputes/:id/resolve", Roles(ARBITRATOR), Ownership::AssignedArbitrator, "裁决").with_step_up(),
+    policy("POST", "/api/v1/timestamps", Authenticated, Ownership::None, "时间戳盖章"),
+    policy("GET", "/api/v1/timestamps/keys", Public, Ownership::None, "TSA 验签公钥"),
+    policy("GET", "/api/v1/anchors", Authenticated, Ownership::None, "锚定批次"),
+    policy("GET", "/api/v1/anchors/proof", Authenticated, Ownership::None, "包含证明"),
+    policy("GET", "/api/v1/anchors/tx/:tx_hash", Authenticated, Ownership::None, "链上锚定根"),
+    policy("GET", "/api/v1/ops/freezes", Roles(OPERATOR), Ownership::None, "类别冻结列表"),
+    policy("POST", "/api/v1/ops/freezes/:id/lift", Roles(OPERATOR), Ownership::None, "解除类别冻结").with_step_up(),
+    policy("GET", "/api/v1/ops/arbitrator-freezes", Roles(OPERATOR), Ownership::None, "裁决偏向冻结的仲裁员"),
+    policy("POST", "/api/v1/ops/arbitrator-freezes/:id/lift", Roles(OPERATOR), Ownership::None, "解除仲裁员冻结").with_step_up(),
+    policy("POST", "/api/v1/ops/disputes/:id/assign", Roles(OPERATOR), Ownership::None, "指派或改派仲裁员").with_step_up(),
+    policy("POST", "/api/v1/ops/orders/:id/escrow", Roles(EXECUTOR), Ownership::None, "登记链上托管锁定"),
+    policy("POST", "/api/v1/ops/orders/:id/settlement", Roles(EXECUTOR), Ownership::None, "登记链上放款/退款结果"),
+    policy("GET", "/api/v1/ops/second-reviews", Roles(OPERATOR), Ownership::None, "连续同向裁决的强制复核"),
+    policy("GET", "/api/v1/ops/dispute-capacity", Roles(OPERATOR), Ownership::None, "争议容量"),
+    policy("PUT", "/api/v1/ops/dispute-capacity", Roles(OPERATOR), Ownership::None, "维护在岗仲裁员数").with_step_up(),
+    policy("GET", "/api/v1/ops/sla", Roles(OPERATOR), Ownership::None, "SLA 时钟与告警"),
+    policy("POST", "/api/v1/ops/stablecoin-freeze", Roles(OPERATOR), Ownership::None, "登记稳定币冻结").with_step_up(),
+    policy("GET", "/api/v1/ops/evidence-access-log/export", Roles(COMPLIANCE), Ownership::None, "导出证据访问日志"),
+    policy("GET", "/api/v1/ops/security-log/export", Roles(COMPLIANCE), Ownership::None, "导出账号安全事件日志"),
+    policy("GET", "/api/v1/ops/legal-holds", Roles(OPERATOR_OR_COMPLIANCE), Ownership::None, "法律保全列表"),
+    policy("POST", "/api/v1/ops/legal-holds", Roles(COMPLIANCE), Ownership::None, "人工保全"),
+    policy("POST", "/api/v1/ops/legal-holds/:id/release", Roles(COMPLIANCE), Ownership::None, "解除人工保全"),
+    policy("GET", "/api/v1/ops/evidence-deletions", Roles(OPERATOR_OR_COMPLIANCE), Ownership::None, "原文件删除记录"),
+    policy("GET", "/api/v1/ops/evidence-storage", Roles(OPERATOR), Ownership::None, "证据存储健康"),
+    policy("POST", "/api/v1/ops/anchors/flush", Roles(OPERATOR), Ownership::None, "立即封批上链"),
+    policy("GET", "/api/v1/ops/sybil-signals", Roles(OPERATOR), Ownership::None, "同 IP/设备多账号关联线索"),
+    policy("PUT", "/api/v1/ops/users/:id/role", Roles(OPERATOR), Ownership::NotSelf, "指派账号角色").with_step_up(),
+];
+
+/// 路由模式匹配：段数一致，`:name` 段匹配任意非空段
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern_segs = pattern.split('/');
+    let mut path_segs = path.split('/');
+    loop {
+        match (pattern_segs.next(), path_segs.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s)) if p.starts_with(':') && !s.is_empty() => {}
+            (Some(p), Some(s)) if p == s => {}
+            _ => return false,
+        }
+    }
+}
+
+/// 按请求方法与路径查找策略
+pub fn route_policy(method: &str, path: &str) -> Option<&'static RoutePolicy> {
+    ROUTE_POLICIES
+        .iter()
+        .find(|p| p.method.eq_ignore_ascii_case(method) && path_matches(p.path, path))
+}
+
+pub fn role_label(role: &UserRole) -> &'static str {
+    match role {
+        UserRole::Tourist => "游客",
+        UserRole::Guide => "导游",
+        UserRole::Arbitrator => "仲裁员",
+        UserRole::Operator => "运营",
+        UserRole::Executor => "执行器",
+        UserRole::Compliance => "合规",
+        UserRole::Legal => "法务",
+    }
+}
+
+fn audience_label(audience: &Audience) -> String {
+    match audience {
+        Audience::Public => "公开".to_string(),
+        Audience::Authenticated => "任意已登录".to_string(),
+        Audience::Roles(roles) => roles.iter().map(role_label).collect::<Vec<_>>().join("、"),
+    }
+}
+
+/// 生成路由权限矩阵（Markdown），供 01 §7 RBAC 审计留档
+pub fn render_matrix() -> String {
+    let mut out = String::new();
+    out.push_str("# 路由权限矩阵（RBAC）\n\n");
+    out.push_str("> 本文件由 `traveltrust-api --rbac-matrix` 从 `crates/core/src/rbac.rs` 生成，请勿手工编辑；");
+    out.push_str("路由或权限变更后重新生成并随代码提交。对应 [01-总库总览](01-总库总览.md) §7 权限与角色（P0-2 RBAC 审计）。\n\n");
+    out.push_str("- **允许角色**：不在列表内的已登录角色返回 403 `forbidden_role`；需登录路由未登录返回 401 `unauthenticated`。\n");
+    out.push_str("- **归属规则**：handler 加载资源后校验，不满足返回 403 `forbidden_owner`（detail.rule 为规则名）。\n");
+    out.push_str("- **TOTP**：仲裁员、运营未启用 TOTP 时访问角色专属路由返回 403 `totp_enrollment_required`；");
+    out.push_str("标记 step-up 的路由须当前会话 5 分钟内通过 TOTP 校验，否则返回 403 `step_up_required`。\n\n");
+    out.push_str("| 方法 | 路径 | 允许角色 | 归属规则 | step-up | 说明 |\n");
+    out.push_str("|------|------|----------|----------|---------|------|\n");
+    for p in ROUTE_POLICIES {
+        let ownership = match p.ownership {
+            Ownership::None => "—".to_string(),
+            o => format!("`{}` {}", o.as_str(), o.description()),
+        };
+        out.push_str(&format!(
+            "| {} | `{}` | {} | {} | {} | {} |\n",
+            p.method,
+            p.path,
+            audience_label(&p.audience),
+            ownership,
+            if p.step_up { "需要" } else { "—" },
+            p.note
+        ));
+    }
+    out.push_str("\n## 角色 × 路由数\n\n| 角色 | 可访问路由数（含公开） |\n|------|------|\n");
+    for role in [
+        UserRole::Tourist,
+        UserRole::Guide,
+        UserRole::Arbitrator,
+        UserRole::Operator,
+        UserRole::Executor,
+        UserRole::Compliance,
+        UserRole::Legal,
+    ] {
+        let count = ROUTE_POLICIES.iter().filter(|p| p.audience.permits(Some(&role))).count();
+        out.push_str(&format!("| {}（`{}`） | {} |\n", role_label(&role), role.as_str(), count));
+    }
+    out
+}
@@ -0,0 +1,133 @@
+//! 证据保留与法律保全（08-3 evidenceRetentionDays=1095、Runbook §5「争议期间保全冻结」、Runbook §9 证据删除后司法协助）
+//!
+//! 原文件在保留期满且无生效保全时才可删除；hash、回执与元数据永久保留。
+//! 争议开启即自动对订单加保全，争议裁决后释放；运营可另加人工保全（诉讼、司法协助）。
+//! 每次删除生成 DeletionRecord，供 Runbook §9 证明文件曾存在（对照回执 sha256）。
+
+use crate::Evidence;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 08-3 evidenceRetentionDays
+pub const EVIDENCE_RETENTION_DAYS: i64 = 1095;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum HoldReason {
+    /// 争议开启自动保全，裁决后释放
+    DisputeOpen { dispute_id: Uuid },
+    /// 运营人工保全
+    Manual { note: String },
+}
+
+/// 法律保全：覆盖订单下全部证据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub reason: HoldReason,
+    pub placed_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub released_by: Option<String>,
+}
+
+impl LegalHold {
+    pub fn is_active(&self) -> bool {
+        self.released_at.is_none()
+    }
+}
+
+/// 保全登记表：释放后记录保留用于审计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LegalHoldRegistry {
+    holds: Vec<LegalHold>,
+}
+
+impl LegalHoldRegistry {
+    pub fn place(&mut self, order_id: Uuid, reason: HoldReason, now: DateTime<Utc>) -> &LegalHold {
+        if let Some(idx) = self
+            .holds
+            .iter()
+            .position(|h| h.is_active() && h.order_id == order_id && h.reason == reason)
+        {
+            return &self.holds[idx];
+        }
+        self.holds.push(LegalHold {
+            id: Uuid::new_v4(),
+            order_id,
+            reason,
+            placed_at: now,
+            released_at: None,
+            released_by: None,
+        });
+        &self.holds[self.holds.len() - 1]
+    }
+
+    /// 释放人工保全；争议保全只能经 release_dispute 随裁决释放。id 不存在、已释放或非人工保全返回 None
+    pub fn release(&mut self, id: Uuid, by: &str, now: DateTime<Utc>) -> Option<&LegalHold> {
+        let hold = self
+            .holds
+            .iter_mut()
+            .find(|h| h.id == id && h.is_active() && matches!(h.reason, HoldReason::Manual { .. }))?;
+        hold.released_at = Some(now);
+        hold.released_by = Some(by.to_string());
+        Some(hold)
+    }
+
+    /// 争议裁决后释放其自动保全
+    pub fn release_dispute(&mut self, dispute_id: Uuid, now: DateTime<Utc>) {
+        for hold in self.holds.iter_mut().filter(|h| h.is_active()) {
+            if hold.reason == (HoldReason::DisputeOpen { dispute_id }) {
+                hold.released_at = Some(now);
+                hold.released_by = Some("dispute_resolved".to_string());
+            }
+        }
+    }
+
+    pub fn is_held(&self, order_id: Uuid) -> bool {
+        self.holds.iter().any(|h| h.is_active() && h.order_id == order_id)
+    }
+
+    pub fn all(&self) -> &[LegalHold] {
+        &self.holds
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub retention_days: i64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: EVIDENCE_RETENTION_DAYS,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn expires_at(&self, evidence: &Evidence) -> DateTime<Utc> {
+        evidence.uploaded_at + Duration::days(self.retention_days)
+    }
+
+    /// 原文件是否可删除：未删除、保留期满、订单无生效保全
+    pub fn is_deletable(&self, evidence: &Evidence, holds: &LegalHoldRegistry, now: DateTime<Utc>) -> bool {
+        evidence.deleted_at.is_none() && now >= self.expires_at(evidence) && !holds.is_held(evidence.order_id)
+    }
+}
+
+/// 原文件删除记录（只删原文件，元数据与回执保留）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletionRecord {
+    pub evidence_id: Uuid,
+    pub sha256: String,
+    pub order_id: Uuid,
+    pub size_bytes: u64,
+    pub uploaded_at: DateTime<Utc>,
+    pub retention_expired_at: DateTime<Utc>,
+    pub deleted_at: DateTime<Utc>,
+    /// 同 hash 原文件仍被未到期证据引用时只登记删除、不删除文件
+    pub file_removed: bool,
+}
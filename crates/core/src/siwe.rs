@@ -0,0 +1,337 @@
+//! Sign-In with Ethereum（EIP-4361；01 §7「钱包（P1）：SIWE」）：DApp 用户以钱包签名登录，无需邮箱密码。
+//!
+//! 服务端签发一次性 nonce（10 分钟），客户端按 EIP-4361 格式构造消息并以 personal_sign 签名；服务端解析消息，
+//! 校验 domain、Chain ID、Version、Issued At / Expiration Time / Not Before 与 nonce（未签发、已用或过期即拒绝），
+//! 再恢复签名地址须等于消息中的地址。地址须为 EIP-55 校验和形式（规范要求）。
+//! 地址与账号的对应沿用钱包绑定（wallet::WalletRegistry），登录后的会话与邮箱登录走同一令牌体系。
+
+use crate::wallet;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// nonce 有效期
+pub const SIWE_NONCE_TTL_MINUTES: i64 = 10;
+/// Issued At 允许的时钟偏差
+pub const SIWE_CLOCK_SKEW_SECS: i64 = 300;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SiweError {
+    #[error("SIWE 消息格式无效：{0}")]
+    Malformed(&'static str),
+    #[error("SIWE 消息 domain 与本站不一致")]
+    DomainMismatch,
+    #[error("SIWE 消息 Chain ID 与本站不一致")]
+    ChainIdMismatch,
+    #[error("SIWE 消息版本不受支持（仅 1）")]
+    UnsupportedVersion,
+    #[error("SIWE 消息已过期")]
+    Expired,
+    #[error("SIWE 消息尚未生效")]
+    NotYetValid,
+    #[error("SIWE 消息签发时间晚于当前时间")]
+    IssuedInFuture,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+    pub domain: String,
+    /// 签名地址（小写 0x）
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+fn parse_time(value: &str, field: &'static str) -> Result<DateTime<Utc>, SiweError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| SiweError::Malformed(field))
+}
+
+/// 按顺序逐行读取「Key: value」字段
+struct Lines<'a> {
+    lines: std::iter::Peekable<std::str::Split<'a, char>>,
+}
+
+impl<'a> Lines<'a> {
+    fn required(&mut self, key: &str, field: &'static str) -> Result<&'a str, SiweError> {
+        self.optional(key).ok_or(SiweError::Malformed(field))
+    }
+
+    fn optional(&mut self, key: &str) -> Option<&'a str> {
+        let value = self.lines.peek()?.strip_prefix(key)?.strip_prefix(": ")?;
+        self.lines.next();
+        Some(value)
+    }
+}
+
+impl SiweMessage {
+    pub fn parse(message: &str) -> Result<Self, SiweError> {
+        let mut lines = Lines {
+            lines: message.split('\n').peekable(),
+        };
+        let header = lines.lines.next().ok_or(SiweError::Malformed("header"))?;
+        let domain = header
+            .strip_suffix(HEADER_SUFFIX)
+            .filter(|d| !d.is_empty() && !d.contains(char::is_whitespace))
+            .ok_or(SiweError::Malformed("header"))?;
+        let raw_address = lines.lines.next().ok_or(SiweError::Malformed("address"))?;
+        let address = wallet::normalize_address(raw_address).map_err(|_| SiweError::Malformed("address"))?;
+        if wallet::checksum_address(&address).ok().as_deref() != Some(raw_address) {
+            return Err(SiweError::Malformed("address 须为 EIP-55 校验和形式"));
+        }
+        if lines.lines.next() != Some("") {
+            return Err(SiweError::Malformed("address 后须空一行"));
+        }
+        // 可选 statement：单行，前后各空一行；省略时按 ABNF 为两个空行，亦兼容只空一行的实现
+        let statement = match lines.lines.peek() {
+            Some(line) if line.starts_with("URI: ") => None,
+            Some(&"") => {
+                lines.lines.next();
+                None
+            }
+            Some(_) => {
+                let statement = lines.lines.next().map(str::to_string);
+                if lines.lines.next() != Some("") {
+                    return Err(SiweError::Malformed("statement 后须空一行"));
+                }
+                statement
+            }
+            None => None,
+        };
+        let uri = lines.required("URI", "URI")?.to_string();
+        let version = lines.required("Version", "Version")?.to_string();
+        let chain_id = lines
+            .required("Chain ID", "Chain ID")?
+            .parse()
+            .map_err(|_| SiweError::Malformed("Chain ID"))?;
+        let nonce = lines.required("Nonce", "Nonce")?.to_string();
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SiweError::Malformed("Nonce"));
+        }
+        let issued_at = parse_time(lines.required("Issued At", "Issued At")?, "Issued At")?;
+        let expiration_time = lines
+            .optional("Expiration Time")
+            .map(|v| parse_time(v, "Expiration Time"))
+            .transpose()?;
+        let not_before = lines.optional("Not Before").map(|v| parse_time(v, "Not Before")).transpose()?;
+        let request_id = lines.optional("Request ID").map(str::to_string);
+        let mut resources = Vec::new();
+        if lines.lines.peek() == Some(&"Resources:") {
+            lines.lines.next();
+            while let Some(resource) = lines.lines.peek().and_then(|l| l.strip_prefix("- ")) {
+                resources.push(resource.to_string());
+                lines.lines.next();
+            }
+        }
+        if lines.lines.any(|l| !l.is_empty()) {
+            return Err(SiweError::Malformed("存在多余或乱序的字段"));
+        }
+        Ok(Self {
+            domain: domain.to_string(),
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+
+    /// domain、Chain ID、版本与时间窗口；nonce 由 SiweNonces 核销
+    pub fn validate(&self, domain: &str, chain_id: u64, now: DateTime<Utc>) -> Result<(), SiweError> {
+        if !self.domain.eq_ignore_ascii_case(domain) {
+            return Err(SiweError::DomainMismatch);
+        }
+        if self.chain_id != chain_id {
+            return Err(SiweError::ChainIdMismatch);
+        }
+        if self.version != "1" {
+            return Err(SiweError::UnsupportedVersion);
+        }
+        if self.issued_at > now + Duration::seconds(SIWE_CLOCK_SKEW_SECS) {
+            return Err(SiweError::IssuedInFuture);
+        }
+        if self.expiration_time.is_some_and(|t| now >= t) {
+            return Err(SiweError::Expired);
+        }
+        if self.not_before.is_some_and(|t| now < t) {
+            return Err(SiweError::NotYetValid);
+        }
+        Ok(())
+    }
+}
+
+/// 已签发未使用的 nonce
+#[derive(Debug, Default)]
+pub struct SiweNonces {
+    issued: HashMap<String, DateTime<Utc>>,
+}
+
+impl SiweNonces {
+    /// 登记 nonce，返回过期时间
+    pub fn issue(&mut self, nonce: String, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.issued.retain(|_, expires_at| now < *expires_at);
+        let expires_at = now + Duration::minutes(SIWE_NONCE_TTL_MINUTES);
+        self.issued.insert(nonce, expires_at);
+        expires_at
+    }
+
+    /// 核销：须已签发、未用、未过期；无论成败均移除
+    pub fn consume(&mut self, nonce: &str, now: DateTime<Utc>) -> bool {
+        self.issued.remove(nonce).is_some_and(|expires_at| now < expires_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EIP-4361 规范示例消息
+    const SPEC_EXAMPLE: &str = "service.invalid wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+I accept the ServiceOrg Terms of Service: https://service.invalid/tos
+
+URI: https://service.invalid/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    const ADDRESS: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    fn message(statement: Option<&str>, fields: &str) -> String {
+        let statement = statement.map(|s| format!("{}\n", s)).unwrap_or_default();
+        format!(
+            "traveltrust.example wants you to sign in with your Ethereum account:\n{}\n\n{}\n{}",
+            ADDRESS, statement, fields
+        )
+    }
+
+    const FIELDS: &str = "URI: https://traveltrust.example/login\nVersion: 1\nChain ID: 31337\nNonce: abcdef1234\nIssued At: 2026-01-01T00:00:00Z";
+
+    #[test]
+    fn parses_spec_example() {
+        let msg = SiweMessage::parse(SPEC_EXAMPLE).unwrap();
+        assert_eq!(msg.domain, "service.invalid");
+        assert_eq!(msg.address, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        assert_eq!(
+            msg.statement.as_deref(),
+            Some("I accept the ServiceOrg Terms of Service: https://service.invalid/tos")
+        );
+        assert_eq!(msg.uri, "https://service.invalid/login");
+        assert_eq!(msg.version, "1");
+        assert_eq!(msg.chain_id, 1);
+        assert_eq!(msg.nonce, "32891756");
+        assert_eq!(msg.issued_at, parse_time("2021-09-30T16:25:24Z", "Issued At").unwrap());
+        assert_eq!(msg.expiration_time, None);
+        assert_eq!(
+            msg.resources,
+            vec![
+                "ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/".to_string(),
+                "https://example.com/my-web2-claim.json".to_string(),
+            ]
+        );
+        assert_eq!(msg.validate("service.invalid", 1, msg.issued_at), Ok(()));
+        assert_eq!(msg.validate("evil.invalid", 1, msg.issued_at), Err(SiweError::DomainMismatch));
+        assert_eq!(msg.validate("service.invalid", 5, msg.issued_at), Err(SiweError::ChainIdMismatch));
+    }
+
+    #[test]
+    fn statement_is_optional() {
+        let with = SiweMessage::parse(&message(Some("Sign in to TravelTrust"), FIELDS)).unwrap();
+        assert_eq!(with.statement.as_deref(), Some("Sign in to TravelTrust"));
+
+        // ABNF：省略 statement 时 address 后两个空行
+        let without = SiweMessage::parse(&message(None, FIELDS)).unwrap();
+        assert_eq!(without.statement, None);
+        assert_eq!(without.uri, "https://traveltrust.example/login");
+
+        // 只空一行的实现
+        let compact = format!(
+            "traveltrust.example wants you to sign in with your Ethereum account:\n{}\n\n{}",
+            ADDRESS, FIELDS
+        );
+        assert_eq!(SiweMessage::parse(&compact).unwrap(), without);
+    }
+
+    #[test]
+    fn parses_optional_fields_and_resources() {
+        let fields = format!(
+            "{}\nExpiration Time: 2026-01-01T00:10:00Z\nNot Before: 2026-01-01T00:00:00Z\nRequest ID: req-1\nResources:\n- https://traveltrust.example/a\n- ipfs://b",
+            FIELDS
+        );
+        let msg = SiweMessage::parse(&message(None, &fields)).unwrap();
+        assert_eq!(msg.request_id.as_deref(), Some("req-1"));
+        assert_eq!(msg.resources, vec!["https://traveltrust.example/a".to_string(), "ipfs://b".to_string()]);
+        let expires = msg.expiration_time.unwrap();
+        assert_eq!(msg.validate("traveltrust.example", 31337, expires - Duration::seconds(1)), Ok(()));
+        assert_eq!(msg.validate("traveltrust.example", 31337, expires), Err(SiweError::Expired));
+        assert_eq!(
+            msg.validate("traveltrust.example", 31337, msg.issued_at - Duration::seconds(1)),
+            Err(SiweError::NotYetValid)
+        );
+    }
+
+    #[test]
+    fn rejects_non_checksummed_address() {
+        let lower = message(None, FIELDS).replace(ADDRESS, &ADDRESS.to_ascii_lowercase());
+        assert!(matches!(SiweMessage::parse(&lower), Err(SiweError::Malformed(_))));
+        let upper = message(None, FIELDS).replace(ADDRESS, &format!("0x{}", ADDRESS[2..].to_ascii_uppercase()));
+        assert!(matches!(SiweMessage::parse(&upper), Err(SiweError::Malformed(_))));
+    }
+
+    #[test]
+    fn rejects_extra_or_out_of_order_fields() {
+        let extra = message(None, &format!("{}\nFoo: bar", FIELDS));
+        assert!(matches!(SiweMessage::parse(&extra), Err(SiweError::Malformed(_))));
+
+        let swapped = message(
+            None,
+            "URI: https://traveltrust.example/login\nChain ID: 31337\nVersion: 1\nNonce: abcdef1234\nIssued At: 2026-01-01T00:00:00Z",
+        );
+        assert!(matches!(SiweMessage::parse(&swapped), Err(SiweError::Malformed(_))));
+
+        let late_request_id = message(
+            None,
+            &format!("{}\nRequest ID: req-1\nExpiration Time: 2026-01-01T00:10:00Z", FIELDS),
+        );
+        assert!(matches!(SiweMessage::parse(&late_request_id), Err(SiweError::Malformed(_))));
+
+        let missing_nonce = message(
+            None,
+            "URI: https://traveltrust.example/login\nVersion: 1\nChain ID: 31337\nIssued At: 2026-01-01T00:00:00Z",
+        );
+        assert_eq!(SiweMessage::parse(&missing_nonce), Err(SiweError::Malformed("Nonce")));
+    }
+
+    #[test]
+    fn nonce_is_single_use() {
+        let now = Utc::now();
+        let mut nonces = SiweNonces::default();
+        nonces.issue("abcdef1234".into(), now);
+        assert!(nonces.consume("abcdef1234", now));
+        assert!(!nonces.consume("abcdef1234", now));
+        nonces.issue("expired123".into(), now);
+        assert!(!nonces.consume("expired123", now + Duration::minutes(SIWE_NONCE_TTL_MINUTES)));
+    }
+}
@@ -0,0 +1,236 @@
+//! 争议 SLA 计时（08-3 freezeDisputePolicy「SLA 暂停计算」、Runbook §11「SLA 暂停滥用」）
+//!
+//! 每个争议一只时钟：按阶段（Open / Assigned）累计有效时长；稳定币冻结期间暂停计时。
+//! 防拖延：单争议累计暂停时长与暂停次数封顶，超出部分照常计时并告警；有效时长超过目标裁决时长即告警违约。
+
+use crate::{Dispute, DisputeStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// SLA 策略（暂停上限由风控定稿写死 Runbook §11）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaPolicy {
+    /// 目标裁决时长（秒，有效计时，不含暂停）；由争议容量模式决定（capacity::CapacityController::sla）
+    pub target_secs: i64,
+    /// 单争议累计可暂停时长上限（秒）
+    pub max_paused_secs: i64,
+    /// 单争议可暂停次数上限
+    pub max_pauses: u32,
+}
+
+impl SlaPolicy {
+    pub fn with_target(target: Duration) -> Self {
+        Self {
+            target_secs: target.num_seconds(),
+            max_paused_secs: Duration::days(14).num_seconds(),
+            max_pauses: 3,
+        }
+    }
+
+    pub fn target(&self) -> Duration {
+        Duration::seconds(self.target_secs)
+    }
+
+    pub fn max_paused(&self) -> Duration {
+        Duration::seconds(self.max_paused_secs)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SlaError {
+    #[error("争议已裁决，SLA 计时已结束")]
+    Stopped,
+    #[error("SLA 计时已处于暂停")]
+    AlreadyPaused,
+    #[error("SLA 计时未暂停")]
+    NotPaused,
+    #[error("暂停次数已达上限 {0}")]
+    PauseLimitReached(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum SlaAlertKind {
+    /// 有效计时超过目标裁决时长
+    Breached { overdue_secs: i64 },
+    /// 暂停次数达上限后仍有冻结事件，拒绝暂停（疑似拖延）
+    PauseCountExceeded { pauses: u32 },
+    /// 累计暂停时长达上限，冻结期间恢复计时
+    PauseTimeCapReached { paused_secs: i64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaAlert {
+    pub dispute_id: Uuid,
+    pub kind: SlaAlertKind,
+    pub at: DateTime<Utc>,
+}
+
+/// 单阶段累计有效时长
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageTime {
+    pub stage: DisputeStatus,
+    pub active_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaClock {
+    pub dispute_id: Uuid,
+    pub policy: SlaPolicy,
+    pub started_at: DateTime<Utc>,
+    pub stage: DisputeStatus,
+    pub stages: Vec<StageTime>,
+    pub paused_since: Option<DateTime<Utc>>,
+    pub paused_total_secs: i64,
+    pub pause_count: u32,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub alerts: Vec<SlaAlert>,
+    last_tick: DateTime<Utc>,
+}
+
+impl SlaClock {
+    /// 从争议创建时刻开始计时
+    pub fn start(dispute: &Dispute, policy: SlaPolicy) -> Self {
+        Self {
+            dispute_id: dispute.id,
+            policy,
+            started_at: dispute.created_at,
+            stage: dispute.status.clone(),
+            stages: vec![StageTime {
+                stage: dispute.status.clone(),
+                active_secs: 0,
+            }],
+            paused_since: None,
+            paused_total_secs: 0,
+            pause_count: 0,
+            stopped_at: None,
+            alerts: Vec::new(),
+            last_tick: dispute.created_at,
+        }
+    }
+
+    /// 推进计时到 now：暂停中计入暂停时长（超上限部分计入有效时长），否则计入当前阶段
+    pub fn advance(&mut self, now: DateTime<Utc>) {
+        if self.stopped_at.is_some() || now <= self.last_tick {
+            return;
+        }
+        let delta = now - self.last_tick;
+        self.last_tick = now;
+        let active = if self.paused_since.is_some() {
+            let allowance = (self.policy.max_paused() - self.paused_total()).max(Duration::zero());
+            let paused = delta.min(allowance);
+            self.paused_total_secs += paused.num_seconds();
+            if paused < delta && allowance > Duration::zero() {
+                self.push_alert(
+                    SlaAlertKind::PauseTimeCapReached {
+                        paused_secs: self.paused_total_secs,
+                    },
+                    now,
+                );
+            }
+            delta - paused
+        } else {
+            delta
+        };
+        if let Some(current) = self.stages.last_mut() {
+            current.active_secs += active.num_seconds();
+        }
+        self.check_breach(now);
+    }
+
+    /// 争议状态变化：进入新阶段；Resolved 时停止计时
+    pub fn enter_stage(&mut self, stage: DisputeStatus, now: DateTime<Utc>) {
+        self.advance(now);
+        if self.stopped_at.is_some() || stage == self.stage {
+            return;
+        }
+        if stage == DisputeStatus::Resolved {
+            self.paused_since = None;
+            self.stopped_at = Some(now);
+        } else {
+            self.stages.push(StageTime {
+                stage: stage.clone(),
+                active_secs: 0,
+            });
+        }
+        self.stage = stage;
+    }
+
+    /// 冻结事件开始：暂停计时；次数达上限时拒绝并告警，计时继续
+    pub fn pause(&mut self, now: DateTime<Utc>) -> Result<(), SlaError> {
+        self.advance(now);
+        if self.stopped_at.is_some() {
+            return Err(SlaError::Stopped);
+        }
+        if self.paused_since.is_some() {
+            return Err(SlaError::AlreadyPaused);
+        }
+        if self.pause_count >= self.policy.max_pauses {
+            self.push_alert(
+                SlaAlertKind::PauseCountExceeded {
+                    pauses: self.pause_count,
+                },
+                now,
+            );
+            return Err(SlaError::PauseLimitReached(self.policy.max_pauses));
+        }
+        self.pause_count += 1;
+        self.paused_since = Some(now);
+        Ok(())
+    }
+
+    /// 冻结事件结束：恢复计时
+    pub fn resume(&mut self, now: DateTime<Utc>) -> Result<(), SlaError> {
+        self.advance(now);
+        if self.stopped_at.is_some() {
+            return Err(SlaError::Stopped);
+        }
+        self.paused_since.take().map(|_| ()).ok_or(SlaError::NotPaused)
+    }
+
+    /// 争议模式切换后调整目标裁决时长：只延长不缩短（回到 Normal 时存量保持已延长的目标，避免即刻违约）；
+    /// 已停止的时钟不调整。返回是否有变化
+    pub fn extend_target(&mut self, target: Duration) -> bool {
+        if self.stopped_at.is_some() || target <= self.policy.target() {
+            return false;
+        }
+        self.policy.target_secs = target.num_seconds();
+        true
+    }
+
+    /// 累计有效时长（不含计入上限内的暂停）
+    pub fn active(&self) -> Duration {
+        Duration::seconds(self.stages.iter().map(|s| s.active_secs).sum())
+    }
+
+    pub fn paused_total(&self) -> Duration {
+        Duration::seconds(self.paused_total_secs)
+    }
+
+    /// 按当前暂停累计推算的裁决截止时间
+    pub fn due_at(&self) -> DateTime<Utc> {
+        self.started_at + self.policy.target() + self.paused_total()
+    }
+
+    pub fn is_breached(&self) -> bool {
+        self.active() > self.policy.target()
+    }
+
+    fn check_breach(&mut self, now: DateTime<Utc>) {
+        let already = self.alerts.iter().any(|a| matches!(a.kind, SlaAlertKind::Breached { .. }));
+        if !already && self.is_breached() {
+            let overdue_secs = (self.active() - self.policy.target()).num_seconds();
+            self.push_alert(SlaAlertKind::Breached { overdue_secs }, now);
+        }
+    }
+
+    fn push_alert(&mut self, kind: SlaAlertKind, at: DateTime<Utc>) {
+        self.alerts.push(SlaAlert {
+            dispute_id: self.dispute_id,
+            kind,
+            at,
+        });
+    }
+}
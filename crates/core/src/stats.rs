@@ -0,0 +1,292 @@
+//! 按角色统计摘要（04 §三 3.2）：游客—订单数/总消费/评价数；导游—接待单数、完成单数、总收入（链上放款）、评分；仲裁员—裁决数。
+//!
+//! 由订单、评价、结算（escrow::Settlement）与争议聚合；金额按币种精确汇总（amount::TokenAmount），不同币种不相加。
+//! 总消费 = 已托管订单金额 − 链上退款；总收入 = 链上放款给导游的金额，未结算订单不计。
+//! 评分为评价分数按 weight 的加权平均（保留两位小数），无评价为 None。
+//! 聚合结果按用户缓存（StatsCache），订单、评价、结算或争议裁决变更时由调用方失效；角色变更后缓存自然不命中。
+
+use crate::amount::{CurrencyTotals, TokenAmount};
+use crate::escrow::{OrderState, Settlement};
+use crate::types::{Dispute, DisputeStatus, Order, Review, UserRole};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TouristStats {
+    pub orders: u64,
+    pub total_spent: CurrencyTotals,
+    pub reviews_written: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuideStats {
+    /// 已接单（含进行中、争议与终态，不含取消）
+    pub orders_served: u64,
+    pub orders_completed: u64,
+    pub total_income: CurrencyTotals,
+    pub rating: Option<f64>,
+    pub rating_count: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArbitratorStats {
+    pub rulings_issued: u64,
+}
+
+/// 统计摘要：按 role 只填对应一项，运营、执行器与合规类角色均为 None
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserStats {
+    pub role: UserRole,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tourist: Option<TouristStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guide: Option<GuideStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arbitrator: Option<ArbitratorStats>,
+    pub computed_at: DateTime<Utc>,
+}
+
+fn order_amount(order: &Order) -> TokenAmount {
+    // 下单时已校验；历史数据无法解析时按零计，不让整份统计失败
+    order.amount.parse().unwrap_or_default()
+}
+
+pub fn tourist_stats<'a>(
+    user_id: Uuid,
+    orders: impl IntoIterator<Item = &'a Order>,
+    reviews: impl IntoIterator<Item = &'a Review>,
+    settlements: &HashMap<Uuid, Settlement>,
+) -> TouristStats {
+    let mut stats = TouristStats::default();
+    for order in orders.into_iter().filter(|o| o.tourist_id == user_id) {
+        stats.orders += 1;
+        if order.escrow_at.is_none() {
+            continue;
+        }
+        let refund = settlements.get(&order.id).map(|s| s.tourist_refund).unwrap_or_default();
+        stats.total_spent.add(&order.currency, order_amount(order).saturating_sub(refund));
+    }
+    stats.reviews_written = reviews.into_iter().filter(|r| r.reviewer_id == user_id).count() as u64;
+    stats
+}
+
+/// guide_id 为该账号的导游资料；尚未注册导游资料时只统计评价
+pub fn guide_stats<'a>(
+    user_id: Uuid,
+    guide_id: Option<Uuid>,
+    orders: impl IntoIterator<Item = &'a Order>,
+    reviews: impl IntoIterator<Item = &'a Review>,
+    settlements: &HashMap<Uuid, Settlement>,
+) -> GuideStats {
+    let mut stats = GuideStats::default();
+    for order in orders.into_iter().filter(|o| Some(o.guide_id) == guide_id) {
+        if order.acceptance_signature.is_some() && order.state != OrderState::Cancelled {
+            stats.orders_served += 1;
+        }
+        if order.state == OrderState::Completed {
+            stats.orders_completed += 1;
+        }
+        if let Some(settlement) = settlements.get(&order.id) {
+            stats.total_income.add(&settlement.currency, settlement.guide_payout);
+        }
+    }
+    let (weighted, weights, count) = reviews
+        .into_iter()
+        .filter(|r| r.reviewee_id == user_id && r.weight > 0.0)
+        .fold((0.0, 0.0, 0u64), |(sum, w, n), r| (sum + r.score as f64 * r.weight, w + r.weight, n + 1));
+    stats.rating_count = count;
+    stats.rating = (weights > 0.0).then(|| (weighted / weights * 100.0).round() / 100.0);
+    stats
+}
+
+pub fn arbitrator_stats<'a>(user_id: Uuid, disputes: impl IntoIterator<Item = &'a Dispute>) -> ArbitratorStats {
+    ArbitratorStats {
+        rulings_issued: disputes
+            .into_iter()
+            .filter(|d| d.status == DisputeStatus::Resolved && d.arbitrator_id == Some(user_id))
+            .count() as u64,
+    }
+}
+
+/// 统计摘要缓存；命中须角色一致
+#[derive(Debug, Default)]
+pub struct StatsCache {
+    entries: HashMap<Uuid, UserStats>,
+}
+
+impl StatsCache {
+    pub fn get(&self, user_id: Uuid, role: &UserRole) -> Option<&UserStats> {
+        self.entries.get(&user_id).filter(|s| s.role == *role)
+    }
+
+    pub fn insert(&mut self, user_id: Uuid, stats: UserStats) {
+        self.entries.insert(user_id, stats);
+    }
+
+    pub fn invalidate(&mut self, user_id: Uuid) {
+        self.entries.remove(&user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eip712::{OrderSignature, SignedAction};
+    use crate::types::ServiceType;
+
+    fn order(tourist_id: Uuid, guide_id: Uuid, amount: &str, state: OrderState, escrowed: bool) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            tourist_id,
+            guide_id,
+            service_type: ServiceType::WalkingTour,
+            amount: amount.into(),
+            currency: "USDC".into(),
+            state,
+            escrow_at: escrowed.then(Utc::now),
+            completed_at: None,
+            created_at: Utc::now(),
+            intent_signature: None,
+            acceptance_signature: None,
+        }
+    }
+
+    fn review(order_id: Uuid, reviewer_id: Uuid, reviewee_id: Uuid, score: u8, weight: f64) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            order_id,
+            reviewer_id,
+            reviewee_id,
+            score,
+            weight,
+            comment: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn settlement(order: &Order, guide_payout: &str, tourist_refund: &str) -> Settlement {
+        Settlement {
+            order_id: order.id,
+            currency: order.currency.clone(),
+            guide_payout: guide_payout.parse().unwrap(),
+            tourist_refund: tourist_refund.parse().unwrap(),
+            tx_hash: None,
+            settled_at: Utc::now(),
+        }
+    }
+
+    fn totals(pairs: &[(&str, &str)]) -> CurrencyTotals {
+        let mut totals = CurrencyTotals::default();
+        for (currency, amount) in pairs {
+            totals.add(currency, amount.parse().unwrap());
+        }
+        totals
+    }
+
+    #[test]
+    fn tourist_spend_counts_escrowed_orders_net_of_refunds() {
+        let tourist = Uuid::new_v4();
+        let guide_user = Uuid::new_v4();
+        let guide_id = Uuid::new_v4();
+        let completed = order(tourist, guide_id, "100.5", OrderState::Completed, true);
+        let refunded = order(tourist, guide_id, "40", OrderState::PartiallyRefunded, true);
+        let unpaid = order(tourist, guide_id, "999", OrderState::Accepted, false);
+        let mut usdt = order(tourist, guide_id, "7", OrderState::Escrowed, true);
+        usdt.currency = "USDT".into();
+        let someone_else = order(Uuid::new_v4(), guide_id, "50", OrderState::Completed, true);
+        let orders = [completed.clone(), refunded.clone(), unpaid, usdt, someone_else];
+
+        let settlements = HashMap::from([
+            (completed.id, settlement(&completed, "100.5", "0")),
+            (refunded.id, settlement(&refunded, "10", "30")),
+        ]);
+        let reviews = [
+            review(completed.id, tourist, guide_user, 5, 1.0),
+            review(refunded.id, guide_user, tourist, 4, 1.0),
+        ];
+
+        let stats = tourist_stats(tourist, &orders, &reviews, &settlements);
+        assert_eq!(stats.orders, 4);
+        assert_eq!(stats.total_spent, totals(&[("USDC", "110.5"), ("USDT", "7")]));
+        assert_eq!(stats.reviews_written, 1);
+    }
+
+    #[test]
+    fn guide_income_and_weighted_rating() {
+        let guide_user = Uuid::new_v4();
+        let guide_id = Uuid::new_v4();
+        let tourist = Uuid::new_v4();
+        let signed = || OrderSignature {
+            action: SignedAction::Acceptance,
+            signer: "0x0000000000000000000000000000000000000001".into(),
+            signature: String::new(),
+            struct_hash: String::new(),
+            digest: String::new(),
+            nonce: 1,
+            expiry: 0,
+            terms_version_hash: String::new(),
+            signed_at: Utc::now(),
+        };
+        let mut completed = order(tourist, guide_id, "100", OrderState::Completed, true);
+        completed.acceptance_signature = Some(signed());
+        let mut slashed = order(tourist, guide_id, "60", OrderState::Slashed, true);
+        slashed.acceptance_signature = Some(signed());
+        let mut cancelled = order(tourist, guide_id, "20", OrderState::Cancelled, false);
+        cancelled.acceptance_signature = Some(signed());
+        let not_accepted = order(tourist, guide_id, "30", OrderState::Created, false);
+        let other_guide = order(tourist, Uuid::new_v4(), "80", OrderState::Completed, true);
+        let orders = [completed.clone(), slashed.clone(), cancelled, not_accepted, other_guide.clone()];
+
+        let settlements = HashMap::from([
+            (completed.id, settlement(&completed, "100", "0")),
+            (slashed.id, settlement(&slashed, "0.25", "59.75")),
+            (other_guide.id, settlement(&other_guide, "80", "0")),
+        ]);
+        let reviews = [
+            review(completed.id, tourist, guide_user, 5, 3.0),
+            review(slashed.id, tourist, guide_user, 2, 1.0),
+            // 权重为 0 的评价不计入
+            review(slashed.id, tourist, guide_user, 1, 0.0),
+            review(completed.id, guide_user, tourist, 1, 1.0),
+        ];
+
+        let stats = guide_stats(guide_user, Some(guide_id), &orders, &reviews, &settlements);
+        assert_eq!(stats.orders_served, 2);
+        assert_eq!(stats.orders_completed, 1);
+        assert_eq!(stats.total_income, totals(&[("USDC", "100.25")]));
+        // (5×3 + 2×1) / 4 = 4.25
+        assert_eq!(stats.rating, Some(4.25));
+        assert_eq!(stats.rating_count, 2);
+    }
+
+    #[test]
+    fn guide_without_profile_or_reviews_is_empty() {
+        let guide_user = Uuid::new_v4();
+        let orders = [order(Uuid::new_v4(), Uuid::new_v4(), "10", OrderState::Completed, true)];
+        let stats = guide_stats(guide_user, None, &orders, &[], &HashMap::new());
+        assert_eq!(stats, GuideStats::default());
+        assert_eq!(stats.rating, None);
+    }
+
+    #[test]
+    fn cache_hits_only_for_same_role() {
+        let user = Uuid::new_v4();
+        let mut cache = StatsCache::default();
+        cache.insert(
+            user,
+            UserStats {
+                role: UserRole::Tourist,
+                tourist: Some(TouristStats::default()),
+                guide: None,
+                arbitrator: None,
+                computed_at: Utc::now(),
+            },
+        );
+        assert!(cache.get(user, &UserRole::Tourist).is_some());
+        assert!(cache.get(user, &UserRole::Guide).is_none());
+        cache.invalidate(user);
+        assert!(cache.get(user, &UserRole::Tourist).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! Sybil 信号采集（01 §4 Sybil 与评价 P0、04 §四「同设备/IP 多导游账号可限或人工复核」、08-3「关联账户防护」）
+//!
+//! 会话登录与活跃时上报 (账号, IP) 与 (账号, 设备 id 摘要)；同一 IP 或设备在窗口内出现多个账号即为关联信号。
+//! 只产出待复核线索，不自动处置：共享出口 IP（NAT、酒店、运营商）会误报，设备 id 为客户端自报可伪造。
+//! 设备 id 只存 sha256，不存原值。最近出现早于 SYBIL_WINDOW_DAYS 的记录在上报时淘汰（至多每 SYBIL_PRUNE_INTERVAL_MINUTES 一次）。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 默认观察窗口
+pub const SYBIL_WINDOW_DAYS: i64 = 30;
+/// 同一信号至少关联的账号数
+pub const SYBIL_MIN_ACCOUNTS: usize = 2;
+/// 过期淘汰的最小间隔
+const SYBIL_PRUNE_INTERVAL_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// 客户端 IP
+    Ip,
+    /// 客户端自报设备 id 的 sha256
+    Device,
+}
+
+/// 设备 id 的存储形式
+pub fn device_fingerprint(device_id: &str) -> String {
+    hex::encode(Sha256::digest(device_id.trim().as_bytes()))
+}
+
+/// 某账号在某信号下的出现记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSighting {
+    pub user_id: Uuid,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// 以该信号登录的会话数
+    pub sessions: u32,
+}
+
+/// 关联多个账号的信号
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSignal {
+    pub kind: SignalKind,
+    pub value: String,
+    pub accounts: Vec<AccountSighting>,
+}
+
+#[derive(Debug, Default)]
+pub struct SybilSignals {
+    sightings: HashMap<(SignalKind, String), HashMap<Uuid, AccountSighting>>,
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+impl SybilSignals {
+    /// 记录一次出现；new_session 为登录（计入会话数），否则仅刷新 last_seen
+    pub fn observe(&mut self, kind: SignalKind, value: &str, user_id: Uuid, new_session: bool, now: DateTime<Utc>) {
+        if value.is_empty() {
+            return;
+        }
+        if self
+            .last_pruned
+            .is_none_or(|at| now - at >= Duration::minutes(SYBIL_PRUNE_INTERVAL_MINUTES))
+        {
+            self.prune(now - Duration::days(SYBIL_WINDOW_DAYS));
+            self.last_pruned = Some(now);
+        }
+        let sighting = self
+            .sightings
+            .entry((kind, value.to_string()))
+            .or_default()
+            .entry(user_id)
+            .or_insert(AccountSighting {
+                user_id,
+                first_seen: now,
+                last_seen: now,
+                sessions: 0,
+            });
+        sighting.last_seen = now;
+        if new_session {
+            sighting.sessions += 1;
+        }
+    }
+
+    /// 淘汰最近出现早于 cutoff 的记录，信号下无账号时一并移除
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) {
+        self.sightings.retain(|_, accounts| {
+            accounts.retain(|_, s| s.last_seen >= cutoff);
+            !accounts.is_empty()
+        });
+    }
+
+    /// since 之后仍活跃、关联至少 min_accounts 个账号的信号，按账号数降序
+    pub fn shared(&self, since: DateTime<Utc>, min_accounts: usize) -> Vec<SharedSignal> {
+        let mut signals: Vec<SharedSignal> = self
+            .sightings
+            .iter()
+            .filter_map(|((kind, value), accounts)| {
+                let mut accounts: Vec<AccountSighting> =
+                    accounts.values().filter(|s| s.last_seen >= since).cloned().collect();
+                if accounts.len() < min_accounts.max(2) {
+                    return None;
+                }
+                accounts.sort_by_key(|s| s.first_seen);
+                Some(SharedSignal {
+                    kind: *kind,
+                    value: value.clone(),
+                    accounts,
+                })
+            })
+            .collect();
+        signals.sort_by(|a, b| b.accounts.len().cmp(&a.accounts.len()).then_with(|| a.value.cmp(&b.value)));
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_signal_needs_two_accounts_in_window() {
+        let now = Utc::now();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut signals = SybilSignals::default();
+        signals.observe(SignalKind::Ip, "203.0.113.7", a, true, now);
+        signals.observe(SignalKind::Ip, "203.0.113.7", a, false, now);
+        assert!(signals.shared(now - Duration::days(1), SYBIL_MIN_ACCOUNTS).is_empty());
+
+        signals.observe(SignalKind::Ip, "203.0.113.7", b, true, now);
+        signals.observe(SignalKind::Ip, "", b, true, now);
+        let shared = signals.shared(now - Duration::days(1), SYBIL_MIN_ACCOUNTS);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].accounts.len(), 2);
+        let sighting = shared[0].accounts.iter().find(|s| s.user_id == a).unwrap();
+        assert_eq!(sighting.sessions, 1);
+        assert!(signals.shared(now + Duration::seconds(1), SYBIL_MIN_ACCOUNTS).is_empty());
+    }
+
+    #[test]
+    fn observe_evicts_sightings_older_than_window() {
+        let start = Utc::now();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let device = device_fingerprint("device-1");
+        let mut signals = SybilSignals::default();
+        signals.observe(SignalKind::Ip, "198.51.100.1", a, true, start);
+        signals.observe(SignalKind::Device, &device, a, true, start);
+        signals.observe(SignalKind::Device, &device, b, true, start + Duration::days(20));
+
+        let later = start + Duration::days(SYBIL_WINDOW_DAYS + 1);
+        signals.observe(SignalKind::Ip, "198.51.100.2", c, true, later);
+        assert!(!signals.sightings.contains_key(&(SignalKind::Ip, "198.51.100.1".to_string())));
+        let device_accounts = &signals.sightings[&(SignalKind::Device, device.clone())];
+        assert!(!device_accounts.contains_key(&a));
+        assert!(device_accounts.contains_key(&b));
+
+        // 间隔内不重复淘汰
+        signals.observe(SignalKind::Ip, "198.51.100.3", c, true, later + Duration::minutes(1));
+        assert_eq!(signals.last_pruned, Some(later));
+    }
+
+    #[test]
+    fn prune_drops_empty_signals() {
+        let now = Utc::now();
+        let mut signals = SybilSignals::default();
+        signals.observe(SignalKind::Ip, "192.0.2.1", Uuid::new_v4(), true, now);
+        signals.prune(now + Duration::seconds(1));
+        assert!(signals.sightings.is_empty());
+    }
+}
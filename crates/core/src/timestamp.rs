@@ -0,0 +1,220 @@
+//! 可信时间戳（Runbook §11「backend 时间源须可验证」「证据 hash 须配防篡改时间戳与独立时间锚」、08-4 第 3 章）
+//!
+//! 仿 RFC 3161 TSTInfo：对调用方提交的 sha256 摘要（message imprint）签发带序号、生成时间与精度的时间戳令牌，
+//! 由平台 TSA 密钥 ed25519 签名（与回执密钥分开轮换）。令牌只证明「该摘要不晚于 gen_time 已存在」，不含原文。
+//! 盖章对象：证据原文件 sha256、已签名回执摘要（`SignedEvidenceReceipt::digest`）与争议裁决摘要（`Dispute::resolution_digest`）。
+//! `verify_timestamp` 供法院、审计方独立验证，无需信任平台数据库。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::evidence::{verify_ed25519, ReceiptError, ReceiptPublicKey, RECEIPT_ALGORITHM};
+
+/// TSTInfo 版本
+pub const TST_VERSION: u8 = 1;
+/// 签发策略标识（RFC 3161 TSAPolicyId 的对应物）
+pub const TSA_POLICY: &str = "traveltrust-tsa-policy-v1";
+/// 目前只接受 sha256 摘要
+pub const TST_HASH_ALGORITHM: &str = "sha256";
+
+/// 令牌签名域分隔前缀
+const TST_SIGNING_DOMAIN: &str = "traveltrust-timestamp-token-v1";
+
+/// 被盖章的摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageImprint {
+    pub hash_algorithm: String,
+    /// 小写十六进制
+    pub hashed_message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeStampToken {
+    pub version: u8,
+    pub policy: String,
+    pub message_imprint: MessageImprint,
+    /// TSA 内单调递增
+    pub serial_number: u64,
+    pub gen_time: DateTime<Utc>,
+    /// 时间精度（毫秒）
+    pub accuracy_ms: u32,
+    /// 调用方防重放随机数（原样回显）
+    pub nonce: Option<String>,
+    /// TSA 签名密钥标识（见 GET /api/v1/timestamps/keys）
+    pub tsa_key_id: String,
+    pub algorithm: String,
+    /// ed25519 签名，十六进制
+    pub signature: String,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TimestampError {
+    #[error("不支持的摘要算法 {0}")]
+    UnsupportedHashAlgorithm(String),
+    #[error("摘要须为 64 位十六进制")]
+    InvalidImprint,
+    #[error("令牌摘要与待验证内容不一致")]
+    ImprintMismatch,
+    #[error("签名无效: {0}")]
+    Signature(#[from] ReceiptError),
+}
+
+/// 校验并规范化 sha256 摘要（小写）
+pub fn normalize_imprint(hash_algorithm: &str, hashed_message: &str) -> Result<MessageImprint, TimestampError> {
+    if hash_algorithm != TST_HASH_ALGORITHM {
+        return Err(TimestampError::UnsupportedHashAlgorithm(hash_algorithm.to_string()));
+    }
+    if hashed_message.len() != 64 || !hashed_message.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(TimestampError::InvalidImprint);
+    }
+    Ok(MessageImprint {
+        hash_algorithm: TST_HASH_ALGORITHM.to_string(),
+        hashed_message: hashed_message.to_ascii_lowercase(),
+    })
+}
+
+impl TimeStampToken {
+    /// 签名原文：域前缀 + 逐行 `字段=值`（时间固定为 RFC 3339 纳秒 UTC）
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        format!(
+            "{}\nversion={}\npolicy={}\nhash_algorithm={}\nhashed_message={}\nserial_number={}\ngen_time={}\naccuracy_ms={}\nnonce={}\ntsa_key_id={}",
+            TST_SIGNING_DOMAIN,
+            self.version,
+            self.policy,
+            self.message_imprint.hash_algorithm,
+            self.message_imprint.hashed_message,
+            self.serial_number,
+            self.gen_time.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.accuracy_ms,
+            self.nonce.as_deref().unwrap_or_default(),
+            self.tsa_key_id,
+        )
+        .into_bytes()
+    }
+}
+
+/// 验证令牌签名，并核对其摘要是否为 expected_sha256（待证明内容的 sha256）
+pub fn verify_timestamp(
+    token: &TimeStampToken,
+    expected_sha256: &str,
+    public_key_hex: &str,
+) -> Result<(), TimestampError> {
+    if token.algorithm != RECEIPT_ALGORITHM {
+        return Err(ReceiptError::UnsupportedAlgorithm(token.algorithm.clone()).into());
+    }
+    let expected = normalize_imprint(TST_HASH_ALGORITHM, expected_sha256)?;
+    if token.message_imprint != expected {
+        return Err(TimestampError::ImprintMismatch);
+    }
+    verify_ed25519(&token.signing_bytes(), &token.signature, public_key_hex)?;
+    Ok(())
+}
+
+/// 按令牌中的 tsa_key_id 从公布的 TSA 公钥列表中取钥验证（含已轮换的旧钥）
+pub fn verify_timestamp_with_keys(
+    token: &TimeStampToken,
+    expected_sha256: &str,
+    keys: &[ReceiptPublicKey],
+) -> Result<(), TimestampError> {
+    let key = keys
+        .iter()
+        .find(|k| k.key_id == token.tsa_key_id && k.algorithm == token.algorithm)
+        .ok_or_else(|| ReceiptError::UnknownKey(token.tsa_key_id.clone()))?;
+    verify_timestamp(token, expected_sha256, &key.public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[9u8; 32])
+    }
+
+    fn public_hex(key: &SigningKey) -> String {
+        hex::encode(key.verifying_key().to_bytes())
+    }
+
+    fn signed_token(key: &SigningKey, hashed_message: &str) -> TimeStampToken {
+        let mut token = TimeStampToken {
+            version: TST_VERSION,
+            policy: TSA_POLICY.to_string(),
+            message_imprint: normalize_imprint(TST_HASH_ALGORITHM, hashed_message).unwrap(),
+            serial_number: 1,
+            gen_time: Utc::now(),
+            accuracy_ms: 1000,
+            nonce: Some("n-1".into()),
+            tsa_key_id: "tsa-1".into(),
+            algorithm: RECEIPT_ALGORITHM.to_string(),
+            signature: String::new(),
+        };
+        token.signature = hex::encode(key.sign(&token.signing_bytes()).to_bytes());
+        token
+    }
+
+    #[test]
+    fn imprint_must_be_sha256_hex() {
+        let upper = "AB".repeat(32);
+        assert_eq!(normalize_imprint("sha256", &upper).unwrap().hashed_message, "ab".repeat(32));
+        assert_eq!(
+            normalize_imprint("sha1", &upper),
+            Err(TimestampError::UnsupportedHashAlgorithm("sha1".into()))
+        );
+        assert_eq!(normalize_imprint("sha256", &upper[..62]), Err(TimestampError::InvalidImprint));
+        assert_eq!(normalize_imprint("sha256", &"zz".repeat(32)), Err(TimestampError::InvalidImprint));
+    }
+
+    #[test]
+    fn signed_token_verifies_and_detects_tampering() {
+        let key = key();
+        let hash = "ab".repeat(32);
+        let token = signed_token(&key, &hash);
+        assert_eq!(verify_timestamp(&token, &hash, &public_hex(&key)), Ok(()));
+        assert_eq!(verify_timestamp(&token, &hash.to_ascii_uppercase(), &public_hex(&key)), Ok(()));
+
+        assert_eq!(
+            verify_timestamp(&token, &"cd".repeat(32), &public_hex(&key)),
+            Err(TimestampError::ImprintMismatch)
+        );
+        let mut backdated = token.clone();
+        backdated.gen_time -= chrono::Duration::days(1);
+        assert_eq!(
+            verify_timestamp(&backdated, &hash, &public_hex(&key)),
+            Err(TimestampError::Signature(ReceiptError::VerificationFailed))
+        );
+        let mut renonced = token.clone();
+        renonced.nonce = None;
+        assert!(verify_timestamp(&renonced, &hash, &public_hex(&key)).is_err());
+        let other = SigningKey::from_bytes(&[10u8; 32]);
+        assert_eq!(
+            verify_timestamp(&token, &hash, &public_hex(&other)),
+            Err(TimestampError::Signature(ReceiptError::VerificationFailed))
+        );
+        let mut wrong_alg = token;
+        wrong_alg.algorithm = "rsa".into();
+        assert_eq!(
+            verify_timestamp(&wrong_alg, &hash, &public_hex(&key)),
+            Err(TimestampError::Signature(ReceiptError::UnsupportedAlgorithm("rsa".into())))
+        );
+    }
+
+    #[test]
+    fn token_from_unknown_key_is_rejected() {
+        let key = key();
+        let hash = "ab".repeat(32);
+        let token = signed_token(&key, &hash);
+        let published = |key_id: &str| ReceiptPublicKey {
+            key_id: key_id.into(),
+            algorithm: RECEIPT_ALGORITHM.into(),
+            public_key: public_hex(&key),
+            active: false,
+        };
+        assert_eq!(verify_timestamp_with_keys(&token, &hash, &[published("tsa-0"), published("tsa-1")]), Ok(()));
+        assert_eq!(
+            verify_timestamp_with_keys(&token, &hash, &[published("tsa-2")]),
+            Err(TimestampError::Signature(ReceiptError::UnknownKey("tsa-1".into())))
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! TOTP 两步验证（01 §7 权限与角色「仲裁员/运营高权限账号」、P0-2）：RFC 6238（HMAC-SHA1、30 秒步长、6 位）。
+//!
+//! 启用：服务端生成 160 位密钥（otpauth URI 供认证器扫码），用户以首个验证码确认后才生效，同时下发一次性恢复码（只存 sha256）。
+//! 校验允许前后各 1 个步长的时钟偏差；同一步长的验证码只能用一次（记录最后使用步长，防重放）。
+//! 连续失败 5 次锁定 15 分钟。仲裁员与运营必须启用后才能访问角色专属路由；裁决、参数变更等高风险操作另需
+//! 当前会话 5 分钟内做过 TOTP 校验（step-up，见 rbac 路由表 step_up 标记）。
+//! 密钥为内存存储；落库时须经 KMS 加密，不得明文写盘或入日志。
+
+use crate::UserRole;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const TOTP_ISSUER: &str = "TravelTrust";
+/// 密钥长度（RFC 4226 建议 160 位）
+pub const TOTP_SECRET_BYTES: usize = 20;
+pub const TOTP_STEP_SECS: i64 = 30;
+pub const TOTP_DIGITS: u32 = 6;
+/// 允许的时钟偏差（步长数）
+pub const TOTP_SKEW_STEPS: i64 = 1;
+/// 启用时下发的恢复码数量
+pub const RECOVERY_CODE_COUNT: usize = 10;
+/// step-up 有效期：高风险操作前须在此时间内做过 TOTP 校验
+pub const STEP_UP_WINDOW_MINUTES: i64 = 5;
+pub const MAX_FAILED_ATTEMPTS: u32 = 5;
+pub const LOCKOUT_MINUTES: i64 = 15;
+
+/// 必须启用 TOTP 的角色
+pub fn requires_totp(role: &UserRole) -> bool {
+    matches!(role, UserRole::Arbitrator | UserRole::Operator)
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TotpError {
+    #[error("账号未启用 TOTP")]
+    NotEnabled,
+    #[error("账号已启用 TOTP")]
+    AlreadyEnabled,
+    #[error("没有待确认的 TOTP 启用，请先发起启用")]
+    NoPendingEnrollment,
+    #[error("验证码格式无效（须为 6 位数字）")]
+    MalformedCode,
+    #[error("验证码错误")]
+    InvalidCode,
+    #[error("该验证码已使用，请等待认证器刷新")]
+    CodeReused,
+    #[error("恢复码无效或已使用")]
+    InvalidRecoveryCode,
+    #[error("验证失败次数过多，已锁定至 {until}")]
+    Locked { until: DateTime<Utc> },
+}
+
+/// 校验所用的第二因素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecondFactor {
+    Totp,
+    RecoveryCode,
+}
+
+impl SecondFactor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecondFactor::Totp => "totp",
+            SecondFactor::RecoveryCode => "recovery_code",
+        }
+    }
+}
+
+/// RFC 4648 Base32（无填充），认证器录入密钥用
+pub fn base32_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &b in bytes {
+        buffer = (buffer << 8) | u32::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// RFC 4226 HOTP：HMAC-SHA1(secret, counter) 动态截断取 6 位
+pub fn hotp(secret: &[u8], counter: u64) -> u32 {
+    hotp_digits(secret, counter, TOTP_DIGITS)
+}
+
+/// 位数可变的 HOTP（RFC 6238 附录 B 参考值为 8 位）
+fn hotp_digits(secret: &[u8], counter: u64, digits: u32) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC 接受任意长度密钥");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[19] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset] & 0x7f, digest[offset + 1], digest[offset + 2], digest[offset + 3]]);
+    binary % 10u32.pow(digits)
+}
+
+/// 时刻所在步长
+pub fn time_step(at: DateTime<Utc>) -> i64 {
+    at.timestamp().div_euclid(TOTP_STEP_SECS)
+}
+
+/// 某时刻的验证码（6 位，补前导 0）
+pub fn totp_code(secret: &[u8], at: DateTime<Utc>) -> String {
+    format!("{:0width$}", hotp(secret, time_step(at) as u64), width = TOTP_DIGITS as usize)
+}
+
+/// otpauth://totp URI（认证器扫码）；label 一般为邮箱
+pub fn otpauth_uri(secret: &[u8], label: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{label}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = TOTP_ISSUER,
+        label = percent_encode(label),
+        secret = base32_encode(secret),
+        digits = TOTP_DIGITS,
+        period = TOTP_STEP_SECS,
+    )
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn parse_code(code: &str) -> Result<u32, TotpError> {
+    let digits: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() != TOTP_DIGITS as usize || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(TotpError::MalformedCode);
+    }
+    digits.parse().map_err(|_| TotpError::MalformedCode)
+}
+
+/// 恢复码存储形式：忽略大小写、连字符与空白后 sha256
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct RecoveryCode {
+    pub hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TotpEnrollment {
+    secret: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    /// None 为待确认（尚未以验证码确认，不生效）
+    pub enabled_at: Option<DateTime<Utc>>,
+    /// 最后一次成功使用的步长（防重放）
+    pub last_used_step: Option<i64>,
+    pub recovery_codes: Vec<RecoveryCode>,
+    pub failed_attempts: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl TotpEnrollment {
+    pub fn recovery_codes_remaining(&self) -> usize {
+        self.recovery_codes.iter().filter(|c| c.used_at.is_none()).count()
+    }
+
+    /// 校验验证码：±TOTP_SKEW_STEPS 步长内匹配且步长晚于上次使用
+    fn check_code(&mut self, code: &str, now: DateTime<Utc>) -> Result<(), TotpError> {
+        let code = parse_code(code)?;
+        let current = time_step(now);
+        let matched = (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+            .find(|step| *step >= 0 && hotp(&self.secret, *step as u64) == code)
+            .ok_or(TotpError::InvalidCode)?;
+        if self.last_used_step.is_some_and(|last| matched <= last) {
+            return Err(TotpError::CodeReused);
+        }
+        self.last_used_step = Some(matched);
+        Ok(())
+    }
+
+    fn use_recovery_code(&mut self, code: &str, now: DateTime<Utc>) -> Result<(), TotpError> {
+        let hash = hash_recovery_code(code);
+        let entry = self
+            .recovery_codes
+            .iter_mut()
+            .find(|c| c.used_at.is_none() && c.hash == hash)
+            .ok_or(TotpError::InvalidRecoveryCode)?;
+        entry.used_at = Some(now);
+        Ok(())
+    }
+}
+
+/// TOTP 启用记录与会话 step-up 时间
+#[derive(Debug, Default)]
+pub struct TotpRegistry {
+    enrollments: HashMap<Uuid, TotpEnrollment>,
+    /// 会话 id → 最近一次 TOTP 校验时间
+    step_ups: HashMap<Uuid, DateTime<Utc>>,
+}
+
+impl TotpRegistry {
+    pub fn get(&self, user_id: Uuid) -> Option<&TotpEnrollment> {
+        self.enrollments.get(&user_id)
+    }
+
+    pub fn is_enabled(&self, user_id: Uuid) -> bool {
+        self.enrollments.get(&user_id).is_some_and(|e| e.enabled_at.is_some())
+    }
+
+    /// 发起启用（替换未确认的旧密钥）；已启用返回 AlreadyEnabled
+    pub fn begin(&mut self, user_id: Uuid, secret: Vec<u8>, now: DateTime<Utc>) -> Result<(), TotpError> {
+        if self.is_enabled(user_id) {
+            return Err(TotpError::AlreadyEnabled);
+        }
+        self.enrollments.insert(
+            user_id,
+            TotpEnrollment {
+                secret,
+                created_at: now,
+                enabled_at: None,
+                last_used_step: None,
+                recovery_codes: Vec::new(),
+                failed_attempts: 0,
+                locked_until: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// 以首个验证码确认启用，登记恢复码（sha256）
+    pub fn confirm(
+        &mut self,
+        user_id: Uuid,
+        code: &str,
+        recovery_hashes: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, TotpError> {
+        match self.enrollments.get(&user_id) {
+            None => return Err(TotpError::NoPendingEnrollment),
+            Some(e) if e.enabled_at.is_some() => return Err(TotpError::AlreadyEnabled),
+            Some(_) => {}
+        }
+        self.attempt(user_id, now, |e| e.check_code(code, now))?;
+        let enrollment = self.enrollments.get_mut(&user_id).ok_or(TotpError::NoPendingEnrollment)?;
+        enrollment.enabled_at = Some(now);
+        enrollment.recovery_codes = recovery_codes(recovery_hashes);
+        Ok(now)
+    }
+
+    /// 已启用账号按验证码或恢复码校验
+    pub fn verify(&mut self, user_id: Uuid, factor: SecondFactor, code: &str, now: DateTime<Utc>) -> Result<(), TotpError> {
+        if !self.is_enabled(user_id) {
+            return Err(TotpError::NotEnabled);
+        }
+        self.attempt(user_id, now, |e| match factor {
+            SecondFactor::Totp => e.check_code(code, now),
+            SecondFactor::RecoveryCode => e.use_recovery_code(code, now),
+        })
+    }
+
+    /// 替换全部恢复码（旧码作废）
+    pub fn replace_recovery_codes(&mut self, user_id: Uuid, recovery_hashes: Vec<String>) -> Result<(), TotpError> {
+        let enrollment = self
+            .enrollments
+            .get_mut(&user_id)
+            .filter(|e| e.enabled_at.is_some())
+            .ok_or(TotpError::NotEnabled)?;
+        enrollment.recovery_codes = recovery_codes(recovery_hashes);
+        Ok(())
+    }
+
+    /// 停用（连同待确认的启用）；返回是否曾启用
+    pub fn disable(&mut self, user_id: Uuid) -> bool {
+        self.enrollments.remove(&user_id).is_some_and(|e| e.enabled_at.is_some())
+    }
+
+    pub fn record_step_up(&mut self, session_id: Uuid, now: DateTime<Utc>) {
+        self.step_ups.retain(|_, at| now - *at < Duration::minutes(STEP_UP_WINDOW_MINUTES));
+        self.step_ups.insert(session_id, now);
+    }
+
+    /// 会话的 step-up 有效期截止时间（已过期为 None）
+    pub fn step_up_valid_until(&self, session_id: Uuid, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.step_ups
+            .get(&session_id)
+            .map(|at| *at + Duration::minutes(STEP_UP_WINDOW_MINUTES))
+            .filter(|until| now < *until)
+    }
+
+    /// 锁定检查与失败计数：连续 MAX_FAILED_ATTEMPTS 次失败锁定 LOCKOUT_MINUTES
+    fn attempt(
+        &mut self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+        check: impl FnOnce(&mut TotpEnrollment) -> Result<(), TotpError>,
+    ) -> Result<(), TotpError> {
+        let enrollment = self.enrollments.get_mut(&user_id).ok_or(TotpError::NotEnabled)?;
+        if let Some(until) = enrollment.locked_until.filter(|until| now < *until) {
+            return Err(TotpError::Locked { until });
+        }
+        match check(enrollment) {
+            Ok(()) => {
+                enrollment.failed_attempts = 0;
+                enrollment.locked_until = None;
+                Ok(())
+            }
+            Err(e) => {
+                enrollment.failed_attempts += 1;
+                if enrollment.failed_attempts >= MAX_FAILED_ATTEMPTS {
+                    let until = now + Duration::minutes(LOCKOUT_MINUTES);
+                    enrollment.failed_attempts = 0;
+                    enrollment.locked_until = Some(until);
+                    return Err(TotpError::Locked { until });
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+fn recovery_codes(hashes: Vec<String>) -> Vec<RecoveryCode> {
+    hashes.into_iter().map(|hash| RecoveryCode { hash, used_at: None }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// RFC 4226 附录 D / RFC 6238 附录 B 的 SHA1 密钥
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn rfc4226_hotp_vectors() {
+        let expected = [
+            755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489,
+        ];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(RFC_SECRET, counter as u64), *code, "counter {}", counter);
+        }
+    }
+
+    #[test]
+    fn rfc6238_sha1_vectors() {
+        let vectors = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        for (secs, code) in vectors {
+            let step = time_step(at(secs)) as u64;
+            assert_eq!(format!("{:08}", hotp_digits(RFC_SECRET, step, 8)), code, "T={}", secs);
+            // 6 位取 8 位参考值的低 6 位
+            assert_eq!(totp_code(RFC_SECRET, at(secs)), code[2..], "T={}", secs);
+        }
+    }
+
+    #[test]
+    fn base32_matches_rfc4648() {
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_encode(RFC_SECRET), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    }
+
+    /// 前后各一个步长内都不匹配的验证码
+    fn wrong_code(now: DateTime<Utc>) -> String {
+        let step = time_step(now);
+        let valid: Vec<u32> = (step - 1..=step + 1).map(|s| hotp(RFC_SECRET, s as u64)).collect();
+        (0..).map(|n: u32| n * 7919 % 1_000_000).find(|c| !valid.contains(c)).map(|c| format!("{:06}", c)).unwrap()
+    }
+
+    fn enabled(now: DateTime<Utc>) -> (TotpRegistry, Uuid) {
+        let mut registry = TotpRegistry::default();
+        let user = Uuid::new_v4();
+        registry.begin(user, RFC_SECRET.to_vec(), now).unwrap();
+        registry
+            .confirm(user, &totp_code(RFC_SECRET, now), vec![hash_recovery_code("abcd-efgh")], now)
+            .unwrap();
+        (registry, user)
+    }
+
+    #[test]
+    fn code_cannot_be_replayed_within_or_before_last_step() {
+        let now = at(1111111111);
+        let (mut registry, user) = enabled(now);
+        assert_eq!(registry.get(user).unwrap().last_used_step, Some(time_step(now)));
+
+        // 同一步长的码（确认时已用）不能再用，偏差窗口内的上一步长也不行
+        let current = totp_code(RFC_SECRET, now);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &current, now), Err(TotpError::CodeReused));
+        let previous = totp_code(RFC_SECRET, now - Duration::seconds(TOTP_STEP_SECS));
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &previous, now), Err(TotpError::CodeReused));
+
+        // 下一步长的码可用一次
+        let next_at = now + Duration::seconds(TOTP_STEP_SECS);
+        let next = totp_code(RFC_SECRET, next_at);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &next, next_at), Ok(()));
+        assert_eq!(registry.get(user).unwrap().last_used_step, Some(time_step(next_at)));
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &next, next_at), Err(TotpError::CodeReused));
+    }
+
+    #[test]
+    fn clock_skew_allows_one_step() {
+        let now = at(1111111111);
+        let (mut registry, user) = enabled(now);
+        let later = now + Duration::seconds(TOTP_STEP_SECS * 3);
+        let ahead = totp_code(RFC_SECRET, later + Duration::seconds(TOTP_STEP_SECS));
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &ahead, later), Ok(()));
+        let too_far = totp_code(RFC_SECRET, later + Duration::seconds(TOTP_STEP_SECS * 3));
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &too_far, later), Err(TotpError::InvalidCode));
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        let now = at(1111111111);
+        let (mut registry, user) = enabled(now);
+        for code in ["", "12345", "1234567", "12a456"] {
+            assert_eq!(registry.verify(user, SecondFactor::Totp, code, now), Err(TotpError::MalformedCode));
+        }
+    }
+
+    #[test]
+    fn five_failures_lock_for_fifteen_minutes() {
+        let now = at(1111111111);
+        let (mut registry, user) = enabled(now);
+        let wrong = wrong_code(now);
+        for _ in 0..MAX_FAILED_ATTEMPTS - 1 {
+            assert_eq!(registry.verify(user, SecondFactor::Totp, &wrong, now), Err(TotpError::InvalidCode));
+        }
+        let until = now + Duration::minutes(LOCKOUT_MINUTES);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &wrong, now), Err(TotpError::Locked { until }));
+
+        // 锁定期间正确的验证码与恢复码也拒绝
+        let during = until - Duration::seconds(1);
+        let code = totp_code(RFC_SECRET, during);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &code, during), Err(TotpError::Locked { until }));
+        assert_eq!(
+            registry.verify(user, SecondFactor::RecoveryCode, "ABCD EFGH", during),
+            Err(TotpError::Locked { until })
+        );
+
+        // 到期后恢复
+        let code = totp_code(RFC_SECRET, until);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &code, until), Ok(()));
+        let enrollment = registry.get(user).unwrap();
+        assert_eq!(enrollment.failed_attempts, 0);
+        assert_eq!(enrollment.locked_until, None);
+    }
+
+    #[test]
+    fn success_resets_failure_count() {
+        let now = at(1111111111);
+        let (mut registry, user) = enabled(now);
+        let wrong = wrong_code(now);
+        for _ in 0..MAX_FAILED_ATTEMPTS - 1 {
+            let _ = registry.verify(user, SecondFactor::Totp, &wrong, now);
+        }
+        assert_eq!(registry.verify(user, SecondFactor::RecoveryCode, "abcdefgh", now), Ok(()));
+        assert_eq!(registry.get(user).unwrap().failed_attempts, 0);
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &wrong, now), Err(TotpError::InvalidCode));
+        // 恢复码只能用一次
+        assert_eq!(
+            registry.verify(user, SecondFactor::RecoveryCode, "abcd-efgh", now),
+            Err(TotpError::InvalidRecoveryCode)
+        );
+    }
+
+    #[test]
+    fn unconfirmed_enrollment_is_not_enabled() {
+        let now = at(1111111111);
+        let mut registry = TotpRegistry::default();
+        let user = Uuid::new_v4();
+        registry.begin(user, RFC_SECRET.to_vec(), now).unwrap();
+        assert!(!registry.is_enabled(user));
+        assert_eq!(registry.verify(user, SecondFactor::Totp, &totp_code(RFC_SECRET, now), now), Err(TotpError::NotEnabled));
+        let wrong = wrong_code(now);
+        assert_eq!(registry.confirm(user, &wrong, Vec::new(), now), Err(TotpError::InvalidCode));
+        assert!(!registry.is_enabled(user));
+    }
+}
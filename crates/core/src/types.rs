@@ -1,7 +1,10 @@
 //! 通用领域类型：用户、导游、订单、争议
 
-use chrono::{DateTime, Utc};
+use crate::eip712::OrderSignature;
+use crate::timestamp::TimeStampToken;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -10,16 +13,58 @@ pub enum UserRole {
     Tourist,
     Guide,
     Arbitrator,
+    /// 运营：风控冻结、争议容量、SLA、角色指派等运营接口（01 §7）
+    Operator,
+    /// 执行器：按裁决代发链上执行（01 §7 执行器 P0-3）
+    Executor,
+    /// 合规：可导出证据访问审计日志（Runbook §5）
+    Compliance,
+    /// 法务：司法协助、审计日志导出（Runbook §5/§9）
+    Legal,
 }
 
-/// 用户领域类型（最小集合）。04 §二 users 表含更多字段（nickname, avatar_url, default_wallet_address, email_verified_at 等），实现时扩展本结构或使用 DTO 与 API 对齐。
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Tourist => "tourist",
+            UserRole::Guide => "guide",
+            UserRole::Arbitrator => "arbitrator",
+            UserRole::Operator => "operator",
+            UserRole::Executor => "executor",
+            UserRole::Compliance => "compliance",
+            UserRole::Legal => "legal",
+        }
+    }
+
+    /// 合规类角色（证据访问日志导出）
+    pub fn is_compliance(&self) -> bool {
+        matches!(self, UserRole::Compliance | UserRole::Legal)
+    }
+}
+
+/// 用户领域类型（04 §二 2.1 users 表，password_hash 除外）。对外响应经 API 层 DTO，不直接序列化本结构。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
+    /// 规范化邮箱；以钱包（SIWE）注册且尚未关联邮箱的账号为空串
     pub email: String,
     pub role: UserRole,
     pub kyc_status: KycStatus,
     pub created_at: DateTime<Utc>,
+    /// 邮箱验证时间；None 为未验证（04 §二 users.email_verified_at）
+    #[serde(default)]
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// 当前绑定钱包（小写 0x 地址；04 §二 users.default_wallet_address），经 EIP-191 签名证明后写入，历史见 wallet::WalletRegistry
+    #[serde(default)]
+    pub default_wallet_address: Option<String>,
+    /// 昵称（规则见 profile::normalize_nickname）
+    #[serde(default)]
+    pub nickname: Option<String>,
+    /// 头像外链，仅 https（规则见 profile::normalize_avatar_url）
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    /// 资料、凭证、角色或绑定钱包最近变更时间
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -45,7 +90,7 @@ pub struct Guide {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum ServiceType {
     WalkingTour,
@@ -56,6 +101,29 @@ pub enum ServiceType {
     Other(String),
 }
 
+/// 风控统计/冻结的类别维度（08-3「某类服务/某地区」、Runbook §8/§11）：服务类型、导游城市、导游国家
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum RiskScope {
+    ServiceType(ServiceType),
+    City { country_code: String, city: String },
+    Country(String),
+}
+
+impl RiskScope {
+    /// 导游 + 服务类型对应的全部类别维度（订单、争议、裁决均按此归类）
+    pub fn for_guide(guide: &Guide, service_type: &ServiceType) -> Vec<RiskScope> {
+        vec![
+            RiskScope::ServiceType(service_type.clone()),
+            RiskScope::City {
+                country_code: guide.country_code.clone(),
+                city: guide.city.clone(),
+            },
+            RiskScope::Country(guide.country_code.clone()),
+        ]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum GuideStatus {
@@ -69,12 +137,34 @@ pub struct Order {
     pub id: Uuid,
     pub tourist_id: Uuid,
     pub guide_id: Uuid,
+    pub service_type: ServiceType,
     pub amount: String,
     pub currency: String,
     pub state: crate::escrow::OrderState,
+    /// 链上托管锁定时间，由托管事件对账写入（下单时为 None）
     pub escrow_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// 游客下单 EIP-712 签名（OrderIntent）；签名钱包即订单游客侧所有者，换绑不变（P0-1）
+    #[serde(default)]
+    pub intent_signature: Option<OrderSignature>,
+    /// 导游接单 EIP-712 签名（OrderAcceptance）
+    #[serde(default)]
+    pub acceptance_signature: Option<OrderSignature>,
+}
+
+/// 订单评价（04 §二 2.2 reviews）：仅资金终态订单可评（reputation::can_submit_review），weight 见 reputation::ReviewWeight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub reviewee_id: Uuid,
+    /// 1 ~ 5
+    pub score: u8,
+    pub weight: f64,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,11 +172,39 @@ pub struct Dispute {
     pub id: Uuid,
     pub order_id: Uuid,
     pub status: DisputeStatus,
+    /// 证据原文件 sha256；元数据与回执见 evidence::Evidence
     pub evidence_hashes: Vec<String>,
     pub arbitrator_id: Option<Uuid>,
     pub resolution: Option<DisputeResolution>,
     pub resolved_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// 裁决摘要（resolution_digest）的可信时间戳（Runbook §11）
+    #[serde(default)]
+    pub resolution_timestamp: Option<TimeStampToken>,
+}
+
+/// 裁决摘要域分隔前缀
+const RESOLUTION_DIGEST_DOMAIN: &str = "traveltrust-dispute-resolution-v1";
+
+impl Dispute {
+    /// 裁决记录摘要（sha256 十六进制），作为时间戳的 message imprint；未裁决时为 None。
+    /// 覆盖争议、订单、仲裁员、裁决内容与裁决时间，时间固定为 RFC 3339 纳秒 UTC
+    pub fn resolution_digest(&self) -> Option<String> {
+        let resolution = self.resolution.as_ref()?;
+        let resolved_at = self.resolved_at?;
+        let line = format!(
+            "{}\ndispute_id={}\norder_id={}\narbitrator_id={}\nrefund_ratio={}\nslash_guide={}\nevidence_hashes={}\nresolved_at={}",
+            RESOLUTION_DIGEST_DOMAIN,
+            self.id,
+            self.order_id,
+            self.arbitrator_id.map(|id| id.to_string()).unwrap_or_default(),
+            resolution.refund_ratio,
+            resolution.slash_guide,
+            self.evidence_hashes.join(","),
+            resolved_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        );
+        Some(hex::encode(Sha256::digest(line.as_bytes())))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
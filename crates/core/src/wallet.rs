@@ -0,0 +1,348 @@
+//! 钱包绑定（01 §2 账号设置「绑定/解绑钱包」、P0-1 身份与订单所有者、01 §10 12 缝 #11 钱包解绑/换绑）
+//!
+//! 挑战–应答：服务端为 (用户, 地址) 签发一次性 nonce 与待签消息，用户以 personal_sign（EIP-191 0x45）签名，
+//! 服务端以 secp256k1 恢复签名地址并与声明地址比对，一致才登记绑定。签名只接受 low-s（EIP-2），v 取 27/28 或 0/1。
+//! 一个地址同一时间只绑定一个账号（防换马甲）；绑定历史只追加，换绑与解绑均关闭旧记录并注明原因。
+//! 换绑硬规则（12 缝 #11）：订单签名钱包不可变更，故本人存在未终结订单（含争议中订单，结算前均不可）或进行中的证据上传时，不可换绑或解绑；首次绑定不受限。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 挑战有效期
+pub const WALLET_CHALLENGE_TTL_MINUTES: i64 = 10;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WalletError {
+    #[error("钱包地址格式无效（须为 0x 开头的 40 位十六进制）")]
+    InvalidAddress,
+    #[error("签名格式无效（须为 65 字节十六进制 r‖s‖v）")]
+    InvalidSignature,
+    #[error("签名 s 值过大（须 low-s，EIP-2）")]
+    HighS,
+    #[error("签名地址与声明地址不一致")]
+    AddressMismatch,
+    #[error("挑战不存在、已使用或不属于当前账号")]
+    UnknownChallenge,
+    #[error("挑战已过期，请重新获取")]
+    ChallengeExpired,
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// 地址规范化为小写 0x 形式
+pub fn normalize_address(address: &str) -> Result<String, WalletError> {
+    let address = address.trim();
+    let hex_part = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .ok_or(WalletError::InvalidAddress)?;
+    if hex_part.len() != 40 || !hex_part.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(WalletError::InvalidAddress);
+    }
+    Ok(format!("0x{}", hex_part.to_ascii_lowercase()))
+}
+
+/// EIP-55 校验和地址（展示用）
+pub fn checksum_address(address: &str) -> Result<String, WalletError> {
+    let lower = normalize_address(address)?;
+    let hex_part = &lower[2..];
+    let hash = hex::encode(keccak256(hex_part.as_bytes()));
+    let mixed: String = hex_part
+        .chars()
+        .zip(hash.chars())
+        .map(|(c, h)| if c.is_ascii_alphabetic() && h >= '8' { c.to_ascii_uppercase() } else { c })
+        .collect();
+    Ok(format!("0x{}", mixed))
+}
+
+/// EIP-191 personal_sign 摘要：keccak256("\x19Ethereum Signed Message:\n" ‖ len ‖ message)
+pub fn eip191_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// 由 32 字节摘要与 65 字节签名恢复地址（小写 0x）
+pub fn recover_address(prehash: &[u8; 32], signature: &str) -> Result<String, WalletError> {
+    let bytes = hex::decode(signature.trim().trim_start_matches("0x")).map_err(|_| WalletError::InvalidSignature)?;
+    if bytes.len() != 65 {
+        return Err(WalletError::InvalidSignature);
+    }
+    let sig = Signature::from_slice(&bytes[..64]).map_err(|_| WalletError::InvalidSignature)?;
+    if sig.normalize_s().is_some() {
+        return Err(WalletError::HighS);
+    }
+    let v = match bytes[64] {
+        27 | 28 => bytes[64] - 27,
+        v @ (0 | 1) => v,
+        _ => return Err(WalletError::InvalidSignature),
+    };
+    let recovery_id = RecoveryId::from_byte(v).ok_or(WalletError::InvalidSignature)?;
+    let key = VerifyingKey::recover_from_prehash(prehash, &sig, recovery_id).map_err(|_| WalletError::InvalidSignature)?;
+    let point = key.to_encoded_point(false);
+    Ok(format!("0x{}", hex::encode(&keccak256(&point.as_bytes()[1..])[12..])))
+}
+
+/// personal_sign 验签：恢复地址须等于 expected
+pub fn verify_personal_sign(message: &str, signature: &str, expected: &str) -> Result<(), WalletError> {
+    let expected = normalize_address(expected)?;
+    let recovered = recover_address(&eip191_hash(message.as_bytes()), signature)?;
+    if recovered == expected {
+        Ok(())
+    } else {
+        Err(WalletError::AddressMismatch)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletChallenge {
+    pub nonce: String,
+    pub user_id: Uuid,
+    /// 待绑定地址（小写）
+    pub address: String,
+    /// 待签消息原文（personal_sign 对其 UTF-8 字节签名）
+    pub message: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WalletChallenge {
+    /// 消息含用途、账号、地址、nonce 与时间，防止跨用途或跨账号重放
+    pub fn message_for(user_id: Uuid, address: &str, nonce: &str, issued_at: DateTime<Utc>, expires_at: DateTime<Utc>) -> String {
+        format!(
+            "TravelTrust wallet binding\n\nI authorize binding wallet {} to TravelTrust account {}.\n\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+            checksum_address(address).unwrap_or_else(|_| address.to_string()),
+            user_id,
+            nonce,
+            issued_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        )
+    }
+}
+
+/// 绑定记录（只追加；unbound_at 为 None 即当前绑定）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletBinding {
+    pub user_id: Uuid,
+    pub address: String,
+    pub bound_at: DateTime<Utc>,
+    /// 绑定时的签名（可复核）
+    pub signature: String,
+    pub challenge_nonce: String,
+    pub unbound_at: Option<DateTime<Utc>>,
+    /// 关闭原因：rebind / unbind
+    pub unbound_reason: Option<String>,
+}
+
+/// 阻止换绑/解绑的未终结事项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RebindBlocker {
+    /// 未到资金终态（含待接单、待支付、争议中）的订单
+    OpenOrder { order_id: Uuid },
+    /// 进行中的证据上传
+    PendingEvidenceUpload { upload_id: Uuid, order_id: Uuid },
+}
+
+#[derive(Debug, Default)]
+pub struct WalletRegistry {
+    challenges: HashMap<String, WalletChallenge>,
+    bindings: Vec<WalletBinding>,
+}
+
+impl WalletRegistry {
+    pub fn issue_challenge(&mut self, user_id: Uuid, address: String, nonce: String, now: DateTime<Utc>) -> WalletChallenge {
+        self.challenges.retain(|_, c| now < c.expires_at);
+        let expires_at = now + chrono::Duration::minutes(WALLET_CHALLENGE_TTL_MINUTES);
+        let challenge = WalletChallenge {
+            message: WalletChallenge::message_for(user_id, &address, &nonce, now, expires_at),
+            nonce: nonce.clone(),
+            user_id,
+            address,
+            issued_at: now,
+            expires_at,
+        };
+        self.challenges.insert(nonce, challenge.clone());
+        challenge
+    }
+
+    /// 取出挑战（一次性，无论后续验签成败）
+    pub fn take_challenge(&mut self, nonce: &str, user_id: Uuid, now: DateTime<Utc>) -> Result<WalletChallenge, WalletError> {
+        if self.challenges.get(nonce).is_none_or(|c| c.user_id != user_id) {
+            return Err(WalletError::UnknownChallenge);
+        }
+        let challenge = self.challenges.remove(nonce).ok_or(WalletError::UnknownChallenge)?;
+        if now >= challenge.expires_at {
+            return Err(WalletError::ChallengeExpired);
+        }
+        Ok(challenge)
+    }
+
+    pub fn current(&self, user_id: Uuid) -> Option<&WalletBinding> {
+        self.bindings.iter().rev().find(|b| b.user_id == user_id && b.unbound_at.is_none())
+    }
+
+    /// 地址当前绑定的账号
+    pub fn owner_of(&self, address: &str) -> Option<Uuid> {
+        self.bindings
+            .iter()
+            .find(|b| b.address == address && b.unbound_at.is_none())
+            .map(|b| b.user_id)
+    }
+
+    /// 登记新绑定（签名已验证），关闭该用户原绑定（reason=rebind）；nonce 为绑定挑战或 SIWE 登录的 nonce
+    pub fn bind(&mut self, user_id: Uuid, address: String, signature: String, nonce: String, now: DateTime<Utc>) -> WalletBinding {
+        self.close(user_id, "rebind", now);
+        let binding = WalletBinding {
+            user_id,
+            address,
+            bound_at: now,
+            signature,
+            challenge_nonce: nonce,
+            unbound_at: None,
+            unbound_reason: None,
+        };
+        self.bindings.push(binding.clone());
+        binding
+    }
+
+    /// 解绑，返回被关闭的绑定
+    pub fn unbind(&mut self, user_id: Uuid, now: DateTime<Utc>) -> Option<WalletBinding> {
+        self.close(user_id, "unbind", now)
+    }
+
+    fn close(&mut self, user_id: Uuid, reason: &str, now: DateTime<Utc>) -> Option<WalletBinding> {
+        let binding = self
+            .bindings
+            .iter_mut()
+            .find(|b| b.user_id == user_id && b.unbound_at.is_none())?;
+        binding.unbound_at = Some(now);
+        binding.unbound_reason = Some(reason.to_string());
+        Some(binding.clone())
+    }
+
+    /// 用户绑定历史（时间升序）
+    pub fn history(&self, user_id: Uuid) -> Vec<WalletBinding> {
+        self.bindings.iter().filter(|b| b.user_id == user_id).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    /// web3.js accounts.sign 文档示例：私钥 0x4c08…2318 对 "Some data" 的 personal_sign
+    const KNOWN_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const KNOWN_MESSAGE: &str = "Some data";
+    const KNOWN_SIGNATURE: &str = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+
+    fn sign(key: &SigningKey, message: &str) -> String {
+        let (sig, recovery_id) = key.sign_prehash_recoverable(&eip191_hash(message.as_bytes())).unwrap();
+        let mut bytes = sig.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    fn address_of(key: &SigningKey) -> String {
+        let point = key.verifying_key().to_encoded_point(false);
+        format!("0x{}", hex::encode(&keccak256(&point.as_bytes()[1..])[12..]))
+    }
+
+    #[test]
+    fn eip55_checksum_vectors() {
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(checksum_address(&expected.to_ascii_lowercase()).unwrap(), expected);
+            assert_eq!(normalize_address(expected).unwrap(), expected.to_ascii_lowercase());
+        }
+        assert_eq!(
+            normalize_address(" 0X5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED ").unwrap(),
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        );
+        for bad in [
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg",
+        ] {
+            assert_eq!(normalize_address(bad), Err(WalletError::InvalidAddress));
+        }
+    }
+
+    #[test]
+    fn verify_known_personal_sign_vector() {
+        assert_eq!(verify_personal_sign(KNOWN_MESSAGE, KNOWN_SIGNATURE, KNOWN_ADDRESS), Ok(()));
+        assert_eq!(
+            recover_address(&eip191_hash(KNOWN_MESSAGE.as_bytes()), KNOWN_SIGNATURE).unwrap(),
+            KNOWN_ADDRESS.to_ascii_lowercase()
+        );
+        assert_eq!(
+            verify_personal_sign("Some other data", KNOWN_SIGNATURE, KNOWN_ADDRESS),
+            Err(WalletError::AddressMismatch)
+        );
+    }
+
+    #[test]
+    fn recover_address_accepts_both_v_forms() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let signature = sign(&key, "hello");
+        let prehash = eip191_hash(b"hello");
+        assert_eq!(recover_address(&prehash, &signature).unwrap(), address_of(&key));
+
+        // v 取 0/1 与 27/28 等价
+        let mut bytes = hex::decode(&signature[2..]).unwrap();
+        bytes[64] -= 27;
+        assert_eq!(recover_address(&prehash, &hex::encode(&bytes)).unwrap(), address_of(&key));
+
+        bytes[64] = 29;
+        assert_eq!(recover_address(&prehash, &hex::encode(&bytes)), Err(WalletError::InvalidSignature));
+        assert_eq!(recover_address(&prehash, &signature[..128]), Err(WalletError::InvalidSignature));
+        assert_eq!(recover_address(&prehash, "0xzz"), Err(WalletError::InvalidSignature));
+    }
+
+    #[test]
+    fn high_s_signature_is_rejected() {
+        let bytes = hex::decode(&KNOWN_SIGNATURE[2..]).unwrap();
+        let sig = Signature::from_slice(&bytes[..64]).unwrap();
+        let (r, s) = sig.split_scalars();
+        // (r, n - s) 同样是有效签名，但 EIP-2 只接受 low-s
+        let high = Signature::from_scalars(r.to_bytes(), (-*s).to_bytes()).unwrap();
+        let mut malleated = high.to_bytes().to_vec();
+        malleated.push(bytes[64] ^ 1);
+        assert_eq!(
+            verify_personal_sign(KNOWN_MESSAGE, &hex::encode(malleated), KNOWN_ADDRESS),
+            Err(WalletError::HighS)
+        );
+    }
+
+    #[test]
+    fn challenge_is_single_use_bound_to_user_and_expires() {
+        let now = Utc::now();
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let address = KNOWN_ADDRESS.to_ascii_lowercase();
+        let mut registry = WalletRegistry::default();
+
+        let challenge = registry.issue_challenge(user, address.clone(), "n1".into(), now);
+        assert!(challenge.message.contains(KNOWN_ADDRESS));
+        assert_eq!(registry.take_challenge("n1", other, now).unwrap_err(), WalletError::UnknownChallenge);
+        assert_eq!(registry.take_challenge("n1", user, now).unwrap().address, address);
+        assert_eq!(registry.take_challenge("n1", user, now).unwrap_err(), WalletError::UnknownChallenge);
+
+        registry.issue_challenge(user, address, "n2".into(), now);
+        let expired = now + chrono::Duration::minutes(WALLET_CHALLENGE_TTL_MINUTES);
+        assert_eq!(registry.take_challenge("n2", user, expired).unwrap_err(), WalletError::ChallengeExpired);
+        assert_eq!(registry.take_challenge("n2", user, expired).unwrap_err(), WalletError::UnknownChallenge);
+    }
+}